@@ -0,0 +1,80 @@
+/// This module archives posts before risky edits. The archive is a gzipped tarball of the
+/// post directory, its build output excepted, named after the post and the time of the
+/// backup.
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::info;
+
+use crate::post::Post;
+use crate::utils::{create_path, list_files};
+
+/// The directory the archives are written to by default
+pub const BACKUP_DIR: &str = "backups";
+
+/// Archives the post into the given directory, `backups/` by default. Everything the post
+/// directory holds is archived but its `dist/` build output. Returns the path of the
+/// archive, once it was read back successfully.
+pub fn backup(post: &Post, output: Option<PathBuf>) -> Result<PathBuf, String> {
+    let output = output.unwrap_or_else(|| PathBuf::from(BACKUP_DIR));
+    create_path(&output)?;
+
+    let name = post
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "post".to_string());
+    let archive_path = output.join(format!(
+        "{name}-{}.tar.gz",
+        Utc::now().format("%Y%m%dT%H%M%S")
+    ));
+
+    info!(
+        "Archiving {} into {}",
+        post.path.display(),
+        archive_path.display()
+    );
+    let file = File::create(&archive_path)
+        .map_err(|e| format!("Failed to create {}: {e}", archive_path.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let dist = post.path.join("dist");
+    for file in list_files(&post.path)? {
+        if file.starts_with(&dist) {
+            continue;
+        }
+        let relative = file.strip_prefix(&post.path).unwrap_or(&file);
+        archive
+            .append_path_with_name(&file, Path::new(&name).join(relative))
+            .map_err(|e| format!("Failed to archive {}: {e}", file.display()))?;
+    }
+    archive
+        .into_inner()
+        .and_then(GzEncoder::finish)
+        .map_err(|e| format!("Failed to write {}: {e}", archive_path.display()))?;
+
+    let entries = archive_entries(&archive_path)?;
+    info!("Archived {} files", entries.len());
+    Ok(archive_path)
+}
+
+/// Returns the paths of the files stored in the given archive. The whole archive is read,
+/// so that a truncated or corrupted archive is reported.
+pub fn archive_entries(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+    let invalid = |e: io::Error| format!("The archive {} is invalid: {e}", path.display());
+    let mut entries = vec![];
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        entries.push(entry.path().map_err(invalid)?.to_path_buf());
+        io::copy(&mut entry, &mut io::sink()).map_err(invalid)?;
+    }
+    Ok(entries)
+}
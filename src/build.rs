@@ -0,0 +1,235 @@
+/// This module builds whole blogs at once, walking the post tree and building every
+/// post it finds into a single output tree.
+///
+/// A `.build-cache` file kept in the output directory records a hash of each post at
+/// its last successful build, so that unchanged posts are not rebuilt. The hash covers
+/// the posts its page links to too: its related posts and the posts published before
+/// and after it.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use sha2::{Digest, Sha256};
+
+use crate::post::{BuildOptions, PlannedWrite, Post};
+use crate::related::related_posts;
+use crate::utils::{find_posts, hex, list_files, normalize_path};
+
+/// The name of the build cache file, stored in the output directory
+pub const BUILD_CACHE: &str = ".build-cache";
+
+#[derive(Debug, Default)]
+/// The outcome of the build of several posts
+pub struct BuildSummary {
+    pub succeeded: Vec<PathBuf>,
+    /// The time taken by the build of each succeeded post, in the same order
+    pub durations: Vec<Duration>,
+    /// The posts that did not change since their last build, and the drafts unless they
+    /// are included
+    pub skipped: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, String)>,
+    /// In dry-run mode, the writes the builds of the posts would have made
+    pub planned: Vec<PlannedWrite>,
+}
+
+/// Computes a hash of the post at the given path, covering its content, its metadata
+/// and the list of its images.
+pub fn post_hash(path: &Path) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    hasher.update(Post::load(path)?.content_hash());
+    let metadata = fs::read(path.join("metadata.toml"))
+        .map_err(|e| format!("Failed to read metadata.toml: {e}"))?;
+    hasher.update(&metadata);
+
+    let images_path = path.join("images");
+    for image in list_files(&images_path)? {
+        let size = fs::metadata(&image).map_or(0, |metadata| metadata.len());
+        let relative = image.strip_prefix(&images_path).unwrap_or(&image);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update(size.to_le_bytes());
+    }
+
+    Ok(hex(&hasher.finalize()))
+}
+
+/// The title and URL of the posts published right before and right after a post
+type Neighbors = (Option<(String, String)>, Option<(String, String)>);
+
+/// Returns the chronological neighbors of the post at the given path among the published
+/// posts, sorted newest first. Drafts have none. The loaded posts have normalized paths,
+/// unlike those found under a relative root.
+fn neighbors(published: &[Post], path: &Path, base_url: &str, root: &Path) -> Neighbors {
+    let path = normalize_path(path);
+    let Some(index) = published.iter().position(|post| post.path == path) else {
+        return (None, None);
+    };
+    let link = |post: &Post| {
+        (
+            post.metadata.post.title.clone(),
+            post.url_with_base(base_url, root),
+        )
+    };
+    let previous = published.get(index + 1).map(link);
+    let next = index
+        .checked_sub(1)
+        .and_then(|index| published.get(index))
+        .map(link);
+    (previous, next)
+}
+
+/// Returns the entry of the post at the given path in the build cache: its hash, along
+/// with the posts linked to by the options it is built with, so that the post is rebuilt
+/// when they change, e.g. when a post is published after it.
+fn cache_entry(path: &Path, options: &BuildOptions) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    hasher.update(post_hash(path)?);
+    let links = options
+        .related
+        .iter()
+        .chain(&options.previous)
+        .chain(&options.next);
+    for (title, url) in links {
+        hasher.update(title);
+        hasher.update(url);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Reads the build cache of the given output directory, mapping the path of each post
+/// relative to the root of the blog to its hash. A missing cache is empty.
+fn read_cache(output: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(output.join(BUILD_CACHE))
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Writes the build cache into the given output directory.
+fn write_cache(output: &Path, cache: &BTreeMap<String, String>) -> Result<(), String> {
+    let content =
+        toml::to_string(cache).map_err(|e| format!("Failed to serialize build cache: {e}"))?;
+    fs::write(output.join(BUILD_CACHE), content)
+        .map_err(|e| format!("Failed to write build cache: {e}"))
+}
+
+impl fmt::Display for BuildSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, e) in &self.failed {
+            writeln!(f, "Failed to build {}: {e}", path.display())?;
+        }
+        write!(
+            f,
+            "{} posts built, {} skipped, {} failed",
+            self.succeeded.len(),
+            self.skipped.len(),
+            self.failed.len()
+        )
+    }
+}
+
+/// Builds every post found under the given root. The posts are built into the output
+/// directory of the options, or `<root>/dist` if none is set. A failing post does not
+/// stop the build of the others, unless the options fail fast. Posts that did not change
+/// since their last build are skipped, unless the build is forced. Drafts are skipped too,
+/// unless they are included, in which case they are built into the drafts directory. Each
+/// post lists the posts related to it, among all the posts of the blog, and links to the
+/// posts published right before and after it.
+pub fn build_all(root: &Path, options: &BuildOptions) -> Result<BuildSummary, String> {
+    let output = options.output.clone().unwrap_or_else(|| root.join("dist"));
+    let options = BuildOptions {
+        output: Some(output.clone()),
+        root: Some(root.to_path_buf()),
+        ..options.clone()
+    };
+
+    let posts = if options.related_count > 0 {
+        Post::load_all(root)?
+    } else {
+        vec![]
+    };
+
+    let published = Post::load_published(root)?;
+
+    let mut cache = read_cache(&output);
+    let mut summary = BuildSummary::default();
+    for path in find_posts(root)? {
+        let key = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+        let post = Post::load(&path);
+        let draft = post
+            .as_ref()
+            .is_ok_and(|post| post.metadata.post.is_draft());
+        if draft && !options.include_drafts {
+            info!("Skipping draft {}", path.display());
+            summary.skipped.push(path);
+            continue;
+        }
+        let related = match &post {
+            Ok(post) => related_posts(post, &posts, options.related_count)
+                .into_iter()
+                .map(|related| {
+                    let title = related.metadata.post.title.clone();
+                    (title, related.url_with_base(&options.base_url, root))
+                })
+                .collect(),
+            Err(_) => vec![],
+        };
+        let (previous, next) = neighbors(&published, &path, &options.base_url, root);
+        let post_options = BuildOptions {
+            related,
+            previous,
+            next,
+            ..options.clone()
+        };
+        // A post that cannot be hashed is built anyway, for its error to be reported
+        let unchanged =
+            cache_entry(&path, &post_options).is_ok_and(|entry| cache.get(&key) == Some(&entry));
+        if !options.force && unchanged {
+            info!("Skipping unchanged post {}", path.display());
+            summary.skipped.push(path);
+            continue;
+        }
+
+        info!("Building post {}", path.display());
+        let start = Instant::now();
+        let built = post.and_then(|mut post| post.build(&post_options));
+        match built {
+            Ok(built) => {
+                summary.planned.extend(built.planned);
+                if !options.dry_run {
+                    // The hash is computed after the build, which updates the metadata
+                    match cache_entry(&path, &post_options) {
+                        Ok(entry) => {
+                            cache.insert(key, entry);
+                        }
+                        Err(e) => {
+                            warn!("Unable to cache the build of {}: {e}", path.display());
+                            cache.remove(&key);
+                        }
+                    }
+                }
+                summary.succeeded.push(path);
+                summary.durations.push(start.elapsed());
+            }
+            Err(e) => {
+                error!("Failed to build post {}: {e}", path.display());
+                cache.remove(&key);
+                summary.failed.push((path, e));
+                if options.fail_fast {
+                    break;
+                }
+            }
+        }
+    }
+
+    if !options.dry_run && output.is_dir() {
+        write_cache(&output, &cache)?;
+    }
+    Ok(summary)
+}
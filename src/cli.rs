@@ -1,10 +1,34 @@
-use clap::Parser;
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+
+use blog::compress::CompressionFormat;
+use blog::config::PublishBackend;
+use blog::export::PdfTool;
+use blog::post::TagPosition;
+use blog::render::{CssMode, Flavor, MathMode};
 
 #[derive(Parser)]
 #[clap(name = "blog")]
 #[clap(version)]
 /// A CLI blog post manager
 pub struct Cli {
+    #[clap(long, global = true)]
+    /// Prints the output of the command as JSON, when supported
+    pub json: bool,
+    #[clap(long, global = true)]
+    /// The level of the logged messages (off, error, warn, info, debug or trace),
+    /// overriding RUST_LOG
+    pub log_level: Option<log::LevelFilter>,
+    #[clap(long, global = true)]
+    /// Also appends the logged messages to the given file
+    pub log_file: Option<String>,
+    #[clap(short, long, global = true)]
+    /// Hides the progress bars of the downloads. The other output is still printed
+    pub quiet: bool,
+    #[clap(long, global = true)]
+    /// The env file to load the environment variables (e.g. the API keys) from, instead of
+    /// `.env.<BLOG_ENV>` or `.env`
+    pub env_file: Option<String>,
     #[clap(subcommand)]
     pub subcmd: SubCommand,
 }
@@ -13,28 +37,390 @@ pub struct Cli {
 pub enum SubCommand {
     #[clap(name = "new")]
     /// Creates a new blog post with the given title
-    New { title: String },
+    New {
+        #[clap(required_unless_present = "interactive")]
+        title: Option<String>,
+        #[clap(short, long)]
+        /// Prompts for the title, author, description, tags and keywords of the post
+        interactive: bool,
+        #[clap(long)]
+        /// Overwrites the post already existing at the path of the new one
+        force: bool,
+    },
     #[clap(name = "build")]
     /// Builds the blog post (fetches header images, generates index.html, etc.)
-    Build { path: String },
+    Build(Build),
+    #[clap(name = "delete")]
+    /// Deletes the blog post, after asking for confirmation
+    Delete {
+        path: String,
+        #[clap(long)]
+        /// Deletes the post without asking for confirmation
+        yes: bool,
+    },
+    #[clap(name = "repair")]
+    /// Regenerates a minimal metadata file for a post whose metadata is corrupt,
+    /// backing up the broken file
+    Repair { path: String },
+    #[clap(name = "import")]
+    /// Creates a blog post from an existing markdown file, reading its front matter
+    /// and copying the local images it references
+    Import {
+        file: String,
+        #[clap(short, long)]
+        /// The title of the post, instead of the one of the front matter, the first
+        /// heading or the name of the file
+        title: Option<String>,
+        #[clap(short, long, default_value = ".")]
+        /// The directory the post is created in
+        root: String,
+    },
+    #[clap(name = "backup")]
+    /// Archives a post, its build output excepted, into a timestamped tarball
+    Backup {
+        path: String,
+        #[clap(short, long)]
+        /// The directory to write the archive to, defaults to `backups/`
+        output: Option<String>,
+    },
     #[clap(name = "publish")]
-    /// Publishes the blog post (Not implemented yet, missing remote handler)
-    Publish { path: String },
+    /// Publishes the built blog post to a remote location
+    Publish {
+        #[clap(required_unless_present = "due", conflicts_with = "due")]
+        path: Option<String>,
+        #[clap(long, value_enum)]
+        /// The backend to publish the post with, overrides the configuration
+        backend: Option<PublishBackend>,
+        #[clap(long)]
+        /// Publishes every draft found under `--root` whose scheduled date has passed
+        due: bool,
+        #[clap(long, default_value = ".", requires = "due")]
+        /// The directory searched for the due drafts
+        root: String,
+        #[clap(long)]
+        /// The directory the posts are built into, as given to `build --output`. The due
+        /// drafts are built there before being published
+        output: Option<String>,
+    },
+    #[clap(name = "unpublish")]
+    /// Reverts a post to a draft, removing its published output when a backend is given or
+    /// configured
+    Unpublish {
+        path: String,
+        #[clap(long)]
+        /// Keeps the publication date of the post, used again when it is published back
+        keep_date: bool,
+        #[clap(long, value_enum)]
+        /// The backend the post was published with, overrides the configuration
+        backend: Option<PublishBackend>,
+    },
+    #[clap(name = "schedule")]
+    /// Schedules the publication of a draft, by `publish --due`
+    Schedule {
+        path: String,
+        #[clap(value_parser = blog::utils::parse_date)]
+        /// The date the post is due, written as `YYYY-MM-DD` or RFC 3339
+        date: DateTime<Utc>,
+    },
     #[clap(name = "tag")]
     /// Manages tags for a blog post
     Tag(Tag),
     #[clap(name = "keyword")]
     /// Manages keywords for a blog post
     Keyword(Keyword),
+    #[clap(name = "set")]
+    /// Sets a field of the metadata of a blog post
+    Set {
+        path: String,
+        #[clap(subcommand)]
+        field: SetField,
+    },
+    #[clap(name = "author")]
+    /// Manages the authors of a blog post
+    Author(Author),
+    #[clap(name = "content", subcommand)]
+    /// Edits the content of a blog post without opening an editor
+    Content(ContentSubCommand),
     #[clap(name = "header")]
     /// Manages header image for a blog post
     Header(Header),
+    #[clap(name = "images")]
+    /// Manages the images of a blog post
+    Images(Images),
+    #[clap(name = "export")]
+    /// Exports the content of the blog post to another format
+    Export {
+        path: String,
+        #[clap(value_enum)]
+        /// The format to export the post to
+        format: ExportFormat,
+        #[clap(long)]
+        /// The file to write the export to, defaults to `export.<ext>` in the post directory
+        output: Option<String>,
+        #[clap(long, value_enum, default_value = "chromium")]
+        /// The tool converting the page of the post to PDF
+        pdf_tool: PdfTool,
+        #[clap(long)]
+        /// The path to the PDF conversion tool, if it is not in the PATH
+        pdf_binary: Option<String>,
+    },
+    #[clap(name = "feed")]
+    /// Generates a syndication feed of the published posts found under the given directory
+    Feed(Feed),
+    #[clap(name = "tag-pages")]
+    /// Generates a page per tag listing the published posts carrying it
+    TagPages { root: String, output: String },
+    #[clap(name = "archives")]
+    /// Generates a page per year and per month of publication listing the published posts
+    Archives { root: String, output: String },
+    #[clap(name = "open")]
+    /// Opens the built page of the post in the default browser
+    Open {
+        path: String,
+        #[clap(long)]
+        /// Builds the post first
+        build: bool,
+    },
+    #[clap(name = "diff")]
+    /// Prints the changes of the content of the post since its last build, as a unified diff
+    Diff {
+        path: String,
+        #[clap(long)]
+        /// The directory the post was built into, as given to `build --output`
+        output: Option<String>,
+    },
+    #[clap(name = "robots")]
+    /// Writes the `robots.txt` file of the site into the output directory
+    Robots { output: String },
+    #[clap(name = "index")]
+    /// Generates the landing page of the blog, listing all the published posts
+    Index {
+        root: String,
+        output: String,
+        #[clap(long)]
+        /// Groups the posts by year of publication
+        group_by_year: bool,
+        #[clap(flatten)]
+        filter: Filter,
+    },
+    #[clap(name = "list")]
+    /// Lists the posts found under the given directory, newest first, drafts last
+    List {
+        root: String,
+        #[clap(flatten)]
+        filter: Filter,
+    },
+    #[clap(name = "stats")]
+    /// Prints statistics about all the posts found under the given directory
+    Stats { root: String },
+    #[clap(name = "taxonomy")]
+    /// Lists the tags and keywords of the posts found under the given directory with their
+    /// number of uses, flagging those used once
+    Taxonomy {
+        root: String,
+        #[clap(long)]
+        /// Only lists the tags
+        tags: bool,
+        #[clap(long)]
+        /// Only lists the keywords
+        keywords: bool,
+    },
+    #[clap(name = "search", alias = "grep")]
+    /// Searches the content, title, tags and keywords of the posts found under the given
+    /// directory
+    Search(Search),
+    #[clap(name = "search-index")]
+    /// Generates the JSON search index of the published posts found under the given
+    /// directory, for client-side search
+    SearchIndex {
+        root: String,
+        /// The file to write the index to
+        output: String,
+        #[clap(long)]
+        /// Truncates the body of each entry to the given number of characters
+        max_chars: Option<usize>,
+    },
+    #[clap(name = "lint")]
+    /// Checks the post for common quality issues: skipped heading levels, duplicate
+    /// headings, images without alternative text, missing keywords or description
+    Lint { path: String },
+    #[clap(name = "spell")]
+    /// Checks the spelling of the prose of the post against a dictionary and the wordlist of
+    /// the project, reporting the unknown words with their line
+    Spell {
+        path: String,
+        #[clap(long)]
+        /// Adds the word to the wordlist of the project before checking, can be repeated
+        add: Vec<String>,
+        #[clap(long, default_value = blog::spell::DEFAULT_DICTIONARY)]
+        /// The dictionary to check the words against, one word per line
+        dictionary: String,
+        #[clap(long, default_value = blog::spell::WORDLIST)]
+        /// The wordlist listing the terms allowed in the posts of the project
+        wordlist: String,
+    },
+    #[clap(name = "check-links")]
+    /// Checks that the links of the post point to existing files
+    CheckLinks {
+        path: String,
+        #[clap(long)]
+        /// Also checks that the external links respond
+        external: bool,
+    },
+    #[clap(name = "validate-links")]
+    /// Checks that the links between the posts found under the given directory point to
+    /// existing posts
+    ValidateLinks { root: String },
+    #[clap(name = "validate")]
+    /// Validates every post found under the given directory before a deploy: metadata,
+    /// content and referenced images
+    Validate {
+        root: String,
+        #[clap(flatten)]
+        batch: Batch,
+    },
+}
+
+#[derive(Parser)]
+pub struct Search {
+    /// The root of the blog
+    pub root: String,
+    /// The text to search for, case-insensitively
+    pub query: String,
+    #[clap(long)]
+    /// Interprets the query as a regular expression
+    pub regex: bool,
+    #[clap(long)]
+    /// Only searches the tags of the posts
+    pub tag_only: bool,
+    #[clap(long, default_value_t = 1)]
+    /// The number of lines of content shown around the matching lines
+    pub context: usize,
+}
+
+#[derive(Parser)]
+pub struct Build {
+    /// The path to the post, or to the root of the blog with `--all`
+    pub path: String,
+    #[clap(long)]
+    /// Builds every post found under the given directory
+    pub all: bool,
+    #[clap(flatten)]
+    pub batch: Batch,
+    #[clap(long)]
+    /// Rebuilds the posts that did not change since their last build with `--all`
+    pub force: bool,
+    #[clap(long)]
+    /// Renders the post and reports what would be written, without writing anything
+    pub dry_run: bool,
+    #[clap(long, conflicts_with = "all")]
+    /// Opens the built page in the default browser, unless `--dry-run` is passed
+    pub open: bool,
+    #[clap(long)]
+    /// Builds the post into `<OUTPUT>/<post path>/` instead of the post's `dist/` directory.
+    /// Defaults to `<PATH>/dist` with `--all`
+    pub output: Option<String>,
+    #[clap(long)]
+    /// Adds lazy-loading and asynchronous decoding to the images of the content
+    pub lazy_images: bool,
+    #[clap(long)]
+    /// Generates resized variants of the header image and serves it with a `srcset`
+    pub responsive_header: bool,
+    #[clap(long)]
+    /// Skips copying the images of the post, and generating the header variants, to
+    /// iterate on the prose faster. The page still references them
+    pub no_images: bool,
+    #[clap(long)]
+    /// Adds the Twitter card meta tags (always added when the post has a header image)
+    pub twitter: bool,
+    #[clap(long, value_enum, default_value = "gfm")]
+    /// The markdown flavor the content is written in: GitHub Flavored Markdown or strict
+    /// CommonMark, without tables, autolinks nor footnotes
+    pub flavor: Flavor,
+    #[clap(long, value_enum)]
+    /// Renders the `$...$` and `$$...$$` math of the content, in the browser with KaTeX
+    /// or as MathML while building
+    pub math: Option<MathMode>,
+    #[clap(long)]
+    /// Replaces the `:shortcode:` sequences of the content by the corresponding emoji
+    pub emoji: bool,
+    #[clap(long)]
+    /// Renders the title of the metadata above the header, removing the matching `# Title`
+    /// heading from the top of the content
+    pub title_from_meta: bool,
+    #[clap(long)]
+    /// Adds a table of contents of the headings above the content
+    pub toc: bool,
+    #[clap(long)]
+    /// Builds the drafts too with `--all`, into the drafts directory
+    pub include_drafts: bool,
+    #[clap(long)]
+    /// The directory the drafts are built into, instead of the `drafts/` directory of the
+    /// output
+    pub drafts_dir: Option<String>,
+    #[clap(long)]
+    /// Appends a `#` link to itself to each heading, to share links to the sections
+    pub heading_links: bool,
+    #[clap(long)]
+    /// Adds the microformats2 h-entry markup (title, content, author and publication
+    /// date) to the page, for IndieWeb readers
+    pub microformats: bool,
+    #[clap(long, default_value_t = 3)]
+    /// The number of related posts, sharing tags or keywords with the built post, listed
+    /// below the content with `--all`
+    pub related: usize,
+    #[clap(long)]
+    /// The URL the site is served at (e.g. `https://example.com/blog`), the links and
+    /// images of the page are made absolute under it. Defaults to the `site.base_url`
+    /// configuration
+    pub base_url: Option<String>,
+    #[clap(long)]
+    /// Fails the build of the posts missing a description or a header image, or with
+    /// broken links to other posts, instead of warning about them
+    pub strict: bool,
+    #[clap(long)]
+    /// Only warns when the pre-build or post-build hook of the configuration fails,
+    /// instead of failing the build
+    pub ignore_hook_errors: bool,
+    #[clap(long, conflicts_with = "dry_run")]
+    /// Writes a JSON manifest of the build to the given file: the files written for each
+    /// post with their size and SHA-256 hash, the options of the build and its duration
+    pub manifest: Option<String>,
+    #[clap(long, value_enum)]
+    /// Writes `.gz` and/or `.br` compressed siblings of the built HTML, CSS and JS files,
+    /// images and small files excepted
+    pub compress: Option<CompressionFormat>,
+    #[clap(long)]
+    /// The stylesheet of the pages, a minimal theme is used if omitted
+    pub css: Option<String>,
+    #[clap(long)]
+    /// The directory of the `template.html` wrapping the pages, which can include the
+    /// `<name>.html` partials of the directory with `{{> name}}`. The default template is
+    /// used if omitted
+    pub template_dir: Option<String>,
+    #[clap(long, value_enum, default_value = "inline")]
+    /// Whether the stylesheet is inlined in the pages or copied next to them and linked
+    pub css_mode: CssMode,
+    #[clap(long)]
+    /// The image copied next to the pages as their favicon. Defaults to the `site.favicon`
+    /// configuration
+    pub favicon: Option<String>,
+    #[clap(long)]
+    /// Generates 32x32 and 16x16 PNG variants of the favicon
+    pub resize_favicon: bool,
 }
 
 #[derive(Parser)]
+#[clap(subcommand_precedence_over_arg = true)]
 pub struct Tag {
-    /// The path to the post
-    pub post: String,
+    /// The paths to the posts
+    pub posts: Vec<String>,
+    #[clap(long)]
+    /// Applies the command to every post found under the given directory. Posts missing
+    /// the removed or renamed tags, or already carrying the added ones, are left alone
+    pub root: Option<String>,
+    #[clap(flatten)]
+    pub batch: Batch,
     #[clap(subcommand)]
     pub subcmd: TagSubCommand,
 }
@@ -47,15 +433,82 @@ pub enum TagSubCommand {
     #[clap(name = "remove")]
     /// Removes the space separated tags from the post
     Remove { tags: Vec<String> },
+    #[clap(name = "rename")]
+    /// Renames a tag of the post
+    Rename { from: String, to: String },
+    #[clap(name = "merge")]
+    /// Merges a tag into another one: the posts carrying the old tag get the new one
+    /// instead, without duplicates
+    Merge { old: String, new: String },
+    #[clap(name = "move")]
+    /// Moves a tag of the post to the given position: `first`, `last` or a 0-based index
+    Move { tag: String, position: TagPosition },
     #[clap(name = "list")]
     /// Lists the tags attached to the post
     List,
 }
 
 #[derive(Parser)]
-pub struct Keyword {
+pub enum SetField {
+    #[clap(name = "license")]
+    /// The license the post is published under: an SPDX identifier such as `CC-BY-4.0`,
+    /// linked to in the footer of the page when known, any other text, or `none`
+    License { license: String },
+}
+
+#[derive(Parser)]
+pub struct Author {
+    /// The path to the post
+    pub post: String,
+    #[clap(subcommand)]
+    pub subcmd: AuthorSubCommand,
+}
+
+#[derive(Parser)]
+pub enum AuthorSubCommand {
+    #[clap(name = "add")]
+    /// Adds the authors to the post, quote the names containing spaces
+    Add { authors: Vec<String> },
+    #[clap(name = "remove")]
+    /// Removes the authors from the post
+    Remove { authors: Vec<String> },
+    #[clap(name = "list")]
+    /// Lists the authors of the post
+    List,
+}
+
+#[derive(Parser)]
+pub enum ContentSubCommand {
+    #[clap(name = "append")]
+    /// Adds the text at the end of the content of the post, e.g. an update note
+    Append(ContentText),
+    #[clap(name = "prepend")]
+    /// Adds the text at the start of the content of the post
+    Prepend(ContentText),
+}
+
+#[derive(Parser)]
+pub struct ContentText {
     /// The path to the post
     pub post: String,
+    /// The markdown added to the content, read from the standard input if omitted
+    pub text: Option<String>,
+    #[clap(long, conflicts_with = "text")]
+    /// Reads the markdown added to the content from the given file
+    pub from_file: Option<String>,
+}
+
+#[derive(Parser)]
+#[clap(subcommand_precedence_over_arg = true)]
+pub struct Keyword {
+    /// The paths to the posts
+    pub posts: Vec<String>,
+    #[clap(long)]
+    /// Applies the command to every post found under the given directory. Posts missing
+    /// the removed keywords, or already carrying the added ones, are left alone
+    pub root: Option<String>,
+    #[clap(flatten)]
+    pub batch: Batch,
     #[clap(subcommand)]
     pub subcmd: KeywordSubCommand,
 }
@@ -71,6 +524,16 @@ pub enum KeywordSubCommand {
     #[clap(name = "list")]
     /// Lists the keywords attached to this post
     List,
+    #[clap(name = "suggest")]
+    /// Suggests keywords for the post, from the terms its prose uses the most
+    Suggest {
+        #[clap(long, default_value_t = 10)]
+        /// The number of terms to suggest
+        count: usize,
+        #[clap(long)]
+        /// Adds the given number of top suggestions as keywords of the post
+        apply: Option<usize>,
+    },
 }
 
 #[derive(Parser)]
@@ -88,8 +551,128 @@ pub enum HeaderSubCommand {
     Choose { index: usize },
     #[clap(name = "fetch")]
     /// Fetches header images from Pexel for the post
-    Fetch { amount: usize },
+    Fetch {
+        /// The number of images to fetch, `header.amount` of the configuration (10) by
+        /// default. Lowered to the maximum of the provider (80 on pexels, 30 on unsplash)
+        amount: Option<usize>,
+        #[clap(long, default_value_t = 1)]
+        /// The page of the search results to fetch, its candidates are numbered after
+        /// those of the previous pages
+        page: usize,
+        #[clap(long)]
+        /// Only fetch pictures of the given dominant color, a name (e.g. `blue`) or a
+        /// hexadecimal code (e.g. `#ffffff`)
+        color: Option<String>,
+        #[clap(long, default_value_t = 0)]
+        /// Drop the pictures narrower than this many pixels
+        min_width: usize,
+        #[clap(long, default_value_t = 0)]
+        /// Drop the pictures shorter than this many pixels
+        min_height: usize,
+        #[clap(long)]
+        /// Search each keyword separately and merge the results, which are often more
+        /// relevant than those of a search of all the keywords at once
+        per_keyword: bool,
+        #[clap(long)]
+        /// Only list the pictures found, without downloading them
+        dry_run: bool,
+    },
+    #[clap(name = "list")]
+    /// Lists the candidate header images of the post
+    List {
+        #[clap(long)]
+        /// Shows a preview of each candidate, when the output is a terminal
+        thumbnails: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct Images {
+    /// The path to the post
+    pub post: String,
+    #[clap(subcommand)]
+    pub subcmd: ImagesSubCommand,
+}
+
+#[derive(Parser)]
+pub enum ImagesSubCommand {
+    #[clap(name = "add")]
+    /// Copies the given image files into the post's images directory
+    Add {
+        files: Vec<String>,
+        #[clap(long)]
+        /// Slugifies the names of the copied images
+        slugify: bool,
+        #[clap(long)]
+        /// Copies the files even if they do not look like images, overwriting the images of
+        /// the same name
+        force: bool,
+    },
     #[clap(name = "list")]
-    /// Lists the header images paths for the post
+    /// Lists the images of the post
     List,
 }
+
+#[derive(Parser)]
+pub struct Feed {
+    /// The root directory of the blog
+    pub root: String,
+    #[clap(long, value_enum, default_value = "rss")]
+    /// The format of the feed
+    pub format: FeedFormat,
+    #[clap(long)]
+    /// The file to write the feed to, the feed is printed if omitted
+    pub output: Option<String>,
+    #[clap(flatten)]
+    pub filter: Filter,
+}
+
+#[derive(Parser)]
+/// How a command applied to several posts handles the failing ones
+pub struct Batch {
+    #[clap(long, conflicts_with = "fail_fast")]
+    /// Goes on with the other posts when one fails, and reports all the failures at the
+    /// end (the default)
+    pub keep_going: bool,
+    #[clap(long)]
+    /// Stops at the first failing post
+    pub fail_fast: bool,
+}
+
+#[derive(Parser)]
+/// Restricts a listing to the newest posts
+pub struct Filter {
+    #[clap(long)]
+    /// Keeps only the given number of newest posts
+    pub limit: Option<usize>,
+    #[clap(long, value_parser = blog::utils::parse_date)]
+    /// Keeps only the posts published on or after the date, as YYYY-MM-DD or RFC 3339
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl From<Filter> for blog::post::PostFilter {
+    fn from(filter: Filter) -> Self {
+        Self {
+            limit: filter.limit,
+            since: filter.since,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FeedFormat {
+    /// RSS 2.0
+    Rss,
+    /// Atom 1.0
+    Atom,
+    /// JSON Feed 1.1
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// Plain text, e.g. for newsletters
+    Text,
+    /// PDF, rendered by an external tool
+    Pdf,
+}
@@ -0,0 +1,102 @@
+/// This module pre-compresses the built text files for static hosting: each of them gets
+/// `.gz` and/or `.br` siblings, served by the web server to the clients accepting them.
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+
+use crate::utils::list_files;
+
+/// The files smaller than this number of bytes are not worth compressing
+pub const MIN_SIZE: u64 = 256;
+
+/// The extensions of the text files that are compressed
+const TEXT_EXTENSIONS: [&str; 8] = ["html", "css", "js", "json", "xml", "svg", "txt", "map"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// The compressed siblings written next to the built files
+pub enum CompressionFormat {
+    /// `.gz` files
+    Gzip,
+    /// `.br` files
+    Brotli,
+    /// Both `.gz` and `.br` files
+    Both,
+}
+
+impl CompressionFormat {
+    fn gzip(self) -> bool {
+        matches!(self, Self::Gzip | Self::Both)
+    }
+
+    fn brotli(self) -> bool {
+        matches!(self, Self::Brotli | Self::Both)
+    }
+}
+
+/// Returns whether the file is a text file worth compressing.
+fn is_compressible(path: &Path) -> bool {
+    let text = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| TEXT_EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+    text && fs::metadata(path).is_ok_and(|metadata| metadata.len() >= MIN_SIZE)
+}
+
+/// Returns the path of the file with the extension appended, e.g. `index.html.gz`.
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// Writes the gzipped copy of the file.
+fn gzip(path: &Path, content: &[u8]) -> Result<PathBuf, String> {
+    let output = sibling(path, "gz");
+    let file =
+        File::create(&output).map_err(|e| format!("Failed to create {}: {e}", output.display()))?;
+    let mut encoder = GzEncoder::new(file, Compression::best());
+    encoder
+        .write_all(content)
+        .and_then(|_| encoder.finish().map(|_| ()))
+        .map_err(|e| format!("Failed to write {}: {e}", output.display()))?;
+    Ok(output)
+}
+
+/// Writes the brotli compressed copy of the file.
+fn brotli(path: &Path, content: &[u8]) -> Result<PathBuf, String> {
+    let output = sibling(path, "br");
+    let mut file =
+        File::create(&output).map_err(|e| format!("Failed to create {}: {e}", output.display()))?;
+    let params = brotli::enc::BrotliEncoderParams::default();
+    brotli::BrotliCompress(&mut &content[..], &mut file, &params)
+        .map_err(|e| format!("Failed to write {}: {e}", output.display()))?;
+    Ok(output)
+}
+
+/// Writes the compressed siblings of the text files found in the directory. Images and
+/// files smaller than `MIN_SIZE` are left alone. Returns the paths of the written files.
+pub fn compress_dir(dir: &Path, format: CompressionFormat) -> Result<Vec<PathBuf>, String> {
+    let mut written = vec![];
+    for file in list_files(dir)? {
+        if !is_compressible(&file) {
+            continue;
+        }
+
+        let content =
+            fs::read(&file).map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+        if format.gzip() {
+            written.push(gzip(&file, &content)?);
+        }
+        if format.brotli() {
+            written.push(brotli(&file, &content)?);
+        }
+    }
+    Ok(written)
+}
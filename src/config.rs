@@ -0,0 +1,291 @@
+/// This module handles the blog-wide configuration, read from a `blog.toml` file
+/// located in the current directory. Every field is optional and falls back to a
+/// sensible default, so the tool keeps working without any configuration file.
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use log::info;
+use serde::{Deserialize, Serialize};
+use slugify::slugify;
+
+/// The name of the configuration file looked up in the current directory
+pub const CONFIG_FILE: &str = "blog.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+/// The blog configuration
+pub struct Config {
+    pub site: SiteConfig,
+    pub posts: PostsConfig,
+    pub display: DisplayConfig,
+    pub markdown: MarkdownConfig,
+    pub header: HeaderConfig,
+    pub publish: PublishConfig,
+    pub robots: RobotsConfig,
+    pub hooks: HooksConfig,
+    pub slug: SlugConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+/// Information about the site the posts are published on
+pub struct SiteConfig {
+    pub title: String,
+    pub description: String,
+    pub author: String,
+    pub base_url: String,
+    /// The image used as the favicon of the pages, none if empty
+    pub favicon: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+/// The settings of the posts themselves
+pub struct PostsConfig {
+    /// The directory of the new posts, made of the `{year}`, `{month}`, `{day}` and
+    /// `{slug}` tokens
+    pub path_format: String,
+    /// The maximum number of tags of a post, zero means unlimited
+    pub max_tags: usize,
+    /// The maximum number of keywords of a post, zero means unlimited
+    pub max_keywords: usize,
+    /// The average reading speed used to estimate the reading time of the posts
+    pub words_per_minute: usize,
+}
+
+impl Default for PostsConfig {
+    fn default() -> Self {
+        Self {
+            path_format: "{year}/{month}/{slug}".to_string(),
+            max_tags: 0,
+            max_keywords: 0,
+            words_per_minute: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+/// How the dates are displayed. They are always stored in UTC.
+pub struct DisplayConfig {
+    /// The IANA name of the timezone the dates are displayed in, e.g. `Europe/Brussels`
+    pub timezone: Tz,
+    /// The `strftime`-like format of the displayed dates
+    pub date_format: String,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            timezone: Tz::UTC,
+            date_format: "%Y-%m-%d".to_string(),
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// Converts the date to the display timezone.
+    pub fn localize(&self, date: DateTime<Utc>) -> DateTime<Tz> {
+        date.with_timezone(&self.timezone)
+    }
+
+    /// Formats the date in the display timezone, with the display format.
+    pub fn format(&self, date: DateTime<Utc>) -> String {
+        self.localize(date).format(&self.date_format).to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+/// The markdown constructs enabled when rendering the posts, on top of GitHub Flavored
+/// Markdown
+pub struct MarkdownConfig {
+    /// Renders footnote references and definitions, with links back to the references
+    pub footnotes: bool,
+    /// The heading of the footnotes section
+    pub footnote_label: String,
+    /// The label of the links going back from a footnote to its reference
+    pub footnote_back_label: String,
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        Self {
+            footnotes: true,
+            footnote_label: "Footnotes".to_string(),
+            footnote_back_label: "Back to content".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+/// The settings of the header images of the posts
+pub struct HeaderConfig {
+    /// The providers the header images are searched on, in order. The next provider is
+    /// tried when one fails or finds nothing.
+    pub providers: Vec<ImageProviderKind>,
+    /// The number of images fetched when `header fetch` is given no amount
+    pub amount: usize,
+}
+
+impl Default for HeaderConfig {
+    fn default() -> Self {
+        Self {
+            providers: vec![ImageProviderKind::Pexels],
+            amount: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The sources of header images
+pub enum ImageProviderKind {
+    /// pexels.com, with the `PEXEL_API_KEY` environment variable
+    Pexels,
+    /// unsplash.com, with the `UNSPLASH_ACCESS_KEY` environment variable
+    Unsplash,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+/// The way the titles of the posts and the tags are turned into the slugs of their paths
+pub struct SlugConfig {
+    /// Keeps the non-ASCII letters and digits, lowercased, instead of transliterating them
+    /// to ASCII (`Café` becomes `café` rather than `cafe`)
+    pub unicode: bool,
+    /// The character separating the words of the slugs
+    pub separator: char,
+}
+
+impl Default for SlugConfig {
+    fn default() -> Self {
+        Self {
+            unicode: false,
+            separator: '-',
+        }
+    }
+}
+
+impl SlugConfig {
+    /// Turns the text into a slug: its lowercased letters and digits, the runs of other
+    /// characters replaced by a single separator.
+    pub fn slugify(&self, text: &str) -> String {
+        let separator = self.separator.to_string();
+        if !self.unicode {
+            return slugify!(text).replace('-', &separator);
+        }
+
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(&separator)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+/// The rules of the `robots.txt` file of the site
+pub struct RobotsConfig {
+    /// The crawlers the rules apply to, `*` for all of them
+    pub user_agent: String,
+    /// The paths the crawlers may index
+    pub allow: Vec<String>,
+    /// The paths the crawlers may not index
+    pub disallow: Vec<String>,
+    /// The path of the sitemap, relative to the base URL of the site
+    pub sitemap: String,
+}
+
+impl Default for RobotsConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: "*".to_string(),
+            allow: vec!["/".to_string()],
+            disallow: vec![],
+            sitemap: "sitemap.xml".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+/// The shell commands run around the build of each post, with the paths of the post and
+/// of its output in the `BLOG_POST_PATH` and `BLOG_OUTPUT_PATH` environment variables
+pub struct HooksConfig {
+    /// Run before the post is built
+    pub pre_build: Option<String>,
+    /// Run once the post is built
+    pub post_build: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+/// The settings of the publication of the posts
+pub struct PublishConfig {
+    /// The backend used when none is given on the command line
+    pub backend: Option<PublishBackend>,
+    /// The `Cache-Control` header set on the uploaded files
+    pub cache_control: String,
+    pub git: GitPublishConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+/// The settings of the git publish backend
+pub struct GitPublishConfig {
+    /// The local clone of the repository the posts are published to
+    pub repo: String,
+    /// The branch pushed to the remote
+    pub branch: String,
+    pub remote: String,
+    /// The commit message, `{title}` is replaced by the title of the post
+    pub message: String,
+}
+
+impl Default for GitPublishConfig {
+    fn default() -> Self {
+        Self {
+            repo: String::new(),
+            branch: "main".to_string(),
+            remote: "origin".to_string(),
+            message: "Publish: {title}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// The remote locations posts can be published to
+pub enum PublishBackend {
+    /// An S3-compatible bucket
+    S3,
+    /// A git repository, committed to and pushed
+    Git,
+}
+
+impl Config {
+    /// Loads the configuration from the `blog.toml` file in the current directory.
+    /// Returns the default configuration if the file does not exist.
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(Path::new(CONFIG_FILE))
+    }
+
+    /// Loads the configuration from the given file, or the default configuration if
+    /// the file does not exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("Loading configuration from {}", path.display());
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read configuration file: {e}"))?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse configuration file: {e}"))
+    }
+}
@@ -0,0 +1,211 @@
+/// This module exports the content of the posts to other formats, such as plain text
+/// for newsletters. The text conversion works on the markdown syntax tree of the content
+/// and does not touch the disk. The PDF export renders the post's page and hands it to
+/// an external converter, such as a headless browser.
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::ValueEnum;
+use log::info;
+use markdown::mdast::Node;
+
+use crate::post::{BuildOptions, Post};
+
+/// Converts the given markdown to readable plain text: headings become uppercase
+/// lines, list items are prefixed with `- `, links are written as `text (url)` and
+/// code blocks are indented.
+pub fn to_plain_text(content: &str) -> Result<String, String> {
+    let tree = markdown::to_mdast(content, &markdown::ParseOptions::gfm())
+        .map_err(|e| format!("Failed to parse content: {e}"))?;
+
+    let mut text = blocks(&tree, "");
+    text.push('\n');
+    Ok(text)
+}
+
+/// Renders the block children of the node, separated by blank lines. Every line is
+/// prefixed with the given indentation.
+fn blocks(node: &Node, indent: &str) -> String {
+    node.children()
+        .map(|children| {
+            children
+                .iter()
+                .map(|child| block(child, indent))
+                .filter(|block| !block.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .unwrap_or_default()
+}
+
+/// Prefixes each line of the text with the given string.
+fn prefix_lines(text: &str, prefix: &str) -> String {
+    text.lines()
+        .map(|line| format!("{prefix}{line}").trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders a block node.
+fn block(node: &Node, indent: &str) -> String {
+    match node {
+        Node::Heading(_) => prefix_lines(&inline_children(node).to_uppercase(), indent),
+        Node::Code(code) => prefix_lines(&code.value, &format!("{indent}    ")),
+        Node::List(_) => node
+            .children()
+            .into_iter()
+            .flatten()
+            .map(|item| {
+                let content = blocks(item, "");
+                let mut lines = content.lines();
+                let first = lines.next().unwrap_or_default();
+                let rest = prefix_lines(&lines.collect::<Vec<_>>().join("\n"), "  ");
+                let item = if rest.is_empty() {
+                    format!("- {first}")
+                } else {
+                    format!("- {first}\n{rest}")
+                };
+                prefix_lines(&item, indent)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Node::Blockquote(_) => prefix_lines(&blocks(node, ""), &format!("{indent}> ")),
+        Node::ThematicBreak(_) => format!("{indent}----"),
+        Node::Table(_) => node
+            .children()
+            .into_iter()
+            .flatten()
+            .map(|row| {
+                let cells = row
+                    .children()
+                    .into_iter()
+                    .flatten()
+                    .map(inline_children)
+                    .collect::<Vec<_>>();
+                format!("{indent}{}", cells.join(" | "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Node::Html(_) | Node::Definition(_) | Node::Yaml(_) | Node::Toml(_) => String::new(),
+        Node::Paragraph(_) => prefix_lines(&inline_children(node), indent),
+        _ if node.children().is_some() => blocks(node, indent),
+        _ => prefix_lines(&inline(node), indent),
+    }
+}
+
+/// Renders the inline children of the node.
+fn inline_children(node: &Node) -> String {
+    node.children()
+        .map(|children| children.iter().map(inline).collect())
+        .unwrap_or_default()
+}
+
+/// Renders an inline node.
+fn inline(node: &Node) -> String {
+    match node {
+        Node::Text(text) => text.value.clone(),
+        Node::InlineCode(code) => code.value.clone(),
+        Node::InlineMath(math) => math.value.clone(),
+        Node::Break(_) => "\n".to_string(),
+        Node::Image(image) => image.alt.clone(),
+        Node::Link(link) => {
+            let text = inline_children(node);
+            if text.is_empty() || text == link.url {
+                link.url.clone()
+            } else {
+                format!("{text} ({})", link.url)
+            }
+        }
+        Node::Html(_) => String::new(),
+        _ => inline_children(node),
+    }
+}
+
+/// Converts an HTML file to a PDF document
+pub trait PdfConverter {
+    fn convert(&self, html: &Path, pdf: &Path) -> Result<(), String>;
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+/// The external tools able to convert HTML to PDF
+pub enum PdfTool {
+    /// Chromium (or Chrome) in headless mode
+    Chromium,
+    /// wkhtmltopdf
+    Wkhtmltopdf,
+}
+
+/// Converts HTML to PDF by running an external tool
+pub struct CommandConverter {
+    pub tool: PdfTool,
+    /// The program to run
+    pub binary: String,
+}
+
+impl CommandConverter {
+    /// Creates a converter running the given tool, optionally from a custom binary.
+    pub fn new(tool: PdfTool, binary: Option<String>) -> Self {
+        let binary = binary.unwrap_or_else(|| {
+            match tool {
+                PdfTool::Chromium => "chromium",
+                PdfTool::Wkhtmltopdf => "wkhtmltopdf",
+            }
+            .to_string()
+        });
+        Self { tool, binary }
+    }
+}
+
+impl PdfConverter for CommandConverter {
+    fn convert(&self, html: &Path, pdf: &Path) -> Result<(), String> {
+        let mut command = Command::new(&self.binary);
+        match self.tool {
+            PdfTool::Chromium => command
+                .arg("--headless")
+                .arg("--disable-gpu")
+                .arg(format!("--print-to-pdf={}", pdf.display()))
+                .arg(format!("file://{}", html.display())),
+            PdfTool::Wkhtmltopdf => command.arg("--enable-local-file-access").arg(html).arg(pdf),
+        };
+
+        info!("Converting {} to {}", html.display(), pdf.display());
+        let output = command.output().map_err(|e| match e.kind() {
+            ErrorKind::NotFound => format!(
+                "`{}` was not found, install it or point --pdf-binary to it",
+                self.binary
+            ),
+            _ => format!("Failed to run `{}`: {e}", self.binary),
+        })?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "`{}` failed: {}",
+                self.binary,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+/// Exports the post to a PDF document at the given path, defaulting to `export.pdf`
+/// in the post directory. The page of the post is rendered next to its content, so
+/// that the images resolve, then converted. Returns the path of the document.
+pub fn to_pdf(
+    post: &Post,
+    converter: &dyn PdfConverter,
+    output: Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    let page = post.render_page(&BuildOptions::default())?;
+    let html = post.path.join(".export.html");
+    fs::write(&html, page).map_err(|e| format!("Failed to write the page: {e}"))?;
+
+    let pdf = output.unwrap_or_else(|| post.path.join("export.pdf"));
+    let result = converter.convert(&html, &pdf);
+    fs::remove_file(&html).map_err(|e| format!("Failed to remove the page: {e}"))?;
+
+    result.map(|()| pdf)
+}
@@ -0,0 +1,186 @@
+/// This module generates the syndication feeds of the blog.
+/// The RSS 2.0, Atom 1.0 and JSON Feed 1.1 documents are all produced from the same list of
+/// posts, gathered by walking the post tree and discarding the drafts, so that the
+/// formats always expose the same entries.
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::post::{BuildOptions, Post};
+use crate::render;
+use crate::utils::escape_xml;
+
+/// Returns the publication and last update dates of a post, in the display timezone. A
+/// post that was never updated is considered updated when it was published.
+fn post_dates(post: &Post, config: &Config) -> (DateTime<Tz>, DateTime<Tz>) {
+    let published = post.metadata.post.published_date.unwrap_or_default();
+    let updated = post.metadata.post.update.unwrap_or(published);
+    (
+        config.display.localize(published),
+        config.display.localize(updated),
+    )
+}
+
+/// Generates an RSS 2.0 document listing the given posts.
+pub fn rss(posts: &[Post], root: &Path, config: &Config) -> String {
+    let site = &config.site;
+    let mut feed = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    feed.push_str("<rss version=\"2.0\">\n  <channel>\n");
+    feed.push_str(&format!("    <title>{}</title>\n", escape_xml(&site.title)));
+    feed.push_str(&format!(
+        "    <link>{}</link>\n",
+        escape_xml(&site.base_url)
+    ));
+    feed.push_str(&format!(
+        "    <description>{}</description>\n",
+        escape_xml(&site.description)
+    ));
+
+    for post in posts {
+        let url = escape_xml(&post.url(config, root));
+        let (published, _) = post_dates(post, config);
+
+        feed.push_str("    <item>\n");
+        feed.push_str(&format!(
+            "      <title>{}</title>\n",
+            escape_xml(&post.metadata.post.title)
+        ));
+        feed.push_str(&format!("      <link>{url}</link>\n"));
+        feed.push_str(&format!("      <guid>{url}</guid>\n"));
+        feed.push_str(&format!(
+            "      <pubDate>{}</pubDate>\n",
+            published.to_rfc2822()
+        ));
+        feed.push_str(&format!(
+            "      <description>{}</description>\n",
+            escape_xml(&post.metadata.opengraph.description)
+        ));
+        for tag in &post.metadata.post.tags {
+            feed.push_str(&format!("      <category>{}</category>\n", escape_xml(tag)));
+        }
+        feed.push_str("    </item>\n");
+    }
+
+    feed.push_str("  </channel>\n</rss>\n");
+    feed
+}
+
+/// Generates an Atom 1.0 document listing the given posts.
+pub fn atom(posts: &[Post], root: &Path, config: &Config) -> String {
+    let site = &config.site;
+    let home = escape_xml(&site.base_url);
+    let last_update = posts
+        .iter()
+        .map(|post| post_dates(post, config).1)
+        .max()
+        .unwrap_or_else(|| config.display.localize(Utc::now()));
+
+    let mut feed = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    feed.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    feed.push_str(&format!("  <id>{home}</id>\n"));
+    feed.push_str(&format!("  <title>{}</title>\n", escape_xml(&site.title)));
+    feed.push_str(&format!(
+        "  <updated>{}</updated>\n",
+        last_update.to_rfc3339()
+    ));
+    feed.push_str(&format!("  <link href=\"{home}\"/>\n"));
+    if !site.author.is_empty() {
+        feed.push_str(&format!(
+            "  <author><name>{}</name></author>\n",
+            escape_xml(&site.author)
+        ));
+    }
+
+    for post in posts {
+        let url = escape_xml(&post.url(config, root));
+        let (published, updated) = post_dates(post, config);
+
+        feed.push_str("  <entry>\n");
+        feed.push_str(&format!("    <id>{url}</id>\n"));
+        feed.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape_xml(&post.metadata.post.title)
+        ));
+        feed.push_str(&format!("    <link href=\"{url}\"/>\n"));
+        feed.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            updated.to_rfc3339()
+        ));
+        feed.push_str(&format!(
+            "    <published>{}</published>\n",
+            published.to_rfc3339()
+        ));
+        for author in &post.metadata.post.authors {
+            feed.push_str(&format!(
+                "    <author><name>{}</name></author>\n",
+                escape_xml(author)
+            ));
+        }
+        feed.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            escape_xml(&post.metadata.opengraph.description)
+        ));
+        feed.push_str("  </entry>\n");
+    }
+
+    feed.push_str("</feed>\n");
+    feed
+}
+
+#[derive(Serialize)]
+/// The top-level object of a JSON Feed document
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Serialize)]
+/// A post, as listed in a JSON Feed document
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    content_html: String,
+    date_published: String,
+    tags: Vec<String>,
+}
+
+/// Generates a JSON Feed 1.1 document listing the given posts. The content of each
+/// item is the HTML rendering of the post, its links and images made absolute under the
+/// URL of the post.
+pub fn json(posts: &[Post], root: &Path, config: &Config) -> Result<String, String> {
+    let options = BuildOptions::from_config(config);
+    let items = posts
+        .iter()
+        .map(|post| {
+            let url = post.url(config, root);
+            let (published, _) = post_dates(post, config);
+            Ok(JsonFeedItem {
+                id: url.clone(),
+                title: post.metadata.post.title.clone(),
+                content_html: render::absolute_links(
+                    &post.render_html(&options)?,
+                    &config.site.base_url,
+                    &url,
+                ),
+                date_published: published.to_rfc3339(),
+                tags: post.metadata.post.tags.clone(),
+                url,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: config.site.title.clone(),
+        home_page_url: config.site.base_url.clone(),
+        items,
+    };
+
+    serde_json::to_string_pretty(&feed).map_err(|e| format!("Failed to serialize feed: {e}"))
+}
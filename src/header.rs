@@ -7,36 +7,71 @@
 /// - Saving the downloaded images and their metadata in a structured format.
 /// - Ensuring the required environment variables are set for API access.
 /// - Logging the process of fetching and saving images for debugging and tracking purposes.
-use dotenv::dotenv;
-use log::info;
+///
+/// Each image source implements the `ImageProvider` trait. The providers are tried in the
+/// configured order, until one of them yields candidates.
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env::var;
 use std::fmt;
 use std::path::PathBuf;
 
-use crate::utils::create_path;
+use crate::config::ImageProviderKind;
+use crate::utils::{block_on, create_path};
 
-#[derive(Deserialize)]
-/// The structure of the response from the pexel API
-struct PexelResponse {
-    pub photos: Vec<PexelPicture>,
-}
+/// The environment variable holding the key of the pexels API
+pub const PEXEL_API_KEY: &str = "PEXEL_API_KEY";
 
-#[derive(Deserialize, Serialize)]
-/// The structure of a picture from the pexel API
+/// The environment variable holding the access key of the unsplash API
+pub const UNSPLASH_ACCESS_KEY: &str = "UNSPLASH_ACCESS_KEY";
+
+/// The number of digits the indices of the candidates are zero-padded to, whatever the page
+pub const CANDIDATE_INDEX_WIDTH: usize = 3;
+
+#[derive(Debug, Deserialize, Serialize)]
+/// A picture found by one of the providers, whichever it is
 /// This structure is saved in a TOML file along with the image
-pub struct PexelPicture {
+pub struct Picture {
     width: usize,
     height: usize,
     url: String,
     photographer: String,
     photographer_url: String,
+    /// The urls of the image by size, the `landscape` one being downloaded
     src: HashMap<String, String>,
     alt: String,
 }
 
-impl fmt::Display for PexelPicture {
+impl Picture {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The page of the picture on its provider
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn photographer(&self) -> &str {
+        &self.photographer
+    }
+
+    pub fn photographer_url(&self) -> &str {
+        &self.photographer_url
+    }
+
+    pub fn alt(&self) -> &str {
+        &self.alt
+    }
+}
+
+impl fmt::Display for Picture {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
@@ -46,74 +81,653 @@ impl fmt::Display for PexelPicture {
     }
 }
 
-/// Fetches the requested number of images from the pexel API.
-/// This requires the `PEXEL_API_KEY` to be set in the environment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The credits of the chosen header image of a post
+pub struct HeaderCredit {
+    pub photographer: String,
+    pub photographer_url: String,
+    /// The page of the picture on its provider
+    pub url: String,
+}
+
+impl From<&Picture> for HeaderCredit {
+    fn from(picture: &Picture) -> Self {
+        Self {
+            photographer: picture.photographer().to_string(),
+            photographer_url: picture.photographer_url().to_string(),
+            url: picture.url().to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A candidate header image freshly downloaded, along with the picture it was made from
+pub struct Candidate {
+    /// The index used to choose the candidate
+    pub index: usize,
+    /// The downloaded image
+    pub image: PathBuf,
+    /// The TOML file describing the picture, saved next to the image
+    pub metadata: PathBuf,
+    /// The picture found by the provider, as saved in the TOML file
+    pub picture: Picture,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+/// A candidate header image of a post, identified by the index used to choose it
+pub struct HeaderCandidate {
+    pub index: usize,
+    pub photographer: String,
+    pub photographer_url: String,
+    pub url: String,
+    pub alt: String,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl HeaderCandidate {
+    pub fn new(index: usize, picture: &Picture) -> Self {
+        Self {
+            index,
+            photographer: picture.photographer().to_string(),
+            photographer_url: picture.photographer_url().to_string(),
+            url: picture.url().to_string(),
+            alt: picture.alt().to_string(),
+            width: picture.width(),
+            height: picture.height(),
+        }
+    }
+}
+
+impl fmt::Display for HeaderCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} - Picture by {} - {} `{}` ({}x{})",
+            self.index, self.photographer, self.url, self.alt, self.width, self.height
+        )
+    }
+}
+
+/// Renders the preview of a candidate
+pub type Thumbnail<'a> = dyn Fn(&HeaderCandidate) -> Result<String, String> + 'a;
+
+/// Renders the listing of the candidates, one per line. When a thumbnail renderer is given,
+/// each line is preceded by the preview of the candidate; a preview that cannot be rendered
+/// is left out with a warning.
+pub fn candidates_listing(
+    candidates: &[HeaderCandidate],
+    thumbnail: Option<&Thumbnail<'_>>,
+) -> String {
+    let mut listing = String::new();
+    for candidate in candidates {
+        if let Some(thumbnail) = thumbnail {
+            match thumbnail(candidate) {
+                Ok(preview) => listing.push_str(&preview),
+                Err(e) => warn!("Unable to preview candidate {}: {e}", candidate.index),
+            }
+        }
+        listing.push_str(&format!("{candidate}\n"));
+    }
+    listing
+}
+
+#[derive(Debug, Clone)]
+/// The parameters of a search of candidate header images
+pub struct HeaderSearch {
+    /// The number of results of a page
+    pub amount: usize,
+    /// The page of the results, starting at 1
+    pub page: usize,
+    /// The dominant color of the pictures, either a name or a hexadecimal code
+    pub color: Option<String>,
+    /// The pictures narrower than this are dropped
+    pub min_width: usize,
+    /// The pictures shorter than this are dropped
+    pub min_height: usize,
+    /// Searches each keyword separately and merges the results, rather than searching all
+    /// the keywords at once
+    pub per_keyword: bool,
+    /// Shows the progress of the downloads on stderr, when it is a terminal
+    pub progress: bool,
+    /// Only searches the pictures: the candidates are returned without downloading any
+    /// image nor writing any file
+    pub dry_run: bool,
+}
+
+impl Default for HeaderSearch {
+    fn default() -> Self {
+        Self {
+            amount: 1,
+            page: 1,
+            color: None,
+            min_width: 0,
+            min_height: 0,
+            per_keyword: false,
+            progress: false,
+            dry_run: false,
+        }
+    }
+}
+
+impl HeaderSearch {
+    /// Returns whether the picture is large enough to be kept.
+    pub fn accepts(&self, picture: &Picture) -> bool {
+        picture.width() >= self.min_width && picture.height() >= self.min_height
+    }
+}
+
+/// A source of candidate header images
+pub trait ImageProvider {
+    /// The name of the provider, as written in the configuration
+    fn name(&self) -> &'static str;
+
+    /// The largest number of results of a page the provider returns
+    fn max_amount(&self) -> usize;
+
+    /// Searches the pictures matching the keywords.
+    fn search(&self, keywords: &[String], search: &HeaderSearch) -> Result<Vec<Picture>, String>;
+}
+
+/// Explains the usual failure statuses of the search APIs, given the environment variable
+/// holding the key of the provider.
+fn status_hint(status: u16, key_variable: &str) -> Option<String> {
+    match status {
+        401 => Some(format!("the API key was refused, check {key_variable}")),
+        429 => Some("rate limited, try again later".to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the body of the response, or an error mentioning its status, explained when
+/// possible, if it failed.
+async fn response_text(
+    response: reqwest::Response,
+    provider: &str,
+    key_variable: &str,
+) -> Result<String, String> {
+    let status = response.status();
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    if status.is_success() {
+        return Ok(body);
+    }
+    match status_hint(status.as_u16(), key_variable) {
+        Some(hint) => Err(format!(
+            "Failed to search images on {provider}: HTTP {}, {hint} ({body})",
+            status.as_u16()
+        )),
+        None => Err(format!(
+            "Failed to search images on {provider}: HTTP {} {body}",
+            status.as_u16()
+        )),
+    }
+}
+
+#[derive(Debug)]
+/// Searches the pictures on pexel
+pub struct PexelProvider {
+    pub api_url: String,
+    pub api_key: String,
+}
+
+impl PexelProvider {
+    /// Creates the provider searching the pexels API with the given key.
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_url: "https://api.pexels.com/v1".to_string(),
+            api_key,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+/// The structure of the response from the pexel API
+struct PexelResponse {
+    photos: Vec<PexelPicture>,
+}
+
+#[derive(Deserialize)]
+/// The structure of a picture from the pexel API
+struct PexelPicture {
+    width: usize,
+    height: usize,
+    url: String,
+    photographer: String,
+    photographer_url: String,
+    src: HashMap<String, String>,
+    alt: String,
+}
+
+impl From<PexelPicture> for Picture {
+    fn from(picture: PexelPicture) -> Self {
+        Self {
+            width: picture.width,
+            height: picture.height,
+            url: picture.url,
+            photographer: picture.photographer,
+            photographer_url: picture.photographer_url,
+            src: picture.src,
+            alt: picture.alt,
+        }
+    }
+}
+
+impl ImageProvider for PexelProvider {
+    fn name(&self) -> &'static str {
+        "pexels"
+    }
+
+    fn max_amount(&self) -> usize {
+        80
+    }
+
+    fn search(&self, keywords: &[String], search: &HeaderSearch) -> Result<Vec<Picture>, String> {
+        let client = reqwest::Client::new();
+        let mut request = client
+            .get(format!("{}/search", self.api_url.trim_end_matches('/')))
+            .header("Authorization", &self.api_key)
+            .query(&[("query", keywords.join(", "))])
+            .query(&[("per_page", search.amount.to_string().as_str())])
+            .query(&[("page", search.page.to_string().as_str())]);
+        if let Some(color) = &search.color {
+            request = request.query(&[("color", color)]);
+        }
+
+        let body = block_on(async {
+            let response = request.send().await.map_err(|e| e.to_string())?;
+            response_text(response, self.name(), PEXEL_API_KEY).await
+        })??;
+        let response = serde_json::from_str::<PexelResponse>(&body).map_err(|e| e.to_string())?;
+        Ok(response.photos.into_iter().map(Picture::from).collect())
+    }
+}
+
+#[derive(Deserialize)]
+/// The structure of the response from the unsplash search API
+struct UnsplashResponse {
+    results: Vec<UnsplashPicture>,
+}
+
+#[derive(Deserialize)]
+/// The structure of a picture from the unsplash API
+struct UnsplashPicture {
+    width: usize,
+    height: usize,
+    alt_description: Option<String>,
+    urls: HashMap<String, String>,
+    links: UnsplashLinks,
+    user: UnsplashUser,
+}
+
+#[derive(Deserialize)]
+struct UnsplashLinks {
+    html: String,
+}
+
+#[derive(Deserialize)]
+struct UnsplashUser {
+    name: String,
+    links: UnsplashLinks,
+}
+
+impl From<UnsplashPicture> for Picture {
+    fn from(picture: UnsplashPicture) -> Self {
+        let src = picture
+            .urls
+            .get("regular")
+            .map(|url| HashMap::from([("landscape".to_string(), url.clone())]))
+            .unwrap_or_default();
+
+        Self {
+            width: picture.width,
+            height: picture.height,
+            url: picture.links.html,
+            photographer: picture.user.name,
+            photographer_url: picture.user.links.html,
+            src,
+            alt: picture.alt_description.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Searches the pictures on unsplash
+pub struct UnsplashProvider {
+    pub api_url: String,
+    pub access_key: String,
+}
+
+impl UnsplashProvider {
+    /// Creates the provider searching the unsplash API with the given access key.
+    pub fn new(access_key: String) -> Self {
+        Self {
+            api_url: "https://api.unsplash.com".to_string(),
+            access_key,
+        }
+    }
+}
+
+impl ImageProvider for UnsplashProvider {
+    fn name(&self) -> &'static str {
+        "unsplash"
+    }
+
+    fn max_amount(&self) -> usize {
+        30
+    }
+
+    fn search(&self, keywords: &[String], search: &HeaderSearch) -> Result<Vec<Picture>, String> {
+        let client = reqwest::Client::new();
+        let mut request = client
+            .get(format!(
+                "{}/search/photos",
+                self.api_url.trim_end_matches('/')
+            ))
+            .header("Authorization", format!("Client-ID {}", self.access_key))
+            .query(&[("query", keywords.join(" "))])
+            .query(&[("orientation", "landscape")])
+            .query(&[("per_page", search.amount.to_string().as_str())])
+            .query(&[("page", search.page.to_string().as_str())]);
+        if let Some(color) = &search.color {
+            request = request.query(&[("color", color)]);
+        }
+
+        let body = block_on(async {
+            let response = request.send().await.map_err(|e| e.to_string())?;
+            response_text(response, self.name(), UNSPLASH_ACCESS_KEY).await
+        })??;
+        let response =
+            serde_json::from_str::<UnsplashResponse>(&body).map_err(|e| e.to_string())?;
+        Ok(response.results.into_iter().map(Picture::from).collect())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+/// The API keys of the image providers
+pub struct ApiKeys {
+    pub pexels: Option<String>,
+    pub unsplash: Option<String>,
+}
+
+impl ApiKeys {
+    /// Reads the keys from the `PEXEL_API_KEY` and `UNSPLASH_ACCESS_KEY` environment
+    /// variables, once loaded from the env file.
+    pub fn from_env() -> Self {
+        Self::from_source(|name| var(name).ok())
+    }
+
+    /// Reads the keys from the given source of variables, e.g. the variables of an env file.
+    pub fn from_source(source: impl Fn(&str) -> Option<String>) -> Self {
+        Self {
+            pexels: source(PEXEL_API_KEY),
+            unsplash: source(UNSPLASH_ACCESS_KEY),
+        }
+    }
+}
+
+/// Creates the providers of the given kinds, in the same order, with the given keys. The
+/// providers that cannot be created because of a missing key are left out with a warning.
+pub fn providers(
+    kinds: &[ImageProviderKind],
+    keys: &ApiKeys,
+) -> Result<Vec<Box<dyn ImageProvider>>, String> {
+    let mut providers: Vec<Box<dyn ImageProvider>> = vec![];
+    let mut errors = vec![];
+    for kind in kinds {
+        let provider = match kind {
+            ImageProviderKind::Pexels => keys
+                .pexels
+                .clone()
+                .map(|key| Box::new(PexelProvider::new(key)) as Box<dyn ImageProvider>)
+                .ok_or(format!("Missing {PEXEL_API_KEY}")),
+            ImageProviderKind::Unsplash => keys
+                .unsplash
+                .clone()
+                .map(|key| Box::new(UnsplashProvider::new(key)) as Box<dyn ImageProvider>)
+                .ok_or(format!("Missing {UNSPLASH_ACCESS_KEY}")),
+        };
+        match provider {
+            Ok(provider) => providers.push(provider),
+            Err(e) => {
+                warn!("Skipping the {kind:?} image provider: {e}");
+                errors.push(e);
+            }
+        }
+    }
+
+    if providers.is_empty() {
+        Err(format!(
+            "No image provider is available: {}",
+            errors.join(", ")
+        ))
+    } else {
+        Ok(providers)
+    }
+}
+
+/// Searches the pictures matching the keywords with each provider in turn, until one of them
+/// finds some, then downloads them as candidates. The candidates are numbered after the rank
+/// of their page in the search results, so that the pages fetched one after the other do not
+/// overwrite each other's candidates. The pictures that are too small are dropped, the others
+/// are numbered sequentially.
 ///
-/// This function returns a vector containing the paths to all the new images or an error
-pub async fn get_new_candidates(
+/// This function returns the new candidates, with the paths of their files, or an error
+pub fn fetch_candidates(
+    providers: &[Box<dyn ImageProvider>],
     path: PathBuf,
     keywords: &[String],
-    limit: usize,
-) -> Result<Vec<PathBuf>, String> {
-    dotenv().ok();
+    search: &HeaderSearch,
+) -> Result<Vec<Candidate>, String> {
+    if search.page == 0 {
+        return Err("The pages of the search results start at 1".to_string());
+    }
+    if search.amount == 0 {
+        return Err("The amount of images to fetch must be at least 1".to_string());
+    }
 
-    let pexel_api_key = var("PEXEL_API_KEY").map_err(|_| "Missing PEXEL_API_KEY".to_string())?;
-    let candidates_paths = path.join("candidates");
-    create_path(&candidates_paths)?;
+    let mut errors = vec![];
+    let mut found = None;
+    for provider in providers {
+        info!(
+            "Searching page {} of the images from {} for post: {}",
+            search.page,
+            provider.name(),
+            path.display()
+        );
+        let search = clamp_amount(provider.as_ref(), search);
+        match search_pictures(provider.as_ref(), keywords, &search) {
+            Ok(pictures) if pictures.is_empty() => {
+                warn!("{} found no image", provider.name());
+                errors.push(format!("{} found no image", provider.name()));
+            }
+            Ok(pictures) => {
+                found = Some((provider.name(), pictures, search));
+                break;
+            }
+            Err(e) => {
+                warn!("{e}");
+                errors.push(e);
+            }
+        }
+    }
+    let Some((provider, pictures, search)) = found else {
+        return Err(format!("Failed to fetch images: {}", errors.join(", ")));
+    };
+    info!("Using the {} images found by {provider}", pictures.len());
 
-    let client = reqwest::Client::new();
-    info!("Fetching image from pexel for post: {}", path.display());
-    let response = client
-        .get("https://api.pexels.com/v1/search")
-        .header("Authorization", pexel_api_key)
-        .query(&[("query", keywords.join(", "))])
-        .query(&[("per_page", limit.to_string().as_str())])
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    match response.status() {
-        reqwest::StatusCode::OK => {
-            let pexel_response = response
-                .json::<PexelResponse>()
-                .await
-                .map_err(|e| e.to_string())?;
-            let mut images = vec![];
-
-            for (index, image) in pexel_response.photos.iter().enumerate() {
-                let image_url = image
-                    .src
-                    .get("landscape")
-                    .ok_or("Unable to retreive landscape image from pexel picture".to_string())?;
-                let image_path = candidates_paths.join(format!("header_{}.jpg", index + 1));
-                let image_metadata = candidates_paths.join(format!("header_{}.toml", index + 1));
-
-                info!(
-                    "[{:3}/{:3}] Fetching image: {}",
-                    index + 1,
-                    pexel_response.photos.len(),
-                    image_url
-                );
-                let image_response = client
-                    .get(image_url)
-                    .send()
-                    .await
-                    .map_err(|e| e.to_string())?;
-
-                let image_bytes = image_response.bytes().await.map_err(|e| e.to_string())?;
-
-                std::fs::write(&image_path, image_bytes).map_err(|e| e.to_string())?;
-                let image_metadata_toml = toml::to_string(&image).map_err(|e| e.to_string())?;
-                std::fs::write(&image_metadata, image_metadata_toml).map_err(|e| e.to_string())?;
-
-                images.push(image_path);
+    let candidates = plan_candidates(pictures, &path.join("candidates"), &search);
+    if search.dry_run {
+        return Ok(candidates);
+    }
+    create_path(&path.join("candidates"))?;
+    block_on(download_candidates(candidates, &search))?
+}
+
+/// Returns the search with its amount lowered to the maximum of the provider, if needed.
+fn clamp_amount(provider: &dyn ImageProvider, search: &HeaderSearch) -> HeaderSearch {
+    let max_amount = provider.max_amount();
+    if search.amount <= max_amount {
+        return search.clone();
+    }
+    warn!(
+        "{} returns at most {max_amount} images per page, fetching {max_amount} instead of {}",
+        provider.name(),
+        search.amount
+    );
+    HeaderSearch {
+        amount: max_amount,
+        ..search.clone()
+    }
+}
+
+/// Searches the pictures matching the keywords with the provider. In per keyword mode, each
+/// keyword is searched separately: the results are merged in keyword order, without the
+/// pictures found for several keywords (judging by their page), up to the amount of the
+/// search. The mode fails only if every keyword search fails.
+fn search_pictures(
+    provider: &dyn ImageProvider,
+    keywords: &[String],
+    search: &HeaderSearch,
+) -> Result<Vec<Picture>, String> {
+    if !search.per_keyword {
+        return provider.search(keywords, search);
+    }
+
+    let mut pictures: Vec<Picture> = vec![];
+    let mut errors = vec![];
+    for keyword in keywords {
+        match provider.search(std::slice::from_ref(keyword), search) {
+            Ok(found) => {
+                for picture in found {
+                    if !pictures.iter().any(|other| other.url == picture.url) {
+                        pictures.push(picture);
+                    }
+                }
             }
+            Err(e) => {
+                warn!("Failed to search `{keyword}`: {e}");
+                errors.push(e);
+            }
+        }
+    }
+    if pictures.is_empty() && !errors.is_empty() {
+        return Err(errors.join(", "));
+    }
+    pictures.truncate(search.amount);
+    Ok(pictures)
+}
+
+/// Returns the name of the image of the candidate, its index zero-padded to
+/// [`CANDIDATE_INDEX_WIDTH`] digits so that the candidates sort in order across the pages,
+/// e.g. `header_001.jpg`.
+pub fn candidate_name(index: usize) -> String {
+    format!("header_{index:0CANDIDATE_INDEX_WIDTH$}.jpg")
+}
+
+/// Returns the index of the candidate of the given file name, padded or not.
+pub fn candidate_index(file_name: &str) -> Option<usize> {
+    let (stem, _) = file_name.rsplit_once('.')?;
+    stem.strip_prefix("header_")?.parse().ok()
+}
+
+/// Creates the progress bar of the downloads, drawn on stderr when enabled and stderr is a
+/// terminal. The bar is hidden otherwise, and the downloads are logged instead.
+pub fn progress_bar(enabled: bool) -> ProgressBar {
+    let target = if enabled {
+        ProgressDrawTarget::stderr()
+    } else {
+        ProgressDrawTarget::hidden()
+    };
+    let bar = ProgressBar::with_draw_target(None, target);
+    if let Ok(style) =
+        ProgressStyle::with_template("{prefix} [{bar:30}] {bytes}/{total_bytes} {wide_msg}")
+    {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar
+}
 
-            Ok(images)
+/// Numbers the pictures large enough after the candidates of the previous pages, and names
+/// the files they are to be saved to.
+fn plan_candidates(
+    pictures: Vec<Picture>,
+    candidates_paths: &std::path::Path,
+    search: &HeaderSearch,
+) -> Vec<Candidate> {
+    let first_index = (search.page - 1) * search.amount + 1;
+    let found = pictures.len();
+    let pictures = pictures
+        .into_iter()
+        .filter(|picture| search.accepts(picture))
+        .collect::<Vec<_>>();
+    if pictures.len() < found {
+        info!(
+            "Dropped {} pictures smaller than {}x{}",
+            found - pictures.len(),
+            search.min_width,
+            search.min_height
+        );
+    }
+
+    pictures
+        .into_iter()
+        .enumerate()
+        .map(|(index, picture)| {
+            let image = candidates_paths.join(candidate_name(first_index + index));
+            Candidate {
+                index: first_index + index,
+                metadata: image.with_extension("toml"),
+                image,
+                picture,
+            }
+        })
+        .collect()
+}
+
+/// Downloads the pictures that are large enough into the candidates directory.
+async fn download_candidates(
+    candidates: Vec<Candidate>,
+    search: &HeaderSearch,
+) -> Result<Vec<Candidate>, String> {
+    let client = reqwest::Client::new();
+    let total = candidates.len();
+    let bar = progress_bar(search.progress);
+    for (index, candidate) in candidates.iter().enumerate() {
+        let image_url = candidate
+            .picture
+            .src
+            .get("landscape")
+            .ok_or("Unable to retreive landscape image from picture".to_string())?;
+
+        if bar.is_hidden() {
+            info!(
+                "[{:3}/{:3}] Fetching image: {}",
+                index + 1,
+                total,
+                image_url
+            );
         }
-        _ => Err(format!(
-            "Failed to fetch image: {}",
-            response.text().await.map_err(|e| e.to_string())?
-        )),
+        let mut image_response = client
+            .get(image_url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        bar.reset();
+        bar.set_length(image_response.content_length().unwrap_or_default());
+        bar.set_prefix(format!("[{:3}/{:3}]", index + 1, total));
+        bar.set_message(image_url.clone());
+        let mut image_bytes = vec![];
+        while let Some(chunk) = image_response.chunk().await.map_err(|e| e.to_string())? {
+            bar.inc(chunk.len() as u64);
+            image_bytes.extend_from_slice(&chunk);
+        }
+
+        std::fs::write(&candidate.image, image_bytes).map_err(|e| e.to_string())?;
+        let image_metadata_toml = toml::to_string(&candidate.picture).map_err(|e| e.to_string())?;
+        std::fs::write(&candidate.metadata, image_metadata_toml).map_err(|e| e.to_string())?;
     }
+
+    bar.finish_and_clear();
+    Ok(candidates)
 }
@@ -0,0 +1,48 @@
+/// This module runs the shell commands configured to be run around the build of a post,
+/// e.g. to purge a CDN once the post is built. The commands are run by `sh -c`, from the
+/// current directory, with the paths of the post and of its output in the environment.
+use std::path::Path;
+use std::process::Command;
+
+use log::{info, warn};
+
+/// The environment variable holding the path of the built post
+pub const POST_PATH_VARIABLE: &str = "BLOG_POST_PATH";
+
+/// The environment variable holding the output directory of the built post
+pub const OUTPUT_PATH_VARIABLE: &str = "BLOG_OUTPUT_PATH";
+
+/// Runs the hook command of the given stage (`pre-build`, `post-build`) of the build of
+/// the post. A failing hook fails the build, unless its errors are ignored, in which case
+/// they are only logged.
+pub fn run_hook(
+    stage: &str,
+    command: &str,
+    post: &Path,
+    output: &Path,
+    ignore_errors: bool,
+) -> Result<(), String> {
+    info!("Running {stage} hook `{command}` for {}", post.display());
+    let result = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env(POST_PATH_VARIABLE, post)
+        .env(OUTPUT_PATH_VARIABLE, output)
+        .status()
+        .map_err(|e| format!("Failed to run the {stage} hook `{command}`: {e}"))
+        .and_then(|status| {
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("The {stage} hook `{command}` failed: {status}"))
+            }
+        });
+
+    match result {
+        Err(e) if ignore_errors => {
+            warn!("{e}");
+            Ok(())
+        }
+        result => result,
+    }
+}
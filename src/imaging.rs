@@ -0,0 +1,126 @@
+/// This module handles the processing of the images of a post during its build, such
+/// as the generation of resized variants of the header image.
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use log::info;
+
+/// The widths of the variants generated for the header image
+pub const HEADER_WIDTHS: [u32; 3] = [480, 960, 1600];
+
+/// The sizes of the square favicons generated from the favicon of the site
+pub const FAVICON_SIZES: [u32; 2] = [32, 16];
+
+/// Returns the width of the given image.
+pub fn image_width(path: &Path) -> Result<u32, String> {
+    image::image_dimensions(path)
+        .map(|(width, _)| width)
+        .map_err(|e| format!("Failed to read image {}: {e}", path.display()))
+}
+
+/// Returns the widths of the variants to generate for the given image, skipping those
+/// that would be larger than the image itself.
+pub fn variant_widths(source: &Path) -> Result<Vec<u32>, String> {
+    let width = image_width(source)?;
+    Ok(HEADER_WIDTHS
+        .into_iter()
+        .filter(|variant| *variant < width)
+        .collect())
+}
+
+/// Returns the name of the variant of the given image file with the given width,
+/// e.g. `header-480.jpg` for `header.jpg`.
+pub fn variant_name(file_name: &str, width: u32) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{stem}-{width}.{extension}"),
+        None => format!("{file_name}-{width}"),
+    }
+}
+
+/// Resizes the given image to the given width, keeping its aspect ratio, and saves
+/// it at the destination.
+pub fn resize_image(source: &Path, destination: &Path, width: u32) -> Result<(), String> {
+    let image = image::open(source)
+        .map_err(|e| format!("Failed to open image {}: {e}", source.display()))?;
+    image
+        .resize(width, u32::MAX, FilterType::Lanczos3)
+        .save(destination)
+        .map_err(|e| format!("Failed to save image {}: {e}", destination.display()))
+}
+
+/// Generates the resized variants of the given image in the given directory.
+/// Returns the paths of the generated files.
+pub fn generate_variants(source: &Path, directory: &Path) -> Result<Vec<PathBuf>, String> {
+    let file_name = source
+        .file_name()
+        .ok_or(format!("Unable to get the name of {}", source.display()))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut variants = vec![];
+    for width in variant_widths(source)? {
+        let destination = directory.join(variant_name(&file_name, width));
+        info!("Generating {}", destination.display());
+        resize_image(source, &destination, width)?;
+        variants.push(destination);
+    }
+    Ok(variants)
+}
+
+/// Returns the `srcset` attribute value listing the variants of the image at the given
+/// URL, along with the original image.
+pub fn srcset(url: &str, widths: &[u32], original_width: u32) -> String {
+    widths
+        .iter()
+        .map(|width| format!("{} {width}w", variant_name(url, *width)))
+        .chain(std::iter::once(format!("{url} {original_width}w")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Generates the square PNG favicons of `FAVICON_SIZES` from the given image in the given
+/// directory, named `favicon-<size>.png`. Returns the paths of the generated files.
+pub fn generate_favicons(source: &Path, directory: &Path) -> Result<Vec<PathBuf>, String> {
+    let image = image::open(source)
+        .map_err(|e| format!("Failed to open image {}: {e}", source.display()))?;
+
+    let mut favicons = vec![];
+    for size in FAVICON_SIZES {
+        let destination = directory.join(format!("favicon-{size}.png"));
+        info!("Generating {}", destination.display());
+        image
+            .resize_exact(size, size, FilterType::Lanczos3)
+            .save(&destination)
+            .map_err(|e| format!("Failed to save image {}: {e}", destination.display()))?;
+        favicons.push(destination);
+    }
+    Ok(favicons)
+}
+
+/// The width of the terminal previews of the images, in columns
+pub const THUMBNAIL_WIDTH: u32 = 40;
+
+/// Renders a preview of the given image for the terminal, `width` columns wide. Each
+/// character is a half block drawing two pixels, in 24-bit ANSI colors.
+pub fn ansi_thumbnail(path: &Path, width: u32) -> Result<String, String> {
+    let image =
+        image::open(path).map_err(|e| format!("Failed to open image {}: {e}", path.display()))?;
+    let height = (u64::from(image.height()) * u64::from(width) / u64::from(image.width().max(1)))
+        .max(2) as u32;
+    let image = image
+        .resize_exact(width, height + height % 2, FilterType::Triangle)
+        .to_rgb8();
+
+    let mut thumbnail = String::new();
+    for y in (0..image.height()).step_by(2) {
+        for x in 0..image.width() {
+            let [tr, tg, tb] = image.get_pixel(x, y).0;
+            let [br, bg, bb] = image.get_pixel(x, y + 1).0;
+            thumbnail.push_str(&format!(
+                "\x1b[38;2;{tr};{tg};{tb}m\x1b[48;2;{br};{bg};{bb}m\u{2580}"
+            ));
+        }
+        thumbnail.push_str("\x1b[0m\n");
+    }
+    Ok(thumbnail)
+}
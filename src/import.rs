@@ -0,0 +1,376 @@
+/// This module turns loose markdown files into blog posts. The front matter of the file,
+/// if any, seeds the metadata of the post, and the local images it references are copied
+/// into the post's `images/` directory, under a new name when another one took theirs.
+///
+/// Both YAML (`---`) and TOML (`+++`) front matters are read. Only their `title`, `author`
+/// (or `authors`), `description`, `tags`, `keywords` and `date` keys are used. The TOML
+/// ones are parsed as such; the lists of the YAML ones can be written inline (`[a, b]`) or
+/// one `- item` per line.
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use log::info;
+use markdown::mdast::{Image, Node};
+
+use crate::config::Config;
+use crate::post::Post;
+use crate::utils::{self, create_path, is_image, percent_decode};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+/// The metadata read from the front matter of a markdown file
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub keywords: Vec<String>,
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// Removes the quotes surrounding a YAML or TOML scalar.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Parses an inline `[a, b]` list, or a single value.
+fn inline_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let items = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .unwrap_or(value);
+    items
+        .split(',')
+        .map(unquote)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Parses a date written as RFC 3339 or `YYYY-MM-DD`.
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    utils::parse_date(&unquote(value)).ok()
+}
+
+impl FrontMatter {
+    /// Parses the flat keys of a YAML front matter.
+    fn parse(front_matter: &str) -> Self {
+        let mut parsed = Self::default();
+        // The list the `- item` lines are added to
+        let mut list = None;
+
+        for line in front_matter.lines() {
+            if let Some(item) = line.trim().strip_prefix("- ") {
+                match list {
+                    Some("tags") => parsed.tags.push(unquote(item)),
+                    Some("keywords") => parsed.keywords.push(unquote(item)),
+                    Some("authors") => parsed.authors.push(unquote(item)),
+                    _ => {}
+                }
+                continue;
+            }
+
+            list = None;
+            let Some((key, value)) = line.split_once(':').or_else(|| line.split_once('=')) else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "title" => parsed.title = Some(unquote(value)),
+                "author" | "authors" if value.is_empty() => list = Some("authors"),
+                "author" | "authors" => parsed.authors = inline_list(value),
+                "description" => parsed.description = Some(unquote(value)),
+                "date" => parsed.date = parse_date(value),
+                "tags" if value.is_empty() => list = Some("tags"),
+                "tags" => parsed.tags = inline_list(value),
+                "keywords" if value.is_empty() => list = Some("keywords"),
+                "keywords" => parsed.keywords = inline_list(value),
+                _ => {}
+            }
+        }
+
+        parsed
+    }
+
+    /// Parses a TOML front matter. A single string is accepted in place of a list.
+    fn parse_toml(front_matter: &str) -> Result<Self, String> {
+        let table: toml::Table = toml::from_str(front_matter)
+            .map_err(|e| format!("Failed to parse the TOML front matter: {e}"))?;
+        let string = |key: &str| {
+            table
+                .get(key)
+                .and_then(toml::Value::as_str)
+                .map(str::to_string)
+        };
+        let list = |key: &str| match table.get(key) {
+            Some(toml::Value::Array(items)) => items
+                .iter()
+                .filter_map(toml::Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            Some(toml::Value::String(item)) => vec![item.clone()],
+            _ => vec![],
+        };
+        let date = match table.get("date") {
+            Some(toml::Value::Datetime(date)) => parse_date(&date.to_string()),
+            Some(toml::Value::String(date)) => parse_date(date),
+            _ => None,
+        };
+
+        let authors = list("authors");
+        Ok(Self {
+            title: string("title"),
+            authors: if authors.is_empty() {
+                list("author")
+            } else {
+                authors
+            },
+            description: string("description"),
+            tags: list("tags"),
+            keywords: list("keywords"),
+            date,
+        })
+    }
+}
+
+/// Splits the front matter, delimited by `---` or `+++` lines, from the content of the
+/// markdown file. Fails if a TOML front matter is invalid.
+pub fn split_front_matter(content: &str) -> Result<(Option<FrontMatter>, &str), String> {
+    for delimiter in ["---", "+++"] {
+        let Some(rest) = content
+            .strip_prefix(delimiter)
+            .and_then(|rest| rest.strip_prefix('\n').or(rest.strip_prefix("\r\n")))
+        else {
+            continue;
+        };
+
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == delimiter {
+                let front_matter = match delimiter {
+                    "+++" => FrontMatter::parse_toml(&rest[..offset])?,
+                    _ => FrontMatter::parse(&rest[..offset]),
+                };
+                return Ok((Some(front_matter), &rest[offset + line.len()..]));
+            }
+            offset += line.len();
+        }
+    }
+
+    Ok((None, content))
+}
+
+/// Returns the text of the first level 1 heading of the content.
+pub fn first_heading(content: &str) -> Option<String> {
+    let tree = markdown::to_mdast(content, &markdown::ParseOptions::gfm()).ok()?;
+    tree.children()?.iter().find_map(|node| match node {
+        Node::Heading(heading) if heading.depth == 1 => Some(node.to_string()),
+        _ => None,
+    })
+}
+
+/// Returns the inline images of the content.
+fn images(node: &Node) -> Vec<&Image> {
+    let mut found = match node {
+        Node::Image(image) => vec![image],
+        _ => vec![],
+    };
+    for child in node.children().into_iter().flatten() {
+        found.extend(images(child));
+    }
+    found
+}
+
+/// Returns the byte range of the destination in the source of the inline image `image`,
+/// e.g. `![alt](<dest>)`, the angle brackets included.
+fn destination_range(image: &str) -> Option<Range<usize>> {
+    // The alternative text ends at the closing bracket matching the opening one
+    let bytes = image.as_bytes();
+    let mut depth = 0;
+    let mut index = 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 1,
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    let rest = image.get(index + 1..)?.strip_prefix('(')?;
+    let start = index + 2 + (rest.len() - rest.trim_start().len());
+    let destination = &image[start..];
+    let length = if destination.starts_with('<') {
+        destination.find('>')? + 1
+    } else {
+        destination
+            .find(|c: char| c.is_whitespace() || c == ')')
+            .unwrap_or(destination.len())
+    };
+    Some(start..start + length)
+}
+
+/// Returns the path of the given file name in the directory, suffixed with `-1`, `-2`, ...
+/// when a file already has that name.
+fn free_path(directory: &Path, name: &str) -> PathBuf {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (name, String::new()),
+    };
+    let mut path = directory.join(name);
+    let mut suffix = 0;
+    while path.exists() {
+        suffix += 1;
+        path = directory.join(format!("{stem}-{suffix}{extension}"));
+    }
+    path
+}
+
+/// Copies the local images of the content of the post into its `images/` directory, the
+/// images of the same name under new names, and points their links to the copies.
+fn copy_images(post: &mut Post, base: &Path) -> Result<(), String> {
+    let tree = markdown::to_mdast(&post.content, &markdown::ParseOptions::gfm())
+        .map_err(|e| format!("Failed to parse content: {e}"))?;
+    let images_path = post.path.join("images");
+    let mut copies: HashMap<PathBuf, String> = HashMap::new();
+    let mut rewrites = vec![];
+    for image in images(&tree) {
+        let url = percent_decode(&image.url);
+        let source = base.join(&url);
+        if url.contains(':') || url.starts_with('/') || !source.is_file() || !is_image(&source) {
+            continue;
+        }
+        let Some(position) = &image.position else {
+            continue;
+        };
+        let span = position.start.offset..position.end.offset;
+        let Some(range) = destination_range(&post.content[span.clone()]) else {
+            continue;
+        };
+
+        let name = match copies.get(&source) {
+            Some(name) => name.clone(),
+            None => {
+                let file_name = source
+                    .file_name()
+                    .ok_or(format!("Unable to get the name of {}", source.display()))?
+                    .to_string_lossy()
+                    .to_string();
+                create_path(&images_path)?;
+                let destination = free_path(&images_path, &file_name);
+                info!("Copying {} to {}", source.display(), destination.display());
+                fs::copy(&source, &destination)
+                    .map_err(|e| format!("Failed to copy image: {e}"))?;
+                let name = destination
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                copies.insert(source, name.clone());
+                name
+            }
+        };
+        let destination = if name.contains(|c: char| c.is_whitespace() || "()<>".contains(c)) {
+            format!("<images/{name}>")
+        } else {
+            format!("images/{name}")
+        };
+        rewrites.push((
+            span.start + range.start..span.start + range.end,
+            destination,
+        ));
+    }
+
+    // The later destinations are rewritten first, for the earlier offsets to stay valid
+    for (range, destination) in rewrites.into_iter().rev() {
+        post.content.replace_range(range, &destination);
+    }
+    Ok(())
+}
+
+/// Imports the markdown file as a new post under the given root directory. The title is the
+/// given one, or the title of the front matter, the first level 1 heading of the content or
+/// the name of the file, in that order.
+pub fn import(
+    file: &Path,
+    title: Option<&str>,
+    root: &Path,
+    config: &Config,
+) -> Result<Post, String> {
+    let source =
+        fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+    let (front_matter, content) =
+        split_front_matter(&source).map_err(|e| format!("{}: {e}", file.display()))?;
+    let front_matter = front_matter.unwrap_or_default();
+    let content = content.trim_start();
+
+    let heading = first_heading(content);
+    let title = title
+        .map(str::to_string)
+        .or(front_matter.title)
+        .or(heading.clone())
+        .or_else(|| {
+            file.file_stem()
+                .map(|stem| stem.to_string_lossy().replace(['-', '_'], " "))
+        })
+        .filter(|title| !title.trim().is_empty())
+        .ok_or(format!("Unable to find a title for {}", file.display()))?;
+
+    let mut post = Post::new(&title);
+    if let Some(date) = front_matter.date {
+        post.metadata.post.created_date = Some(date);
+    }
+    let mut post = post.with_path_format(&config.posts.path_format, &config.slug)?;
+    post.path = root.join(&post.path);
+    if post.path.exists() {
+        return Err(format!("A post already exists at {}", post.path.display()));
+    }
+
+    post.metadata.post.authors = if front_matter.authors.is_empty() {
+        Some(config.site.author.clone())
+            .filter(|author| !author.is_empty())
+            .into_iter()
+            .collect()
+    } else {
+        front_matter.authors
+    };
+    post.metadata.opengraph.description = front_matter.description.unwrap_or_default();
+    for tag in front_matter.tags {
+        post.metadata.post.add_tag(tag, config.posts.max_tags)?;
+    }
+    for keyword in front_matter.keywords {
+        post.metadata
+            .opengraph
+            .add_keyword(keyword, config.posts.max_keywords)?;
+    }
+
+    post.content = if heading.is_some() {
+        content.to_string()
+    } else {
+        format!("# {title}\n\n{content}")
+    };
+    post.save()?;
+
+    // Copy the local images along, and point the content to the copies
+    copy_images(&mut post, file.parent().unwrap_or(Path::new(".")))?;
+    post.save()?;
+
+    Ok(post)
+}
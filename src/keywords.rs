@@ -0,0 +1,93 @@
+/// This module suggests keywords for a post, from the terms its prose uses the most. The
+/// prose is read like the spell checker reads it, so that code and markdown syntax are
+/// left out, and the common English words are ignored.
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::post::Post;
+use crate::spell::prose_words;
+
+/// The shortest term worth suggesting
+const MIN_LENGTH: usize = 3;
+
+/// The common English words, never suggested
+const STOPWORDS: &[&str] = &[
+    "about", "above", "after", "again", "against", "all", "also", "and", "any", "are", "because",
+    "been", "before", "being", "below", "between", "both", "but", "can", "could", "did", "does",
+    "doing", "down", "during", "each", "even", "few", "for", "from", "further", "get", "got",
+    "had", "has", "have", "having", "her", "here", "hers", "him", "his", "how", "however", "into",
+    "its", "itself", "just", "let", "like", "made", "make", "many", "more", "most", "much", "must",
+    "not", "now", "off", "once", "one", "only", "other", "our", "ours", "out", "over", "own",
+    "same", "she", "should", "some", "such", "than", "that", "the", "their", "theirs", "them",
+    "then", "there", "these", "they", "this", "those", "through", "too", "under", "until", "use",
+    "used", "using", "very", "was", "way", "well", "were", "what", "when", "where", "which",
+    "while", "who", "whom", "why", "will", "with", "would", "yet", "you", "your", "yours",
+    "yourself", "it's", "don't", "i'm", "can't", "isn't", "there's",
+];
+
+#[derive(Debug, PartialEq, Serialize)]
+/// A term suggested as keyword
+pub struct Suggestion {
+    pub term: String,
+    /// The number of occurrences of the term in the prose of the post
+    pub count: usize,
+    /// The term frequency: the share of the words of the prose that are the term
+    pub frequency: f64,
+}
+
+/// Returns the `count` terms the prose of the post uses the most, most used first; ties
+/// are broken alphabetically. The stopwords, the short terms and the keywords the post
+/// already has are left out.
+pub fn suggest(post: &Post, count: usize) -> Result<Vec<Suggestion>, String> {
+    let words = prose_words(&post.content)?;
+    let total = words.len().max(1);
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in words {
+        let term = word.word.to_lowercase();
+        let term = term.strip_suffix("'s").unwrap_or(&term);
+        if term.chars().count() < MIN_LENGTH || STOPWORDS.contains(&term) {
+            continue;
+        }
+        *counts.entry(term.to_string()).or_default() += 1;
+    }
+
+    let keywords: Vec<String> = post
+        .metadata
+        .opengraph
+        .keywords
+        .iter()
+        .map(|keyword| keyword.to_lowercase())
+        .collect();
+    let mut suggestions: Vec<Suggestion> = counts
+        .into_iter()
+        .filter(|(term, _)| !keywords.contains(term))
+        .map(|(term, count)| Suggestion {
+            term,
+            count,
+            frequency: count as f64 / total as f64,
+        })
+        .collect();
+    suggestions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+    suggestions.truncate(count);
+    Ok(suggestions)
+}
+
+/// Adds the first `count` suggestions as keywords of the post, within the maximum number
+/// of keywords. Returns the added keywords.
+pub fn apply_suggestions(
+    post: &mut Post,
+    suggestions: &[Suggestion],
+    count: usize,
+    max_keywords: usize,
+) -> Result<Vec<String>, String> {
+    let mut added = vec![];
+    for suggestion in suggestions.iter().take(count) {
+        post.metadata
+            .opengraph
+            .add_keyword(suggestion.term.clone(), max_keywords)?;
+        added.push(suggestion.term.clone());
+    }
+    Ok(added)
+}
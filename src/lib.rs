@@ -0,0 +1,38 @@
+//! The library behind the `blog` command line tool: creating, editing, building and
+//! publishing blog posts stored as directories of markdown content and TOML metadata.
+//!
+//! A post is loaded with [`post::Post::load`], built with [`post::Post::build`] and saved
+//! back with [`post::Post::save`]; the other modules implement the blog-wide operations
+//! (building every post, feeds, pages, ...) on top of it.
+
+#[cfg(test)]
+mod tests;
+
+pub mod backup;
+pub mod build;
+pub mod compress;
+pub mod config;
+pub mod export;
+pub mod feed;
+pub mod header;
+pub mod hooks;
+pub mod imaging;
+pub mod import;
+pub mod keywords;
+pub mod links;
+pub mod lint;
+pub mod logger;
+pub mod manifest;
+pub mod pages;
+pub mod post;
+pub mod preview;
+pub mod publish;
+pub mod related;
+pub mod render;
+pub mod robots;
+pub mod search;
+pub mod spell;
+pub mod stats;
+pub mod template;
+pub mod utils;
+pub mod validate;
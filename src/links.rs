@@ -0,0 +1,189 @@
+/// This module checks the links of a post's HTML, making sure the local files they
+/// point to exist and, optionally, that the external URLs they reference respond.
+///
+/// It also validates the references between posts: relative or site-absolute links
+/// leading outside of the post, into the post tree, must point to an existing post.
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use log::info;
+use regex::Regex;
+
+use crate::post::{BuildOptions, Post};
+use crate::utils::{block_on, is_post_dir, normalize_path, relative_to};
+
+#[derive(Debug, PartialEq, Eq)]
+/// The result of the check of a single link
+pub enum LinkStatus {
+    Ok,
+    Broken(String),
+    /// The link was not checked (anchors, external links when not requested, ...)
+    Skipped,
+}
+
+#[derive(Debug)]
+/// A link found in the HTML of a post, along with its status
+pub struct LinkReport {
+    pub link: String,
+    pub status: LinkStatus,
+}
+
+impl fmt::Display for LinkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            LinkStatus::Ok => write!(f, "[ OK ] {}", self.link),
+            LinkStatus::Broken(reason) => write!(f, "[FAIL] {} ({reason})", self.link),
+            LinkStatus::Skipped => write!(f, "[SKIP] {}", self.link),
+        }
+    }
+}
+
+/// Returns the value of all the `href` and `src` attributes found in the given HTML.
+pub fn extract_links(html: &str) -> Vec<String> {
+    let attribute = Regex::new(r#"(?:href|src)\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+        .expect("The link regex is valid");
+
+    attribute
+        .captures_iter(html)
+        .filter_map(|captures| captures.get(1).or_else(|| captures.get(2)))
+        .map(|link| link.as_str().to_string())
+        .collect()
+}
+
+/// Returns whether the link points to a resource on another server.
+fn is_external(link: &str) -> bool {
+    link.starts_with("http://") || link.starts_with("https://") || link.starts_with("//")
+}
+
+/// Checks that the target of a relative link exists, relatively to the given directory.
+pub fn check_local(link: &str, base: &Path) -> LinkStatus {
+    let target = link.split(['#', '?']).next().unwrap_or_default();
+    if target.is_empty() || target.starts_with('/') || target.contains(':') {
+        // Anchors, site-absolute paths and other schemes (mailto:, data:, ...)
+        return LinkStatus::Skipped;
+    }
+
+    if base.join(target).exists() {
+        LinkStatus::Ok
+    } else {
+        LinkStatus::Broken("file not found".to_string())
+    }
+}
+
+/// Checks that the external URL responds with a success status to a HEAD request.
+pub async fn check_external(client: &reqwest::Client, link: &str) -> LinkStatus {
+    let url = if link.starts_with("//") {
+        format!("https:{link}")
+    } else {
+        link.to_string()
+    };
+
+    info!("Checking external link {url}");
+    match client.head(&url).send().await {
+        Ok(response) if response.status().is_success() => LinkStatus::Ok,
+        Ok(response) => LinkStatus::Broken(format!("HTTP {}", response.status().as_u16())),
+        Err(e) => LinkStatus::Broken(e.to_string()),
+    }
+}
+
+/// Checks all the links of the given HTML. Relative links are resolved against `base`
+/// and external links are only checked when requested.
+pub fn check_links(html: &str, base: &Path, external: bool) -> Result<Vec<LinkReport>, String> {
+    let client = reqwest::Client::new();
+
+    block_on(async {
+        let mut reports = vec![];
+        for link in extract_links(html) {
+            let status = match (is_external(&link), external) {
+                (true, true) => check_external(&client, &link).await,
+                (true, false) => LinkStatus::Skipped,
+                (false, _) => check_local(&link, base),
+            };
+            reports.push(LinkReport { link, status });
+        }
+        reports
+    })
+}
+
+/// Resolves the `.` and `..` components of the path without touching the disk, so that
+/// paths that do not exist can be resolved too.
+fn resolve(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+/// Checks that the link, found in the post at `post_dir`, points to an existing post when
+/// it leads into the post tree under `root`. Site-absolute links are resolved against the
+/// root, after removing the path of the site's base URL. Links to the post's own files, to
+/// existing files and to anything outside of the tree are skipped.
+pub fn check_post_reference(
+    link: &str,
+    post_dir: &Path,
+    root: &Path,
+    base_url: &str,
+) -> LinkStatus {
+    let target = link.split(['#', '?']).next().unwrap_or_default();
+    if target.is_empty() || target.contains(':') || target.starts_with("//") {
+        return LinkStatus::Skipped;
+    }
+
+    let path = match target.strip_prefix('/') {
+        Some(absolute) => {
+            let base_path = reqwest::Url::parse(base_url)
+                .map(|url| url.path().trim_matches('/').to_string())
+                .unwrap_or_default();
+            let relative = absolute
+                .strip_prefix(base_path.as_str())
+                .filter(|_| !base_path.is_empty())
+                .unwrap_or(absolute);
+            root.join(relative.trim_start_matches('/'))
+        }
+        None => post_dir.join(target),
+    };
+    let mut path = resolve(&path);
+    if path.file_name().is_some_and(|name| name == "index.html") {
+        path.pop();
+    }
+
+    if path.starts_with(post_dir) || !path.starts_with(root) {
+        return LinkStatus::Skipped;
+    }
+    if is_post_dir(&path) {
+        LinkStatus::Ok
+    } else if path.exists() {
+        LinkStatus::Skipped
+    } else {
+        let relative = relative_to(&path, root).unwrap_or(&path);
+        LinkStatus::Broken(format!("no post at {}", relative.display()))
+    }
+}
+
+/// Checks the references of the post to other posts of the blog under `root`. Only the
+/// links leading to other posts are reported.
+pub fn check_post_references(
+    post: &Post,
+    root: &Path,
+    base_url: &str,
+) -> Result<Vec<LinkReport>, String> {
+    let root = resolve(&normalize_path(root));
+    let post_dir = resolve(&normalize_path(&post.path));
+    let html = post.render_html(&BuildOptions::default())?;
+
+    Ok(extract_links(&html)
+        .into_iter()
+        .map(|link| {
+            let status = check_post_reference(&link, &post_dir, &root, base_url);
+            LinkReport { link, status }
+        })
+        .filter(|report| report.status != LinkStatus::Skipped)
+        .collect())
+}
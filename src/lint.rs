@@ -0,0 +1,292 @@
+/// This module checks the quality of a post: structure of its headings, alternative text
+/// of its images and completeness of its metadata. Each check is a separate function
+/// returning its findings; `lint` runs all of them.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use markdown::mdast::Node;
+use serde::Serialize;
+
+use crate::post::{Metadata, Post};
+use crate::render::anchor_collisions;
+use crate::utils::{list_files, percent_decode};
+
+/// The maximum length of a description recommended by OpenGraph consumers
+pub const MAX_DESCRIPTION_LENGTH: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// How serious a finding is
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+/// An issue found in a post
+pub struct Finding {
+    pub severity: Severity,
+    /// The line of the content the issue is on, if it is in the content
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(severity: Severity, line: Option<usize>, message: String) -> Self {
+        Self {
+            severity,
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Info => "[INFO]",
+            Severity::Warning => "[WARN]",
+            Severity::Error => "[ERROR]",
+        };
+        match self.line {
+            Some(line) => write!(f, "{severity} line {line}: {}", self.message),
+            None => write!(f, "{severity} {}", self.message),
+        }
+    }
+}
+
+/// Returns the line a node starts on.
+fn line(node: &Node) -> Option<usize> {
+    node.position().map(|position| position.start.line)
+}
+
+/// Collects the nodes of the tree for which the predicate holds, in document order.
+fn collect<'a>(node: &'a Node, predicate: &dyn Fn(&Node) -> bool, nodes: &mut Vec<&'a Node>) {
+    if predicate(node) {
+        nodes.push(node);
+    }
+    for child in node.children().into_iter().flatten() {
+        collect(child, predicate, nodes);
+    }
+}
+
+/// Returns the headings of the tree, with their depth.
+fn headings(tree: &Node) -> Vec<(u8, &Node)> {
+    let mut nodes = vec![];
+    collect(tree, &|node| matches!(node, Node::Heading(_)), &mut nodes);
+    nodes
+        .into_iter()
+        .filter_map(|node| match node {
+            Node::Heading(heading) => Some((heading.depth, node)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks that the headings do not skip levels, e.g. a `###` directly after a `#`.
+pub fn check_heading_levels(tree: &Node) -> Vec<Finding> {
+    headings(tree)
+        .windows(2)
+        .filter(|pair| pair[1].0 > pair[0].0 + 1)
+        .map(|pair| {
+            Finding::new(
+                Severity::Error,
+                line(pair[1].1),
+                format!(
+                    "Heading `{}` skips from level {} to level {}",
+                    pair[1].1.to_string(),
+                    pair[0].0,
+                    pair[1].0
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Checks that no two headings have the same text.
+pub fn check_duplicate_headings(tree: &Node) -> Vec<Finding> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut findings = vec![];
+    for (_, heading) in headings(tree) {
+        let text = heading.to_string();
+        match seen.get(&text.to_lowercase()) {
+            Some(first) => findings.push(Finding::new(
+                Severity::Error,
+                line(heading),
+                format!("Heading `{text}` duplicates the heading of line {first}"),
+            )),
+            None => {
+                seen.insert(text.to_lowercase(), line(heading).unwrap_or_default());
+            }
+        }
+    }
+    findings
+}
+
+/// Checks that no two headings get the same anchor, which would make the links to the
+/// later ones point to the first one.
+pub fn check_anchor_collisions(tree: &Node) -> Vec<Finding> {
+    let headings = headings(tree);
+    let texts: Vec<String> = headings.iter().map(|(_, node)| node.to_string()).collect();
+    anchor_collisions(&texts)
+        .into_iter()
+        .map(|(anchor, indices)| {
+            let lines = indices
+                .iter()
+                .filter_map(|&index| line(headings[index].1))
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Finding::new(
+                Severity::Warning,
+                line(headings[indices[0]].1),
+                format!("The headings of lines {lines} share the anchor `#{anchor}`, later ones get numbered suffixes"),
+            )
+        })
+        .collect()
+}
+
+/// Checks that every image has an alternative text.
+pub fn check_image_alt(tree: &Node) -> Vec<Finding> {
+    let mut images = vec![];
+    collect(
+        tree,
+        &|node| matches!(node, Node::Image(image) if image.alt.trim().is_empty()),
+        &mut images,
+    );
+    images
+        .into_iter()
+        .map(|image| {
+            let url = match image {
+                Node::Image(image) => image.url.as_str(),
+                _ => "",
+            };
+            Finding::new(
+                Severity::Error,
+                line(image),
+                format!("Image `{url}` has no alternative text"),
+            )
+        })
+        .collect()
+}
+
+/// Returns the local images of the tree, relatively to the post directory, along with the
+/// line of their reference. Remote images, data URLs and site-absolute paths are left out.
+fn local_images(tree: &Node) -> Vec<(PathBuf, Option<usize>)> {
+    let mut images = vec![];
+    collect(tree, &|node| matches!(node, Node::Image(_)), &mut images);
+    images
+        .into_iter()
+        .filter_map(|node| {
+            let Node::Image(image) = node else {
+                return None;
+            };
+            let target = image.url.split(['#', '?']).next().unwrap_or_default();
+            if target.is_empty() || target.contains(':') || target.starts_with('/') {
+                return None;
+            }
+            Some((normalize(Path::new(&percent_decode(target))), line(node)))
+        })
+        .collect()
+}
+
+/// Removes the `.` components of the relative path.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
+
+/// Checks that the local images referenced by the content exist in the post directory.
+pub fn check_missing_images(tree: &Node, post_dir: &Path) -> Vec<Finding> {
+    local_images(tree)
+        .into_iter()
+        .filter(|(image, _)| !post_dir.join(image).is_file())
+        .map(|(image, line)| {
+            Finding::new(
+                Severity::Error,
+                line,
+                format!("Image `{}` does not exist", image.display()),
+            )
+        })
+        .collect()
+}
+
+/// Reports the images of the `images/` directory that the content never references. The
+/// header images, referenced by the metadata, are left out.
+pub fn check_orphan_images(tree: &Node, post_dir: &Path) -> Result<Vec<Finding>, String> {
+    let referenced: HashSet<PathBuf> = local_images(tree)
+        .into_iter()
+        .map(|(image, _)| image)
+        .collect();
+    let header = Metadata::header_path(post_dir);
+
+    Ok(list_files(&post_dir.join("images"))?
+        .into_iter()
+        .filter(|file| !file.starts_with(&header))
+        .filter_map(|file| file.strip_prefix(post_dir).ok().map(Path::to_path_buf))
+        .filter(|image| !referenced.contains(image))
+        .map(|image| {
+            Finding::new(
+                Severity::Info,
+                None,
+                format!("Image `{}` is never referenced", image.display()),
+            )
+        })
+        .collect())
+}
+
+/// Checks that the post has at least one keyword.
+pub fn check_keywords(metadata: &Metadata) -> Vec<Finding> {
+    if metadata.opengraph.keywords.is_empty() {
+        vec![Finding::new(
+            Severity::Warning,
+            None,
+            "The post has no keywords".to_string(),
+        )]
+    } else {
+        vec![]
+    }
+}
+
+/// Checks that the post has a description, short enough to be displayed in full.
+pub fn check_description(metadata: &Metadata) -> Vec<Finding> {
+    let length = metadata.opengraph.description.chars().count();
+    if length == 0 {
+        vec![Finding::new(
+            Severity::Warning,
+            None,
+            "The post has no description".to_string(),
+        )]
+    } else if length > MAX_DESCRIPTION_LENGTH {
+        vec![Finding::new(
+            Severity::Warning,
+            None,
+            format!(
+                "The description is {length} characters long, more than the recommended {MAX_DESCRIPTION_LENGTH}"
+            ),
+        )]
+    } else {
+        vec![]
+    }
+}
+
+/// Runs all the checks on the post and returns their findings, content issues first.
+pub fn lint(post: &Post) -> Result<Vec<Finding>, String> {
+    let tree = markdown::to_mdast(&post.content, &markdown::ParseOptions::gfm())
+        .map_err(|e| format!("Failed to parse content: {e}"))?;
+
+    let mut findings = vec![];
+    findings.extend(check_heading_levels(&tree));
+    findings.extend(check_duplicate_headings(&tree));
+    findings.extend(check_anchor_collisions(&tree));
+    findings.extend(check_image_alt(&tree));
+    findings.extend(check_missing_images(&tree, &post.path));
+    findings.sort_by_key(|finding| finding.line);
+    findings.extend(check_orphan_images(&tree, &post.path)?);
+    findings.extend(check_keywords(&post.metadata));
+    findings.extend(check_description(&post.metadata));
+    Ok(findings)
+}
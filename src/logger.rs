@@ -0,0 +1,83 @@
+/// This module sets up the logger. The messages are printed in color on stderr, at the
+/// `info` level by default. The level can be changed with the `RUST_LOG` environment
+/// variable or the `--log-level` option, and the messages can be copied to a file.
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Logs the messages to stderr and, optionally, to a file
+pub struct Logger {
+    stderr: env_logger::Logger,
+    file: Option<Mutex<File>>,
+}
+
+impl Logger {
+    /// Creates the logger. The given level overrides the default level of `RUST_LOG`.
+    /// The messages are appended to the log file, if any.
+    pub fn new(level: Option<LevelFilter>, log_file: Option<&Path>) -> Result<Self, String> {
+        let mut builder = colog::default_builder();
+        if let Some(level) = level {
+            builder.filter_level(level);
+        }
+
+        let file = log_file
+            .map(|path| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map(Mutex::new)
+                    .map_err(|e| format!("Failed to open log file {}: {e}", path.display()))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            stderr: builder.build(),
+            file,
+        })
+    }
+
+    /// Installs the logger as the global logger.
+    pub fn init(self) -> Result<(), String> {
+        log::set_max_level(self.stderr.filter());
+        log::set_boxed_logger(Box::new(self)).map_err(|e| e.to_string())
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.stderr.matches(record) {
+            return;
+        }
+
+        self.stderr.log(record);
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = writeln!(
+                    file,
+                    "{} [{}] {}",
+                    Utc::now().to_rfc3339(),
+                    record.level(),
+                    record.args()
+                );
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
@@ -1,146 +1,1368 @@
+use std::io::IsTerminal;
+
 use clap::Parser;
+use header::{HeaderCandidate, HeaderSearch};
 use post::Metadata;
 
-#[cfg(test)]
-mod tests;
-
 mod cli;
-mod header;
-mod post;
-mod utils;
+
+use blog::{
+    backup, build, config, export, feed, header, imaging, import, keywords, links, lint, logger,
+    manifest, pages, post, preview, publish, robots, search, spell, stats, utils, validate,
+};
+
+/// Returns the posts targeted by a tag or keyword command: the given posts, along with
+/// every post found under the root directory, if any.
+fn target_posts(posts: Vec<String>, root: Option<&str>) -> Vec<std::path::PathBuf> {
+    let mut paths: Vec<std::path::PathBuf> = posts.iter().map(utils::normalize_path).collect();
+    if let Some(root) = root {
+        paths.extend(
+            utils::find_posts(std::path::Path::new(root)).unwrap_or_else(|e| {
+                println!("Failed to gather posts: {e}");
+                std::process::exit(1);
+            }),
+        );
+    }
+    if paths.is_empty() {
+        println!("No post given, pass the paths to the posts or --root");
+        std::process::exit(1);
+    }
+    paths
+}
+
+/// Applies the update to the posts and reports the result for each of them when there
+/// are several. Exits with a non-zero status if any post failed.
+fn update_posts<F>(paths: &[std::path::PathBuf], fail_fast: bool, update: F)
+where
+    F: FnMut(&mut post::Post) -> Vec<String>,
+{
+    let results = post::Post::update_all(paths, fail_fast, update);
+    let mut failed = false;
+    for (path, result) in &results {
+        match result {
+            Err(e) if results.len() == 1 => println!("{e}"),
+            Err(e) => println!("[FAIL] {}: {e}", path.display()),
+            Ok(true) if results.len() > 1 => println!("[ OK ] {}", path.display()),
+            Ok(_) if results.len() > 1 => println!("[SKIP] {} (unchanged)", path.display()),
+            Ok(_) => {}
+        }
+        failed |= result.is_err();
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
 
 /// Handles the commands related to keywords
 fn handle_keyword_command(command: cli::Keyword) {
-    let mut post = post::Post::load(command.post).unwrap_or_else(|e| {
-        println!("Failed to load post: {e}");
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
         std::process::exit(1);
     });
+    let lenient = command.root.is_some();
+    let paths = target_posts(command.posts, command.root.as_deref());
+    let fail_fast = command.batch.fail_fast;
 
     match command.subcmd {
-        cli::KeywordSubCommand::Add { keywords } => {
-            for kw in keywords {
-                if let Err(e) = post.metadata.opengraph.add_keyword(kw) {
-                    println!("Unable to add keyword: {e}");
+        cli::KeywordSubCommand::Add { keywords } => update_posts(&paths, fail_fast, |post| {
+            let opengraph = &mut post.metadata.opengraph;
+            keywords
+                .iter()
+                .filter_map(|kw| {
+                    if lenient && opengraph.keywords.contains(kw) {
+                        return None;
+                    }
+                    opengraph
+                        .add_keyword(kw.clone(), config.posts.max_keywords)
+                        .err()
+                })
+                .map(|e| format!("Unable to add keyword: {e}"))
+                .collect()
+        }),
+        cli::KeywordSubCommand::Remove { keywords } => update_posts(&paths, fail_fast, |post| {
+            let opengraph = &mut post.metadata.opengraph;
+            keywords
+                .iter()
+                .filter_map(|kw| {
+                    if lenient && !opengraph.keywords.contains(kw) {
+                        return None;
+                    }
+                    opengraph.remove_keyword(kw).err()
+                })
+                .map(|e| format!("Unable to remove keyword: {e}"))
+                .collect()
+        }),
+        cli::KeywordSubCommand::List => {
+            for path in &paths {
+                match post::Post::load(path) {
+                    Ok(post) => {
+                        if paths.len() > 1 {
+                            println!("{}:", path.display());
+                        }
+                        post.metadata.opengraph.list_keywords();
+                    }
+                    Err(e) => println!("Failed to load post {}: {e}", path.display()),
                 }
             }
+        }
+        cli::KeywordSubCommand::Suggest { count, apply } => {
+            for path in &paths {
+                if paths.len() > 1 {
+                    println!("{}:", path.display());
+                }
+                if let Err(e) = suggest_keywords(path, count, apply, &config) {
+                    println!("Failed to suggest keywords for {}: {e}", path.display());
+                }
+            }
+        }
+    }
+}
+
+/// Prints the keywords suggested for the post, and adds the `apply` first ones to it.
+fn suggest_keywords(
+    path: &std::path::Path,
+    count: usize,
+    apply: Option<usize>,
+    config: &config::Config,
+) -> Result<(), String> {
+    let mut post = post::Post::load(path)?;
+    let suggestions = keywords::suggest(&post, count.max(apply.unwrap_or_default()))?;
+    if suggestions.is_empty() {
+        println!("No keywords to suggest");
+    }
+    for suggestion in suggestions.iter().take(count) {
+        println!(
+            "{} ({} occurrences, {:.1}%)",
+            suggestion.term,
+            suggestion.count,
+            suggestion.frequency * 100.0
+        );
+    }
 
-            if let Err(e) = post.save() {
-                println!("Unable to save post: {e}");
+    if let Some(apply) = apply {
+        let added =
+            keywords::apply_suggestions(&mut post, &suggestions, apply, config.posts.max_keywords)?;
+        post.save()?;
+        println!("Added keywords: {}", added.join(", "));
+    }
+    Ok(())
+}
+
+/// Handles the commands related to tags
+fn handle_tag_command(command: cli::Tag) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+    let lenient = command.root.is_some();
+    let paths = target_posts(command.posts, command.root.as_deref());
+    let fail_fast = command.batch.fail_fast;
+
+    match command.subcmd {
+        cli::TagSubCommand::Add { tags } => update_posts(&paths, fail_fast, |post| {
+            let info = &mut post.metadata.post;
+            tags.iter()
+                .filter_map(|tag| {
+                    if lenient && info.tags.contains(tag) {
+                        return None;
+                    }
+                    info.add_tag(tag.clone(), config.posts.max_tags).err()
+                })
+                .map(|e| format!("Unable to add tag: {e}"))
+                .collect()
+        }),
+        cli::TagSubCommand::Remove { tags } => update_posts(&paths, fail_fast, |post| {
+            let info = &mut post.metadata.post;
+            tags.iter()
+                .filter_map(|tag| {
+                    if lenient && !info.tags.contains(tag) {
+                        return None;
+                    }
+                    info.remove_tag(tag).err()
+                })
+                .map(|e| format!("Unable to remove tag: {e}"))
+                .collect()
+        }),
+        cli::TagSubCommand::Rename { from, to } => update_posts(&paths, fail_fast, |post| {
+            let info = &mut post.metadata.post;
+            if lenient && !info.tags.contains(&from) {
+                return vec![];
             }
+            info.rename_tag(&from, &to)
+                .err()
+                .map(|e| format!("Unable to rename tag: {e}"))
+                .into_iter()
+                .collect()
+        }),
+        cli::TagSubCommand::Merge { old, new } => {
+            let mut merged = 0;
+            update_posts(&paths, fail_fast, |post| {
+                match post.metadata.post.merge_tag(&old, &new) {
+                    Ok(changed) => {
+                        merged += usize::from(changed);
+                        vec![]
+                    }
+                    Err(e) => vec![format!("Unable to merge tag: {e}")],
+                }
+            });
+            println!("Merged tag `{old}` into `{new}` in {merged} posts");
         }
-        cli::KeywordSubCommand::Remove { keywords } => {
-            for kw in keywords {
-                if let Err(e) = post.metadata.opengraph.remove_keyword(&kw) {
-                    println!("Unable to remove keyword: {e}");
+        cli::TagSubCommand::Move { tag, position } => update_posts(&paths, fail_fast, |post| {
+            let info = &mut post.metadata.post;
+            if lenient && !info.tags.contains(&tag) {
+                return vec![];
+            }
+            info.move_tag(&tag, position)
+                .err()
+                .map(|e| format!("Unable to move tag: {e}"))
+                .into_iter()
+                .collect()
+        }),
+        cli::TagSubCommand::List => {
+            for path in &paths {
+                match post::Post::load(path) {
+                    Ok(post) => {
+                        if paths.len() > 1 {
+                            println!("{}:", path.display());
+                        }
+                        post.metadata.post.list_tags();
+                    }
+                    Err(e) => println!("Failed to load post {}: {e}", path.display()),
                 }
             }
+        }
+    }
+}
 
-            if let Err(e) = post.save() {
-                println!("Unable to save post: {e}");
-            }
+/// Handles the import of a markdown file as a new post
+fn handle_import_command(file: &str, title: Option<&str>, root: &str) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    match import::import(
+        &utils::normalize_path(file),
+        title,
+        std::path::Path::new(root),
+        &config,
+    ) {
+        Ok(post) => println!(
+            "Imported `{}` at {}",
+            post.metadata.post.title,
+            post.path.display()
+        ),
+        Err(e) => {
+            println!("Failed to import {file}: {e}");
+            std::process::exit(1);
         }
-        cli::KeywordSubCommand::List => {
-            post.metadata.opengraph.list_keywords();
+    }
+}
+
+/// Handles the setting of a metadata field of a post
+fn handle_set_command(path: &str, field: cli::SetField) {
+    let mut post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    match field {
+        cli::SetField::License { license } => {
+            post.metadata.post.license = Some(license).filter(|license| license != "none");
         }
     }
+    if let Err(e) = post.save() {
+        println!("Failed to save post: {e}");
+        std::process::exit(1);
+    }
 }
 
-/// Handles the commands related to tags
-fn handle_tag_command(command: cli::Tag) {
+/// Handles the commands related to the authors of a post
+fn handle_author_command(command: cli::Author) {
     let mut post = post::Post::load(command.post).unwrap_or_else(|e| {
         println!("Failed to load post: {e}");
         std::process::exit(1);
     });
+    let info = &mut post.metadata.post;
+
+    let result = match command.subcmd {
+        cli::AuthorSubCommand::Add { authors } => authors
+            .into_iter()
+            .try_for_each(|author| info.add_author(author)),
+        cli::AuthorSubCommand::Remove { authors } => authors
+            .iter()
+            .try_for_each(|author| info.remove_author(author)),
+        cli::AuthorSubCommand::List => {
+            info.list_authors();
+            return;
+        }
+    };
+
+    if let Err(e) = result.and_then(|()| post.save()) {
+        println!("Unable to update the authors: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Handles the edition of the content of a post
+fn handle_content_command(command: cli::ContentSubCommand) {
+    let (text, prepend) = match command {
+        cli::ContentSubCommand::Append(text) => (text, false),
+        cli::ContentSubCommand::Prepend(text) => (text, true),
+    };
+    let mut post = post::Post::load(&text.post).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    let added = match (text.text, text.from_file) {
+        (Some(text), _) => Ok(text),
+        (None, Some(file)) => std::fs::read_to_string(utils::normalize_path(&file))
+            .map_err(|e| format!("Failed to read {file}: {e}")),
+        (None, None) => std::io::read_to_string(std::io::stdin())
+            .map_err(|e| format!("Failed to read the standard input: {e}")),
+    };
+    let result = added.and_then(|added| {
+        if prepend {
+            post.prepend_content(&added);
+        } else {
+            post.append_content(&added);
+        }
+        post.save()
+    });
+    if let Err(e) = result {
+        println!("Unable to update the content: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Handles the backup of a post
+fn handle_backup_command(path: &str, output: Option<String>) {
+    let post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    match backup::backup(&post, output.map(utils::normalize_path)) {
+        Ok(archive) => println!("Backed up the post to {}", archive.display()),
+        Err(e) => {
+            println!("Failed to back up the post: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles the commands related to the header image of a post
+fn handle_header_command(command: cli::Header, json: bool, quiet: bool) {
+    if let Err(e) = run_header_command(command, json, quiet) {
+        println!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// Runs the header command, printing its output. Returns the message of the error if the
+/// command failed.
+fn run_header_command(command: cli::Header, json: bool, quiet: bool) -> Result<(), String> {
+    let mut post =
+        post::Post::load(command.post).map_err(|e| format!("Failed to load post: {e}"))?;
 
     match command.subcmd {
-        cli::TagSubCommand::Add { tags } => {
-            for tag in tags {
-                if let Err(e) = post.metadata.post.add_tag(tag) {
-                    println!("Unable to add tag: {e}");
-                }
+        cli::HeaderSubCommand::Choose { index } => post
+            .metadata
+            .choose_header(&post.path, index)
+            .map_err(|e| format!("Error while selecting the header: {e}")),
+        cli::HeaderSubCommand::Fetch {
+            amount,
+            page,
+            color,
+            min_width,
+            min_height,
+            per_keyword,
+            dry_run,
+        } => {
+            let config =
+                config::Config::load().map_err(|e| format!("Failed to load configuration: {e}"))?;
+            let search = HeaderSearch {
+                amount: amount.unwrap_or(config.header.amount),
+                page,
+                color,
+                min_width,
+                min_height,
+                per_keyword,
+                progress: !quiet,
+                dry_run,
+            };
+
+            let candidates =
+                header::providers(&config.header.providers, &header::ApiKeys::from_env())
+                    .and_then(|providers| {
+                        post.metadata
+                            .fetch_new_header_images(&post.path, &providers, &search)
+                    })
+                    .map_err(|e| format!("Error while fetching new posts: {e}"))?;
+            let candidates = candidates
+                .iter()
+                .map(|candidate| HeaderCandidate::new(candidate.index, &candidate.picture))
+                .collect::<Vec<_>>();
+            if json {
+                let candidates = serde_json::to_string_pretty(&candidates)
+                    .map_err(|e| format!("Unable to serialize candidate pictures: {e}"))?;
+                println!("{candidates}");
+            } else {
+                print!("{}", header::candidates_listing(&candidates, None));
             }
+            Ok(())
+        }
+        cli::HeaderSubCommand::List { thumbnails } => {
+            let candidates = Metadata::list_header_candidates(&post.path)
+                .map_err(|e| format!("Error while displaying candidate pictures: {e}"))?;
+            if json {
+                let candidates = serde_json::to_string_pretty(&candidates)
+                    .map_err(|e| format!("Unable to serialize candidate pictures: {e}"))?;
+                println!("{candidates}");
+            } else {
+                let thumbnail = |candidate: &HeaderCandidate| {
+                    imaging::ansi_thumbnail(
+                        &Metadata::candidate_image(&post.path, candidate.index),
+                        imaging::THUMBNAIL_WIDTH,
+                    )
+                };
+                let thumbnail: Option<&header::Thumbnail<'_>> =
+                    (thumbnails && std::io::stdout().is_terminal()).then_some(&thumbnail);
+                print!("{}", header::candidates_listing(&candidates, thumbnail));
+            }
+            Ok(())
+        }
+    }
+}
 
-            if let Err(e) = post.save() {
-                println!("Unable to save post: {e}");
+/// Handles the commands related to the images of a post
+fn handle_images_command(command: cli::Images) {
+    let post = post::Post::load(command.post).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    match command.subcmd {
+        cli::ImagesSubCommand::Add {
+            files,
+            slugify,
+            force,
+        } => {
+            let mut failed = false;
+            for file in files {
+                if let Err(e) = post.add_image(&utils::normalize_path(&file), slugify, force) {
+                    println!("Unable to add image: {e}");
+                    failed = true;
+                }
+            }
+            if failed {
+                std::process::exit(1);
             }
         }
-        cli::TagSubCommand::Remove { tags } => {
-            for tag in tags {
-                if let Err(e) = post.metadata.post.remove_tag(&tag) {
-                    println!("Unable to remove tag: {e}");
+        cli::ImagesSubCommand::List => match post.list_images() {
+            Ok(images) if images.is_empty() => println!("This post has no images"),
+            Ok(images) => {
+                for image in images {
+                    println!("* {}", image.display());
                 }
             }
+            Err(e) => println!("Unable to list images: {e}"),
+        },
+    }
+}
+
+/// Handles the build of one or all posts
+fn handle_build_command(command: cli::Build) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+    let configured = post::BuildOptions::from_config(&config);
+    let options = post::BuildOptions {
+        dry_run: command.dry_run,
+        output: command.output.map(std::path::PathBuf::from),
+        force: command.force,
+        lazy_images: command.lazy_images,
+        responsive_header: command.responsive_header,
+        no_images: command.no_images,
+        twitter: command.twitter,
+        flavor: command.flavor,
+        base_url: command.base_url.unwrap_or(configured.base_url),
+        math: command.math,
+        emoji: command.emoji,
+        title_from_meta: command.title_from_meta,
+        toc: command.toc,
+        heading_links: command.heading_links,
+        microformats: command.microformats,
+        include_drafts: command.include_drafts,
+        fail_fast: command.batch.fail_fast,
+        drafts_dir: command.drafts_dir.map(std::path::PathBuf::from),
+        related_count: command.related,
+        strict: command.strict,
+        compress: command.compress,
+        css: command.css.map(std::path::PathBuf::from),
+        template_dir: command.template_dir.map(std::path::PathBuf::from),
+        css_mode: command.css_mode,
+        favicon: command
+            .favicon
+            .map(std::path::PathBuf::from)
+            .or(configured.favicon),
+        resize_favicon: command.resize_favicon,
+        ignore_hook_errors: command.ignore_hook_errors,
+        ..configured
+    };
+
+    let start = std::time::Instant::now();
+    if command.all {
+        let root = std::path::Path::new(&command.path);
+        let summary = build::build_all(root, &options).unwrap_or_else(|e| {
+            println!("Failed to gather posts: {e}");
+            std::process::exit(1);
+        });
 
-            if let Err(e) = post.save() {
-                println!("Unable to save post: {e}");
+        for planned in &summary.planned {
+            println!("{planned}");
+        }
+        println!("{summary}");
+        if let Some(path) = &command.manifest {
+            let written = manifest::Manifest::all(&summary, root, &options, start.elapsed())
+                .and_then(|manifest| manifest.write(std::path::Path::new(path)));
+            if let Err(e) = written {
+                println!("Failed to write manifest: {e}");
+                std::process::exit(1);
             }
         }
-        cli::TagSubCommand::List => {
-            post.metadata.post.list_tags();
+        if !summary.failed.is_empty() {
+            std::process::exit(1);
         }
+        return;
     }
-}
 
-fn handle_header_command(command: cli::Header) {
-    let post = post::Post::load(command.post).unwrap_or_else(|e| {
+    // Building a post will create its output directory and write the post's content to an index.html file. It will also update the post's metadata file with the current date and time.
+    let mut post = post::Post::load(&command.path).unwrap_or_else(|e| {
         println!("Failed to load post: {e}");
         std::process::exit(1);
     });
 
-    match command.subcmd {
-        cli::HeaderSubCommand::Choose { index } => {
-            if let Err(e) = Metadata::choose_header(&post.path, index) {
-                println!("Error while selecting the header: {e}");
+    match preview::build_post(&mut post, &options, command.open, &preview::BrowserOpener) {
+        Ok(output) => {
+            for planned in &output.planned {
+                println!("{planned}");
             }
         }
-        cli::HeaderSubCommand::Fetch { amount } => {
-            if let Err(e) = post.metadata.fetch_new_header_images(&post.path, amount) {
-                println!("Error while fetching new posts: {e}");
+        Err(e) => {
+            println!("Failed to build post: {e}");
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(path) = &command.manifest {
+        let written = manifest::Manifest::post(&post, &options, start.elapsed())
+            .and_then(|manifest| manifest.write(std::path::Path::new(path)));
+        if let Err(e) = written {
+            println!("Failed to write manifest: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles the deletion of a post
+fn handle_delete_command(path: &str, yes: bool) {
+    let path = &utils::normalize_path(path);
+    if !utils::is_post_dir(path) {
+        println!("{} is not a blog post", path.display());
+        std::process::exit(1);
+    }
+
+    if !yes {
+        let title = post::Post::load(path)
+            .map(|post| post.metadata.post.title)
+            .unwrap_or_default();
+        let question = format!("Delete the post `{title}` at {}?", path.display());
+        let confirmed = utils::confirm(std::io::stdin().lock(), std::io::stdout(), &question)
+            .unwrap_or_else(|e| {
+                println!("Unable to read the answer: {e}");
+                std::process::exit(1);
+            });
+        if !confirmed {
+            println!("Aborting");
+            return;
+        }
+    }
+
+    if let Err(e) = post::Post::delete(path) {
+        println!("Failed to delete post: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Handles the publication of a post
+fn handle_publish_command(
+    path: String,
+    backend: Option<config::PublishBackend>,
+    output: Option<String>,
+) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+    let options = post::BuildOptions {
+        output: output.map(std::path::PathBuf::from),
+        ..Default::default()
+    };
+
+    println!("Publishing post: {path}");
+    let mut post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    let publisher = match backend.or(config.publish.backend) {
+        Some(backend) => publisher(backend, &config),
+        None => Err("No publish backend configured, use --backend or blog.toml".to_string()),
+    };
+
+    if let Err(e) = publisher.and_then(|publisher| post.publish(publisher.as_ref(), &options)) {
+        println!("Error while publishing post: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Handles the publication of the scheduled drafts that are due
+fn handle_publish_due_command(
+    root: &str,
+    backend: Option<config::PublishBackend>,
+    output: Option<String>,
+) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+    let options = post::BuildOptions {
+        output: output.map(std::path::PathBuf::from),
+        root: Some(std::path::PathBuf::from(root)),
+        ..post::BuildOptions::from_config(&config)
+    };
+    let schedule = publish::Schedule::load(std::path::Path::new(root), chrono::Utc::now())
+        .unwrap_or_else(|e| {
+            println!("Failed to gather the scheduled posts: {e}");
+            std::process::exit(1);
+        });
+
+    let mut failed = false;
+    if !schedule.due.is_empty() {
+        let publisher = match backend.or(config.publish.backend) {
+            Some(backend) => publisher(backend, &config),
+            None => Err("No publish backend configured, use --backend or blog.toml".to_string()),
+        }
+        .unwrap_or_else(|e| {
+            println!("Error while publishing posts: {e}");
+            std::process::exit(1);
+        });
+        for mut post in schedule.due {
+            match publish::publish_due(&mut post, publisher.as_ref(), &options) {
+                Ok(()) => println!("[ OK ] {}", post.path.display()),
+                Err(e) => {
+                    println!("[FAIL] {}: {e}", post.path.display());
+                    failed = true;
+                }
             }
         }
-        cli::HeaderSubCommand::List => {
-            if let Err(e) = Metadata::list_header_candidates(&post.path) {
-                println!("Error while displaying candidate pictures: {e}");
+    } else {
+        println!("No post is due");
+    }
+    for post in &schedule.pending {
+        if let Some(date) = post.metadata.post.scheduled_date {
+            println!("[WAIT] {} (scheduled for {date})", post.path.display());
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Handles the scheduling of the publication of a draft
+fn handle_schedule_command(path: &str, date: chrono::DateTime<chrono::Utc>) {
+    let mut post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+    if !post.metadata.post.is_draft() {
+        println!("The post is already published");
+        std::process::exit(1);
+    }
+
+    post.metadata.post.scheduled_date = Some(date);
+    if let Err(e) = post.save() {
+        println!("Unable to schedule the post: {e}");
+        std::process::exit(1);
+    }
+    println!("Scheduled `{}` for {date}", post.metadata.post.title);
+}
+
+/// Returns the publisher of the given backend.
+fn publisher(
+    backend: config::PublishBackend,
+    config: &config::Config,
+) -> Result<Box<dyn publish::Publisher>, String> {
+    match backend {
+        config::PublishBackend::S3 => publish::S3Publisher::from_env(&config.publish)
+            .map(|publisher| Box::new(publisher) as Box<dyn publish::Publisher>),
+        config::PublishBackend::Git => publish::GitPublisher::from_config(&config.publish.git)
+            .map(|publisher| Box::new(publisher) as Box<dyn publish::Publisher>),
+    }
+}
+
+/// Handles the reversion of a post to a draft
+fn handle_unpublish_command(
+    path: String,
+    keep_date: bool,
+    backend: Option<config::PublishBackend>,
+) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let mut post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    let publisher = backend
+        .or(config.publish.backend)
+        .map(|backend| publisher(backend, &config))
+        .transpose()
+        .unwrap_or_else(|e| {
+            println!("Failed to set up the publish backend: {e}");
+            std::process::exit(1);
+        });
+    if publisher.is_none() {
+        println!("No publish backend configured, only the local metadata is updated");
+    }
+
+    if let Err(e) = post.unpublish(publisher.as_deref(), keep_date) {
+        println!("Error while unpublishing post: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Handles the export of a post to another format
+fn handle_export_command(
+    path: String,
+    format: cli::ExportFormat,
+    output: Option<String>,
+    converter: &export::CommandConverter,
+) {
+    let post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+    let output = output.map(std::path::PathBuf::from);
+
+    let exported = match format {
+        cli::ExportFormat::Text => export::to_plain_text(&post.content).and_then(|text| {
+            let output = output.unwrap_or_else(|| post.path.join("export.txt"));
+            std::fs::write(&output, text)
+                .map(|()| output)
+                .map_err(|e| format!("Unable to write export: {e}"))
+        }),
+        cli::ExportFormat::Pdf => export::to_pdf(&post, converter, output),
+    };
+
+    match exported {
+        Ok(output) => println!("Exported post to {}", output.display()),
+        Err(e) => {
+            println!("Failed to export post: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles the generation of the blog's feed
+fn handle_feed_command(command: cli::Feed) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let root = std::path::Path::new(&command.root);
+    let posts = post::Post::load_published(root).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+    let posts = post::PostFilter::from(command.filter).apply(posts);
+
+    let document = match command.format {
+        cli::FeedFormat::Rss => feed::rss(&posts, root, &config),
+        cli::FeedFormat::Atom => feed::atom(&posts, root, &config),
+        cli::FeedFormat::Json => feed::json(&posts, root, &config).unwrap_or_else(|e| {
+            println!("Failed to generate feed: {e}");
+            std::process::exit(1);
+        }),
+    };
+
+    match command.output {
+        Some(output) => {
+            if let Err(e) = std::fs::write(&output, document) {
+                println!("Unable to write feed: {e}");
+                std::process::exit(1);
             }
         }
+        None => print!("{document}"),
     }
 }
 
-fn main() {
-    colog::init();
+/// Handles the generation of the tag pages
+fn handle_tag_pages_command(root: &str, output: &str) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
 
-    let args = cli::Cli::parse();
+    let root = std::path::Path::new(root);
+    let posts = post::Post::load_published(root).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
 
-    match args.subcmd {
-        cli::SubCommand::New { title } => {
-            let post = post::Post::new(title);
+    match pages::tag_pages(&posts, root, std::path::Path::new(output), &config) {
+        Ok(pages) => println!("Generated {} tag pages", pages.len()),
+        Err(e) => {
+            println!("Failed to generate tag pages: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles the generation of the archive pages
+fn handle_archives_command(root: &str, output: &str) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let root = std::path::Path::new(root);
+    let posts = post::Post::load_published(root).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+
+    match pages::archive_pages(&posts, root, std::path::Path::new(output), &config) {
+        Ok(pages) => println!("Generated {} archive pages", pages.len()),
+        Err(e) => {
+            println!("Failed to generate archive pages: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles the generation of the blog's index page
+fn handle_index_command(root: &str, output: &str, group_by_year: bool, filter: post::PostFilter) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let root = std::path::Path::new(root);
+    let posts = post::Post::load_published(root).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+    let posts = filter.apply(posts);
+
+    let output = std::path::Path::new(output);
+    if let Err(e) = pages::index_page(&posts, root, output, &config, group_by_year) {
+        println!("Failed to generate index page: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Handles the preview of a built post
+fn handle_open_command(path: &str, build: bool) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+    let mut post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    let options = post::BuildOptions::from_config(&config);
+    if build {
+        if let Err(e) = post.build(&options) {
+            println!("Failed to build post: {e}");
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = preview::open_post(&post, &options, &preview::BrowserOpener) {
+        println!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// Handles the comparison of a post to its last build
+fn handle_diff_command(path: &str, output: Option<String>) {
+    let post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+    let options = post::BuildOptions {
+        output: output.map(std::path::PathBuf::from),
+        ..Default::default()
+    };
+
+    match post.diff_since_build(&options) {
+        Ok(None) => println!("The post has not been built yet"),
+        Ok(Some(diff)) if diff.is_empty() => println!("No changes since the last build"),
+        Ok(Some(diff)) => print!("{diff}"),
+        Err(e) => {
+            println!("Failed to compare the post to its last build: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles the generation of the robots.txt file
+fn handle_robots_command(output: &str) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    match robots::write_robots(std::path::Path::new(output), &config) {
+        Ok(path) => println!("Wrote {}", path.display()),
+        Err(e) => {
+            println!("Failed to write robots.txt: {e}");
+            std::process::exit(1);
+        }
+    }
+}
 
-            if let Err(e) = post.save() {
-                println!("Failed to save post: {e}");
+/// Handles the computation of the blog's statistics
+fn handle_stats_command(root: &str, json: bool) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+    let posts = post::Post::load_all(std::path::Path::new(root)).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+
+    let stats = stats::Stats::compute(&posts, config.posts.words_per_minute);
+    if json {
+        match serde_json::to_string_pretty(&stats) {
+            Ok(stats) => println!("{stats}"),
+            Err(e) => println!("Unable to serialize statistics: {e}"),
+        }
+    } else {
+        print!("{stats}");
+    }
+}
+
+/// Handles the report of the usage of the tags and keywords
+fn handle_taxonomy_command(root: &str, tags: bool, keywords: bool, json: bool) {
+    let posts = post::Post::load_all(std::path::Path::new(root)).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+
+    // Without restriction, both the tags and the keywords are listed
+    let both = !tags && !keywords;
+    let taxonomy = stats::Taxonomy::compute(&posts, tags || both, keywords || both);
+    if json {
+        match serde_json::to_string_pretty(&taxonomy) {
+            Ok(taxonomy) => println!("{taxonomy}"),
+            Err(e) => println!("Unable to serialize the taxonomy: {e}"),
+        }
+    } else {
+        print!("{taxonomy}");
+    }
+}
+
+/// Handles the listing of the posts
+fn handle_list_command(root: &str, filter: post::PostFilter) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let mut posts = post::Post::load_all(std::path::Path::new(root)).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+    post::Post::sort_newest_first(&mut posts);
+    let posts = filter.apply(posts);
+
+    for post in &posts {
+        let date = post
+            .metadata
+            .post
+            .published_date
+            .map_or_else(|| "draft".to_string(), |date| config.display.format(date));
+        println!(
+            "{date}  {}  ({})",
+            post.metadata.post.title,
+            post.path.display()
+        );
+    }
+}
+
+/// Handles the search of the posts
+fn handle_search_command(command: cli::Search, json: bool) {
+    let posts = post::Post::load_all(std::path::Path::new(&command.root)).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+    let mut query = search::Query::new(&command.query, command.regex).unwrap_or_else(|e| {
+        println!("{e}");
+        std::process::exit(1);
+    });
+    query.tag_only = command.tag_only;
+    query.context = command.context;
+
+    let results = search::search(&posts, &query);
+    if json {
+        match serde_json::to_string_pretty(&results) {
+            Ok(results) => println!("{results}"),
+            Err(e) => println!("Unable to serialize the results: {e}"),
+        }
+    } else if results.is_empty() {
+        println!("No post matches `{}`", command.query);
+    } else {
+        for result in results {
+            println!("{result}");
+        }
+    }
+}
+
+/// Handles the generation of the search index
+fn handle_search_index_command(root: &str, output: &str, max_chars: Option<usize>) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let root = std::path::Path::new(root);
+    let posts = post::Post::load_published(root).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+
+    let index = search::search_index(&posts, root, &config, max_chars).unwrap_or_else(|e| {
+        println!("Failed to generate the search index: {e}");
+        std::process::exit(1);
+    });
+    match std::fs::write(output, index) {
+        Ok(()) => println!("Indexed {} posts into {output}", posts.len()),
+        Err(e) => {
+            println!("Unable to write the search index: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles the quality checks of a post
+fn handle_lint_command(path: String, json: bool) {
+    let post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    let findings = lint::lint(&post).unwrap_or_else(|e| {
+        println!("Failed to lint post: {e}");
+        std::process::exit(1);
+    });
+    if json {
+        match serde_json::to_string_pretty(&findings) {
+            Ok(findings) => println!("{findings}"),
+            Err(e) => println!("Unable to serialize the findings: {e}"),
+        }
+    } else if findings.is_empty() {
+        println!("No issues found");
+    } else {
+        for finding in &findings {
+            println!("{finding}");
+        }
+    }
+
+    if findings
+        .iter()
+        .any(|finding| finding.severity == lint::Severity::Error)
+    {
+        std::process::exit(1);
+    }
+}
+
+/// Handles the spell-check of a post
+fn handle_spell_command(
+    path: String,
+    add: &[String],
+    dictionary: &str,
+    wordlist: &str,
+    json: bool,
+) {
+    let wordlist = std::path::Path::new(wordlist);
+    for word in add {
+        match spell::add_word(wordlist, word) {
+            Ok(true) => println!("Added `{word}` to {}", wordlist.display()),
+            Ok(false) => println!("`{word}` is already in {}", wordlist.display()),
+            Err(e) => {
+                println!("Failed to add `{word}`: {e}");
+                std::process::exit(1);
             }
         }
-        cli::SubCommand::Build { path } => {
-            // Building a post will create its output directory and write the post's content to an index.html file. It will also update the post's metadata file with the current date and time.
-            let mut post = post::Post::load(path).unwrap_or_else(|e| {
-                println!("Failed to load post: {e}");
+    }
+
+    let post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+    let unknown = spell::Dictionary::load(std::path::Path::new(dictionary), wordlist)
+        .and_then(|dictionary| spell::check(&post.content, &dictionary))
+        .unwrap_or_else(|e| {
+            println!("Failed to check spelling: {e}");
+            std::process::exit(1);
+        });
+
+    if json {
+        match serde_json::to_string_pretty(&unknown) {
+            Ok(unknown) => println!("{unknown}"),
+            Err(e) => println!("Unable to serialize the unknown words: {e}"),
+        }
+    } else if unknown.is_empty() {
+        println!("No unknown words");
+    } else {
+        for word in &unknown {
+            println!("line {}: {}", word.line, word.word);
+        }
+    }
+
+    if !unknown.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Handles the verification of a post's links
+fn handle_check_links_command(path: String, external: bool) {
+    let post = post::Post::load(path).unwrap_or_else(|e| {
+        println!("Failed to load post: {e}");
+        std::process::exit(1);
+    });
+
+    // Prefer the built output when there is one, as it is what gets published
+    let dist = post.path.join("dist");
+    let built = std::fs::read_to_string(dist.join("index.html"));
+    let (html, base) = match built {
+        Ok(html) => (html, dist),
+        Err(_) => (
+            post.render_html(&post::BuildOptions::default())
+                .unwrap_or_else(|e| {
+                    println!("Failed to render post: {e}");
+                    std::process::exit(1);
+                }),
+            post.path.clone(),
+        ),
+    };
+
+    let reports = links::check_links(&html, &base, external).unwrap_or_else(|e| {
+        println!("Failed to check links: {e}");
+        std::process::exit(1);
+    });
+
+    for report in &reports {
+        println!("{report}");
+    }
+
+    let count = |status: fn(&links::LinkStatus) -> bool| {
+        reports
+            .iter()
+            .filter(|report| status(&report.status))
+            .count()
+    };
+    let broken = count(|status| matches!(status, links::LinkStatus::Broken(_)));
+    println!(
+        "{} ok, {broken} broken, {} skipped",
+        count(|status| *status == links::LinkStatus::Ok),
+        count(|status| *status == links::LinkStatus::Skipped),
+    );
+
+    if broken > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Handles the validation of the references between posts
+fn handle_validate_links_command(root: &str) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let root = std::path::Path::new(root);
+    let posts = post::Post::load_all(root).unwrap_or_else(|e| {
+        println!("Failed to gather posts: {e}");
+        std::process::exit(1);
+    });
+
+    let mut broken = 0;
+    for post in &posts {
+        let reports = links::check_post_references(post, root, &config.site.base_url)
+            .unwrap_or_else(|e| {
+                println!("Failed to check {}: {e}", post.path.display());
                 std::process::exit(1);
             });
+        for report in reports
+            .iter()
+            .filter(|report| matches!(report.status, links::LinkStatus::Broken(_)))
+        {
+            println!("{}: {report}", post.path.display());
+            broken += 1;
+        }
+    }
 
-            if let Err(e) = post.build() {
-                println!("Failed to build post: {e}");
-            }
+    println!("{} posts checked, {broken} broken references", posts.len());
+    if broken > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Handles the validation of the whole blog
+fn handle_validate_command(root: &str, fail_fast: bool, json: bool) {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        println!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    });
+
+    let report = validate::validate_all(std::path::Path::new(root), &config.posts, fail_fast)
+        .unwrap_or_else(|e| {
+            println!("Failed to gather posts: {e}");
+            std::process::exit(1);
+        });
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(report) => println!("{report}"),
+            Err(e) => println!("Unable to serialize the report: {e}"),
         }
-        cli::SubCommand::Publish { path } => {
-            println!("Publishing post: {path}");
-            let mut post = post::Post::load(path).unwrap_or_else(|e| {
-                println!("Failed to load post: {e}");
+    } else {
+        print!("{report}");
+    }
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
+
+fn main() {
+    let args = cli::Cli::parse();
+
+    let log_file = args.log_file.as_deref().map(utils::normalize_path);
+    if let Err(e) =
+        logger::Logger::new(args.log_level, log_file.as_deref()).and_then(logger::Logger::init)
+    {
+        println!("Failed to set up the logger: {e}");
+        std::process::exit(1);
+    }
+    let env_file = args.env_file.as_deref().map(utils::normalize_path);
+    if let Err(e) = utils::load_env(env_file.as_deref()) {
+        println!("{e}");
+        std::process::exit(1);
+    }
+
+    match args.subcmd {
+        cli::SubCommand::New {
+            title,
+            interactive,
+            force,
+        } => {
+            let config = config::Config::load().unwrap_or_else(|e| {
+                println!("Failed to load configuration: {e}");
                 std::process::exit(1);
             });
 
-            if let Err(e) = post.publish() {
-                println!("Error while publishing post: {e}");
+            let post = if interactive {
+                post::Post::prompt(
+                    std::io::stdin().lock(),
+                    std::io::stdout(),
+                    title.as_deref(),
+                    &config,
+                )
+            } else {
+                post::Post::new(title.unwrap_or_default())
+                    .with_path_format(&config.posts.path_format, &config.slug)
+            };
+            match post {
+                Ok(mut post) => {
+                    if let Err(e) = post.create(force) {
+                        println!("Failed to save post: {e}");
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => println!("Failed to create post: {e}"),
             }
         }
+        cli::SubCommand::Build(command) => {
+            handle_build_command(command);
+        }
+        cli::SubCommand::Delete { path, yes } => {
+            handle_delete_command(&path, yes);
+        }
+        cli::SubCommand::Set { path, field } => {
+            handle_set_command(&path, field);
+        }
+        cli::SubCommand::Author(command) => {
+            handle_author_command(command);
+        }
+        cli::SubCommand::Content(command) => {
+            handle_content_command(command);
+        }
+        cli::SubCommand::Import { file, title, root } => {
+            handle_import_command(&file, title.as_deref(), &root);
+        }
+        cli::SubCommand::Backup { path, output } => {
+            handle_backup_command(&path, output);
+        }
+        cli::SubCommand::Repair { path } => match post::Post::repair(path) {
+            Ok(post) => println!(
+                "Regenerated the metadata of `{}` at {}",
+                post.metadata.post.title,
+                post.path.display()
+            ),
+            Err(e) => {
+                println!("Failed to repair post: {e}");
+                std::process::exit(1);
+            }
+        },
+        cli::SubCommand::Publish {
+            path: Some(path),
+            backend,
+            output,
+            ..
+        } => {
+            handle_publish_command(path, backend, output);
+        }
+        cli::SubCommand::Publish {
+            path: None,
+            backend,
+            root,
+            output,
+            ..
+        } => {
+            handle_publish_due_command(&root, backend, output);
+        }
+        cli::SubCommand::Schedule { path, date } => {
+            handle_schedule_command(&path, date);
+        }
+        cli::SubCommand::Unpublish {
+            path,
+            keep_date,
+            backend,
+        } => {
+            handle_unpublish_command(path, keep_date, backend);
+        }
         cli::SubCommand::Tag(command) => {
             handle_tag_command(command);
         }
@@ -148,7 +1370,89 @@ fn main() {
             handle_keyword_command(command);
         }
         cli::SubCommand::Header(command) => {
-            handle_header_command(command);
+            handle_header_command(command, args.json, args.quiet);
+        }
+        cli::SubCommand::Images(command) => {
+            handle_images_command(command);
+        }
+        cli::SubCommand::Export {
+            path,
+            format,
+            output,
+            pdf_tool,
+            pdf_binary,
+        } => {
+            let converter = export::CommandConverter::new(pdf_tool, pdf_binary);
+            handle_export_command(path, format, output, &converter);
+        }
+        cli::SubCommand::Feed(command) => {
+            handle_feed_command(command);
+        }
+        cli::SubCommand::TagPages { root, output } => {
+            handle_tag_pages_command(&root, &output);
+        }
+        cli::SubCommand::Archives { root, output } => {
+            handle_archives_command(&root, &output);
+        }
+        cli::SubCommand::Open { path, build } => {
+            handle_open_command(&path, build);
+        }
+        cli::SubCommand::Diff { path, output } => {
+            handle_diff_command(&path, output);
+        }
+        cli::SubCommand::Robots { output } => {
+            handle_robots_command(&output);
+        }
+        cli::SubCommand::Index {
+            root,
+            output,
+            group_by_year,
+            filter,
+        } => {
+            handle_index_command(&root, &output, group_by_year, filter.into());
+        }
+        cli::SubCommand::List { root, filter } => {
+            handle_list_command(&root, filter.into());
+        }
+        cli::SubCommand::Stats { root } => {
+            handle_stats_command(&root, args.json);
+        }
+        cli::SubCommand::Taxonomy {
+            root,
+            tags,
+            keywords,
+        } => {
+            handle_taxonomy_command(&root, tags, keywords, args.json);
+        }
+        cli::SubCommand::Search(command) => {
+            handle_search_command(command, args.json);
+        }
+        cli::SubCommand::SearchIndex {
+            root,
+            output,
+            max_chars,
+        } => {
+            handle_search_index_command(&root, &output, max_chars);
+        }
+        cli::SubCommand::Lint { path } => {
+            handle_lint_command(path, args.json);
+        }
+        cli::SubCommand::Spell {
+            path,
+            add,
+            dictionary,
+            wordlist,
+        } => {
+            handle_spell_command(path, &add, &dictionary, &wordlist, args.json);
+        }
+        cli::SubCommand::CheckLinks { path, external } => {
+            handle_check_links_command(path, external);
+        }
+        cli::SubCommand::ValidateLinks { root } => {
+            handle_validate_links_command(&root);
+        }
+        cli::SubCommand::Validate { root, batch } => {
+            handle_validate_command(&root, batch.fail_fast, args.json);
         }
     }
 }
@@ -0,0 +1,123 @@
+/// This module describes what a build produced, for deployment tooling: the files written
+/// for each post with their size and hash, the options of the build and how long it took.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::build::{BuildSummary, BUILD_CACHE};
+use crate::post::{BuildOptions, Post, SOURCE_SNAPSHOT};
+use crate::utils::{hex, list_files};
+
+#[derive(Debug, Serialize)]
+/// A file written by the build
+pub struct ManifestFile {
+    /// The path of the file, relative to the output directory of the post
+    pub path: PathBuf,
+    pub size: u64,
+    /// The SHA-256 hash of the content of the file, in hexadecimal
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize)]
+/// The files written by the build of a post
+pub struct PostManifest {
+    pub post: PathBuf,
+    pub output: PathBuf,
+    pub files: Vec<ManifestFile>,
+    /// The time taken by the build of the post, in seconds
+    pub duration: f64,
+}
+
+#[derive(Debug, Serialize)]
+/// The report of a build
+pub struct Manifest {
+    pub options: BuildOptions,
+    pub posts: Vec<PostManifest>,
+    /// The time taken by the whole build, in seconds
+    pub duration: f64,
+}
+
+/// Computes the SHA-256 hash of the file.
+fn file_hash(path: &Path) -> Result<String, String> {
+    let content = fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    Ok(hex(&Sha256::digest(content)))
+}
+
+impl PostManifest {
+    /// Lists the files of the output directory of the post, built with the given options.
+    pub fn new(post: &Post, options: &BuildOptions, duration: Duration) -> Result<Self, String> {
+        let output = post.output_path(options);
+        let mut files = vec![];
+        for file in list_files(&output)? {
+            let path = file.strip_prefix(&output).unwrap_or(&file).to_path_buf();
+            if path == Path::new(BUILD_CACHE) || path == Path::new(SOURCE_SNAPSHOT) {
+                continue;
+            }
+            let size = fs::metadata(&file)
+                .map_err(|e| format!("Failed to read {}: {e}", file.display()))?
+                .len();
+            files.push(ManifestFile {
+                sha256: file_hash(&file)?,
+                path,
+                size,
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self {
+            post: post.path.clone(),
+            output,
+            files,
+            duration: duration.as_secs_f64(),
+        })
+    }
+}
+
+impl Manifest {
+    /// Describes the build of a single post.
+    pub fn post(post: &Post, options: &BuildOptions, duration: Duration) -> Result<Self, String> {
+        Ok(Self {
+            options: options.clone(),
+            posts: vec![PostManifest::new(post, options, duration)?],
+            duration: duration.as_secs_f64(),
+        })
+    }
+
+    /// Describes the build of all the posts under the given root, gathering the posts that
+    /// were built successfully.
+    pub fn all(
+        summary: &BuildSummary,
+        root: &Path,
+        options: &BuildOptions,
+        duration: Duration,
+    ) -> Result<Self, String> {
+        let options = BuildOptions {
+            output: Some(options.output.clone().unwrap_or_else(|| root.join("dist"))),
+            root: Some(root.to_path_buf()),
+            ..options.clone()
+        };
+        let posts = summary
+            .succeeded
+            .iter()
+            .zip(&summary.durations)
+            .map(|(path, duration)| PostManifest::new(&Post::load(path)?, &options, *duration))
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            options,
+            posts,
+            duration: duration.as_secs_f64(),
+        })
+    }
+
+    /// Writes the manifest as JSON into the given file.
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize manifest: {e}"))?;
+        fs::write(path, content)
+            .map_err(|e| format!("Failed to write manifest {}: {e}", path.display()))
+    }
+}
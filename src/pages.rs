@@ -0,0 +1,194 @@
+/// This module generates the blog-wide HTML pages, listing several posts at once
+/// (e.g. the page of each tag, or of each year and month of publication). The pages are
+/// rendered with the same template as the posts themselves.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Month};
+
+use crate::config::Config;
+use crate::post::Post;
+use crate::template::Template;
+use crate::utils::{create_path, escape_xml};
+
+/// The maximum length of the excerpts shown in the listings
+const EXCERPT_LENGTH: usize = 200;
+
+/// Renders an HTML list of the given posts, each entry linking to the post and showing
+/// its title, publication date, reading time and description. The description falls
+/// back to an excerpt of the content when the post has none.
+pub fn listing_html(posts: &[&Post], root: &Path, config: &Config) -> String {
+    let mut html = String::from("<ul class=\"post-list\">\n");
+    for post in posts {
+        html.push_str("  <li>\n");
+        html.push_str(&format!(
+            "    <a href=\"{}\">{}</a>\n",
+            escape_xml(&post.url(config, root)),
+            escape_xml(&post.metadata.post.title)
+        ));
+        if let Some(published) = post.metadata.post.published_date {
+            html.push_str(&format!(
+                "    <time datetime=\"{}\">{}</time>\n",
+                published.to_rfc3339(),
+                escape_xml(&config.display.format(published))
+            ));
+        }
+        html.push_str(&format!(
+            "    <span class=\"reading-time\">{} min read</span>\n",
+            post.reading_time(config.posts.words_per_minute)
+        ));
+
+        let summary = if post.metadata.opengraph.description.is_empty() {
+            post.excerpt(EXCERPT_LENGTH)
+        } else {
+            post.metadata.opengraph.description.clone()
+        };
+        if !summary.is_empty() {
+            html.push_str(&format!("    <p>{}</p>\n", escape_xml(&summary)));
+        }
+        html.push_str("  </li>\n");
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+/// Writes a page listing the given content under the given directory, as `index.html`.
+fn write_page(directory: &Path, title: &str, content: String) -> Result<PathBuf, String> {
+    create_path(directory)?;
+    let page = Template::page().render(&[("title", escape_xml(title)), ("content", content)]);
+
+    let page_path = directory.join("index.html");
+    fs::write(&page_path, page).map_err(|e| format!("Failed to write page: {e}"))?;
+    Ok(page_path)
+}
+
+/// Generates the landing page of the blog as `<output>/index.html`, listing all the given
+/// posts in the order they are given. The posts can optionally be grouped by year of
+/// publication.
+pub fn index_page(
+    posts: &[Post],
+    root: &Path,
+    output: &Path,
+    config: &Config,
+    group_by_year: bool,
+) -> Result<PathBuf, String> {
+    let mut content = format!("<h1>{}</h1>\n", escape_xml(&config.site.title));
+    if group_by_year {
+        let mut years: Vec<(i32, Vec<&Post>)> = vec![];
+        for post in posts {
+            let year = post
+                .metadata
+                .post
+                .published_date
+                .map_or(0, |date| date.year());
+            match years.last_mut() {
+                Some((last_year, year_posts)) if *last_year == year => year_posts.push(post),
+                _ => years.push((year, vec![post])),
+            }
+        }
+
+        for (year, year_posts) in years {
+            content.push_str(&format!("<h2>{year}</h2>\n"));
+            content.push_str(&listing_html(&year_posts, root, config));
+        }
+    } else {
+        let posts: Vec<&Post> = posts.iter().collect();
+        content.push_str(&listing_html(&posts, root, config));
+    }
+
+    write_page(output, &config.site.title, content)
+}
+
+/// Generates a page per tag under `<output>/tags/<slug>/index.html`, each listing the
+/// posts carrying that tag. Returns the paths of the generated pages.
+pub fn tag_pages(
+    posts: &[Post],
+    root: &Path,
+    output: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>, String> {
+    let mut tags: BTreeMap<&str, Vec<&Post>> = BTreeMap::new();
+    for post in posts {
+        for tag in &post.metadata.post.tags {
+            tags.entry(tag.as_str()).or_default().push(post);
+        }
+    }
+
+    let mut pages = vec![];
+    for (tag, tagged_posts) in tags {
+        let directory = output.join("tags").join(config.slug.slugify(tag));
+        let content = format!(
+            "<h1>{}</h1>\n{}",
+            escape_xml(tag),
+            listing_html(&tagged_posts, root, config)
+        );
+        pages.push(write_page(&directory, tag, content)?);
+    }
+
+    Ok(pages)
+}
+
+/// Returns the English name of the month, from its number.
+fn month_name(month: u32) -> &'static str {
+    u8::try_from(month)
+        .ok()
+        .and_then(|month| Month::try_from(month).ok())
+        .map_or("", |month| month.name())
+}
+
+/// Generates the archive pages of the published posts: `<output>/<YYYY>/index.html` for
+/// every year, listing its posts month by month with links to the month pages, and
+/// `<output>/<YYYY>/<MM>/index.html` for every month. The posts without a publication
+/// date are left out; the others keep the order they are given in. Returns the paths
+/// of the generated pages.
+pub fn archive_pages(
+    posts: &[Post],
+    root: &Path,
+    output: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>, String> {
+    let mut years: BTreeMap<i32, BTreeMap<u32, Vec<&Post>>> = BTreeMap::new();
+    for post in posts {
+        if let Some(published) = post.metadata.post.published_date {
+            years
+                .entry(published.year())
+                .or_default()
+                .entry(published.month())
+                .or_default()
+                .push(post);
+        }
+    }
+
+    let mut pages = vec![];
+    for (year, months) in years.iter().rev() {
+        let year_directory = output.join(year.to_string());
+        let mut year_content = format!("<h1>{year}</h1>\n");
+        for (month, month_posts) in months.iter().rev() {
+            let title = format!("{} {year}", month_name(*month));
+            year_content.push_str(&format!(
+                "<h2><a href=\"{month:02}/\">{}</a></h2>\n{}",
+                month_name(*month),
+                listing_html(month_posts, root, config)
+            ));
+
+            let month_content = format!(
+                "<h1>{}</h1>\n{}",
+                escape_xml(&title),
+                listing_html(month_posts, root, config)
+            );
+            pages.push(write_page(
+                &year_directory.join(format!("{month:02}")),
+                &title,
+                month_content,
+            )?);
+        }
+        pages.push(write_page(
+            &year_directory,
+            &year.to_string(),
+            year_content,
+        )?);
+    }
+
+    Ok(pages)
+}
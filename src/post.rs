@@ -1,13 +1,207 @@
+use std::fmt;
 use std::fs;
+use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Datelike, Utc};
 use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use slugify::slugify;
 
-use crate::header::{get_new_candidates, PexelPicture};
-use crate::utils::{copy_dir_all, create_path};
+use crate::compress::{self, CompressionFormat};
+use crate::config::{Config, DisplayConfig, HooksConfig, MarkdownConfig, PostsConfig, SlugConfig};
+use crate::header::{
+    candidate_index, candidate_name, fetch_candidates, Candidate, HeaderCandidate, HeaderCredit,
+    HeaderSearch, ImageProvider, Picture,
+};
+use crate::hooks;
+use crate::imaging;
+use crate::links;
+use crate::lint;
+use crate::publish::Publisher;
+use crate::related;
+use crate::render::{self, CssMode, Flavor, MathMode};
+use crate::template::Template;
+use crate::utils::{
+    copy_dir_all, create_path, escape_xml, find_posts, hex, is_image, is_post_dir, list_files,
+    normalize_path, prompt, relative_to,
+};
+
+/// The directory of the output the drafts are built into by default
+pub const DRAFTS_DIR: &str = "drafts";
+
+/// The snapshot of the content of a post at its last build, stored in its output directory
+/// and left out of its publication
+pub const SOURCE_SNAPSHOT: &str = ".source.md";
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Restricts the posts of a listing, sorted newest first, to the newest ones
+pub struct PostFilter {
+    /// Keeps at most this number of posts
+    pub limit: Option<usize>,
+    /// Keeps the posts published on or after this date, leaving the drafts out
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl PostFilter {
+    /// Filters the given posts, sorted newest first.
+    pub fn apply(&self, posts: Vec<Post>) -> Vec<Post> {
+        posts
+            .into_iter()
+            .filter(|post| match self.since {
+                Some(since) => post
+                    .metadata
+                    .post
+                    .published_date
+                    .is_some_and(|date| date >= since && !post.metadata.post.is_draft()),
+                None => true,
+            })
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+/// The options changing the way a post is built
+pub struct BuildOptions {
+    /// Only reports what would be written, without touching the disk
+    pub dry_run: bool,
+    /// The directory to build the post into, instead of the post's `dist/` directory.
+    /// The post is written under its path relative to the root of the blog.
+    pub output: Option<PathBuf>,
+    /// The root of the blog, defaults to the current directory
+    pub root: Option<PathBuf>,
+    /// Rebuilds the posts even if they did not change since their last build
+    pub force: bool,
+    /// Lazily loads the images of the content
+    pub lazy_images: bool,
+    /// Generates resized variants of the header and serves it responsively
+    pub responsive_header: bool,
+    /// Skips copying the images of the post and generating the header variants, the
+    /// references of the page to them are left as is
+    pub no_images: bool,
+    /// Adds the Twitter card meta tags, even if the post has no header image
+    pub twitter: bool,
+    /// The markdown flavor the content is written in
+    pub flavor: Flavor,
+    /// The markdown constructs to render
+    pub markdown: MarkdownConfig,
+    /// Renders the `$...$` and `$$...$$` math of the content
+    pub math: Option<MathMode>,
+    /// Replaces the `:shortcode:` sequences of the content by the corresponding emoji
+    pub emoji: bool,
+    /// The base URL of the site, links to other hosts open in a new tab
+    pub base_url: String,
+    /// Renders the title of the metadata as the heading of the page, removing the
+    /// matching `# Title` from the top of the content
+    pub title_from_meta: bool,
+    /// Adds a table of contents of the headings above the content
+    pub toc: bool,
+    /// Appends a link to itself to each heading of the content
+    pub heading_links: bool,
+    /// Adds the microformats2 h-entry markup to the page, for IndieWeb readers
+    pub microformats: bool,
+    /// The number of related posts listed below the content when building all the
+    /// posts at once
+    pub related_count: usize,
+    /// The title and URL of the posts related to the built post
+    #[serde(skip)]
+    pub related: Vec<(String, String)>,
+    /// The title and URL of the post published right before the built post, when building
+    /// all the posts at once
+    #[serde(skip)]
+    pub previous: Option<(String, String)>,
+    /// The title and URL of the post published right after the built post, when building
+    /// all the posts at once
+    #[serde(skip)]
+    pub next: Option<(String, String)>,
+    /// Fails the build when the post raises warnings, instead of logging them
+    pub strict: bool,
+    /// Writes compressed siblings of the built text files
+    pub compress: Option<CompressionFormat>,
+    /// The stylesheet of the page, the default theme if none is given
+    pub css: Option<PathBuf>,
+    /// The directory of the `template.html` wrapping the page and of its partials, the
+    /// default template if none is given
+    pub template_dir: Option<PathBuf>,
+    /// The directory the drafts are built into, under their path relative to the root of
+    /// the blog. Defaults to the `drafts/` directory of the output, if any
+    pub drafts_dir: Option<PathBuf>,
+    /// Builds the drafts along with the published posts when building all the posts
+    pub include_drafts: bool,
+    /// Stops building at the first failing post when building all the posts
+    pub fail_fast: bool,
+    /// The commands run before and after the build
+    pub hooks: HooksConfig,
+    /// Only logs the failures of the hooks, instead of failing the build
+    pub ignore_hook_errors: bool,
+    /// Whether the stylesheet is inlined in the page or linked
+    pub css_mode: CssMode,
+    /// The image copied next to the page as its favicon
+    pub favicon: Option<PathBuf>,
+    /// Generates 32x32 and 16x16 PNG variants of the favicon
+    pub resize_favicon: bool,
+    /// The timezone and the format the publication date of the byline is displayed in
+    pub display: DisplayConfig,
+}
+
+impl BuildOptions {
+    /// Returns the options set by the configuration: the markdown constructs, the base URL,
+    /// the favicon, the hooks and the display of the dates. The others are left to their
+    /// default.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            markdown: config.markdown.clone(),
+            base_url: config.site.base_url.clone(),
+            favicon: Some(&config.site.favicon)
+                .filter(|favicon| !favicon.is_empty())
+                .map(PathBuf::from),
+            hooks: config.hooks.clone(),
+            display: config.display.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The files written by the build of a post
+pub struct BuildOutput {
+    /// The directory the post was built into
+    pub directory: PathBuf,
+    /// The page of the post
+    pub index: PathBuf,
+    /// The other files written: the copied images, their variants, the stylesheet, ...
+    pub assets: Vec<PathBuf>,
+    /// In dry-run mode, the writes the build would have made, for the caller to report
+    pub planned: Vec<PlannedWrite>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// A write a dry-run build would have made
+pub enum PlannedWrite {
+    /// A file written, with its size when known
+    Write(PathBuf, Option<usize>),
+    /// A file copied from the post to the output
+    Copy(PathBuf, PathBuf),
+}
+
+impl fmt::Display for PlannedWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Write(path, Some(size)) => {
+                write!(f, "Would write {} ({size} bytes)", path.display())
+            }
+            Self::Write(path, None) => write!(f, "Would write {}", path.display()),
+            Self::Copy(source, destination) => write!(
+                f,
+                "Would copy {} to {}",
+                source.display(),
+                destination.display()
+            ),
+        }
+    }
+}
 
 #[derive(Debug)]
 /// A blog post, represented on disk by a minimum of two files,
@@ -20,35 +214,104 @@ pub struct Post {
 }
 
 impl Post {
-    /// Creates a new post with the given title.
+    /// Creates a new post with the given title, in the default `<year>/<month>/<slug>`
+    /// directory.
     pub fn new<S: AsRef<str>>(title: S) -> Self {
         let title = title.as_ref().to_string();
         info!("Creating new post with title: {}", title);
 
-        let path = {
-            let today = Utc::now();
-            let mut path = PathBuf::new();
-            path.push(format!("{:04}", today.year()));
-            path.push(format!("{:02}", today.month()));
-            path.push(slugify!(title.as_str()));
-            path
-        };
+        let mut metadata = Metadata::default().with_title(title.as_str());
+        metadata.post.created_date = Some(Utc::now());
+
+        Self {
+            content: format!("# {title}"),
+            path: PathBuf::new(),
+            metadata,
+        }
+        .with_path_format(&PostsConfig::default().path_format, &SlugConfig::default())
+        .expect("The default path format is valid")
+    }
+
+    /// Moves the post to the directory given by the path format, expanded with its creation
+    /// date and title. The format is made of the `{year}`, `{month}`, `{day}` and `{slug}`
+    /// tokens and must contain `{slug}`, the title slugified as configured.
+    pub fn with_path_format(
+        mut self,
+        path_format: &str,
+        slug: &SlugConfig,
+    ) -> Result<Self, String> {
+        if !path_format.contains("{slug}") {
+            return Err(format!(
+                "The path format `{path_format}` must contain `{{slug}}`"
+            ));
+        }
+
+        let created = self.metadata.post.created_date.unwrap_or_else(Utc::now);
+        self.path = PathBuf::from(
+            path_format
+                .replace("{year}", &format!("{:04}", created.year()))
+                .replace("{month}", &format!("{:02}", created.month()))
+                .replace("{day}", &format!("{:02}", created.day()))
+                .replace("{slug}", &slug.slugify(&self.metadata.post.title)),
+        );
         info!(
             "Generated path: {}",
-            path.to_str().unwrap_or("Error; unable to display path")
+            self.path
+                .to_str()
+                .unwrap_or("Error; unable to display path")
         );
+        Ok(self)
+    }
 
-        Self {
-            content: format!("# {title}"),
-            path,
-            metadata: Metadata::default().with_title(title),
+    /// Creates a new post from the answers read from `input` to the questions asked on
+    /// `output`: its title, author, description, tags and keywords. The given title and
+    /// the configuration provide the default answers.
+    pub fn prompt<R: BufRead, W: Write>(
+        mut input: R,
+        mut output: W,
+        title: Option<&str>,
+        config: &Config,
+    ) -> Result<Self, String> {
+        let title = prompt(&mut input, &mut output, "Title", title.unwrap_or_default())?;
+        if title.is_empty() {
+            return Err("The post needs a title".to_string());
         }
+        let mut post =
+            Self::new(&title).with_path_format(&config.posts.path_format, &config.slug)?;
+
+        let list = |answer: String| {
+            answer
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect::<Vec<_>>()
+        };
+        let authors = prompt(
+            &mut input,
+            &mut output,
+            "Authors (comma separated)",
+            &config.site.author,
+        )?;
+        post.metadata.post.authors = list(authors);
+        post.metadata.opengraph.description = prompt(&mut input, &mut output, "Description", "")?;
+        let tags = prompt(&mut input, &mut output, "Tags (comma separated)", "")?;
+        for tag in list(tags) {
+            post.metadata.post.add_tag(tag, config.posts.max_tags)?;
+        }
+        let keywords = prompt(&mut input, &mut output, "Keywords (comma separated)", "")?;
+        for keyword in list(keywords) {
+            post.metadata
+                .opengraph
+                .add_keyword(keyword, config.posts.max_keywords)?;
+        }
+
+        Ok(post)
     }
 
     /// Tries to load a post from the given path.
-    pub fn load(path: String) -> Result<Self, String> {
-        info!("Loading post from path: {}", path);
-        let path = PathBuf::from(path);
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = normalize_path(path);
+        info!("Loading post from path: {}", path.display());
         if !path.exists() {
             error!(
                 "Path does not exist: {}",
@@ -65,8 +328,22 @@ impl Post {
         let metadata_toml = fs::read_to_string(&metadata_path)
             .map_err(|e| format!("Failed to read metadata file: {e}"))?;
 
-        let metadata: Metadata = toml::from_str(&metadata_toml)
-            .map_err(|e| format!("Failed to parse metadata file: {e}"))?;
+        let metadata: Metadata = toml::from_str(&metadata_toml).map_err(|e| {
+            let location = e
+                .span()
+                .map(|span| {
+                    let before = &metadata_toml[..span.start];
+                    let line = before.matches('\n').count() + 1;
+                    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+                    format!(" at line {line}, column {column}")
+                })
+                .unwrap_or_default();
+            format!(
+                "Failed to parse {}{location}: {} (run `blog repair` to regenerate it)",
+                metadata_path.display(),
+                e.message()
+            )
+        })?;
 
         Ok(Self {
             content,
@@ -75,13 +352,209 @@ impl Post {
         })
     }
 
+    /// Regenerates a minimal `metadata.toml` for the post at the given path, with a title
+    /// derived from the name of its directory. The previous file, if any, is kept as
+    /// `metadata.toml.bak`.
+    pub fn repair<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = normalize_path(path);
+        let content_path = path.join("content.md");
+        if !content_path.exists() {
+            return Err(format!(
+                "{} is not a blog post (no content.md found)",
+                path.display()
+            ));
+        }
+
+        let metadata_path = path.join("metadata.toml");
+        if metadata_path.exists() {
+            let backup = path.join("metadata.toml.bak");
+            info!(
+                "Backing up {} to {}",
+                metadata_path.display(),
+                backup.display()
+            );
+            fs::rename(&metadata_path, &backup)
+                .map_err(|e| format!("Failed to back up metadata file: {e}"))?;
+        }
+
+        let title: String = path
+            .file_name()
+            .map(|name| name.to_string_lossy().replace(['-', '_'], " "))
+            .map(|name| {
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+
+        let mut post = Self {
+            content: fs::read_to_string(&content_path)
+                .map_err(|e| format!("Failed to read content file: {e}"))?,
+            path,
+            metadata: Metadata::default().with_title(title),
+        };
+        post.save()?;
+        Ok(post)
+    }
+
+    /// Walks the given directory and loads all the posts it contains. Posts that fail
+    /// to load are skipped with a warning.
+    pub fn load_all(root: &Path) -> Result<Vec<Self>, String> {
+        let mut posts = vec![];
+        for path in find_posts(root)? {
+            match Self::load(&path) {
+                Ok(post) => posts.push(post),
+                Err(e) => warn!("Skipping post {}: {e}", path.display()),
+            }
+        }
+        Ok(posts)
+    }
+
+    /// Applies the update to each of the posts at the given paths, saving the posts it
+    /// changed. The update returns the errors it ran into; the post is still saved if it
+    /// changed anyway. Returns for each post whether it changed, or its errors. A failing
+    /// post does not stop the others, unless failing fast: the posts after it are then left
+    /// out of the results.
+    pub fn update_all<F>(
+        paths: &[PathBuf],
+        fail_fast: bool,
+        mut update: F,
+    ) -> Vec<(PathBuf, Result<bool, String>)>
+    where
+        F: FnMut(&mut Self) -> Vec<String>,
+    {
+        let mut results = vec![];
+        for path in paths {
+            let result = Self::load(path).and_then(|mut post| {
+                let before = toml::to_string(&post.metadata).unwrap_or_default();
+                let errors = update(&mut post);
+                let changed = toml::to_string(&post.metadata).unwrap_or_default() != before;
+                if changed {
+                    post.save()?;
+                }
+
+                if errors.is_empty() {
+                    Ok(changed)
+                } else {
+                    Err(errors.join(", "))
+                }
+            });
+            let failed = result.is_err();
+            results.push((path.clone(), result));
+            if fail_fast && failed {
+                break;
+            }
+        }
+        results
+    }
+
+    /// Walks the given directory and loads all the published posts it contains, newest
+    /// first. Posts that fail to load are skipped with a warning.
+    pub fn load_published(root: &Path) -> Result<Vec<Self>, String> {
+        let mut posts: Vec<Self> = Self::load_all(root)?
+            .into_iter()
+            .filter(|post| !post.metadata.post.is_draft())
+            .collect();
+
+        Self::sort_newest_first(&mut posts);
+        Ok(posts)
+    }
+
+    /// Sorts the posts for listing: published posts first, newest first, then the drafts
+    /// by creation date.
+    pub fn sort_newest_first(posts: &mut [Self]) {
+        posts.sort_by(|a, b| {
+            let (a, b) = (&a.metadata.post, &b.metadata.post);
+            a.is_draft()
+                .cmp(&b.is_draft())
+                .then_with(|| b.published_date.cmp(&a.published_date))
+                .then_with(|| b.created_date.cmp(&a.created_date))
+        });
+    }
+
+    /// Returns the public URL of the post, built from the site's base URL and the path
+    /// of the post relative to the root of the blog.
+    pub fn url(&self, config: &Config, root: &Path) -> String {
+        self.url_with_base(&config.site.base_url, root)
+    }
+
+    /// Returns the URL of the post under the given base URL, built from the path of the
+    /// post relative to the root of the blog.
+    pub fn url_with_base(&self, base_url: &str, root: &Path) -> String {
+        let relative = relative_to(&self.path, root).unwrap_or(&self.path);
+        join_url(base_url, relative)
+    }
+
+    /// Returns the URL the page of the post is built for, when a base URL is set.
+    pub fn page_url(&self, options: &BuildOptions) -> Option<String> {
+        if options.base_url.is_empty() {
+            return None;
+        }
+        let relative = self.relative_path(options.root.as_deref());
+        Some(join_url(&options.base_url, &relative))
+    }
+
     /// Builds the post, creating the output directory and writing the post's content to an index.html file.
-    /// It will also update the post's metadata file with the current date and time.
-    pub fn build(&mut self) -> Result<(), String> {
-        self.metadata.post.update = Some(Utc::now());
-        self.save()?;
+    /// Once the output is written, it will also update the post's metadata file with the current date and time.
+    ///
+    /// In dry-run mode, the post is rendered but nothing is written to the disk and the
+    /// metadata is left untouched. The returned output then lists the files that would be
+    /// written, and the planned writes.
+    pub fn build(&mut self, options: &BuildOptions) -> Result<BuildOutput, String> {
+        let warnings = self.warnings(options)?;
+        if options.strict && !warnings.is_empty() {
+            return Err(format!(
+                "The post raised warnings in strict mode: {}",
+                warnings.join("; ")
+            ));
+        }
+        for warning in &warnings {
+            warn!("{}: {warning}", self.path.display());
+        }
+
+        let output_path = self.output_path(options);
+        let output_file = output_path.join(Path::new("index.html"));
+        let images_path = self.path.join(Path::new("images"));
+        let output_images_path = output_path.join(Path::new("images"));
+        let source_images = if options.no_images {
+            vec![]
+        } else {
+            list_files(&images_path)?
+        };
+        let copied_images = source_images
+            .iter()
+            .map(|image| {
+                let relative = image.strip_prefix(&images_path).unwrap_or(image);
+                output_images_path.join(relative)
+            })
+            .collect::<Vec<_>>();
+
+        if options.dry_run {
+            let html_content = self.render_page(options)?;
+            let mut planned = vec![PlannedWrite::Write(
+                output_file.clone(),
+                Some(html_content.len()),
+            )];
+            let mut assets = vec![];
+            if options.css_mode == CssMode::Link {
+                let stylesheet = output_path.join(render::STYLESHEET);
+                planned.push(PlannedWrite::Write(stylesheet.clone(), None));
+                assets.push(stylesheet);
+            }
+            for (image, copy) in source_images.iter().zip(copied_images) {
+                planned.push(PlannedWrite::Copy(image.clone(), copy.clone()));
+                assets.push(copy);
+            }
+            return Ok(BuildOutput {
+                directory: output_path,
+                index: output_file,
+                assets,
+                planned,
+            });
+        }
 
-        let output_path: PathBuf = self.path.join(Path::new("dist/"));
         info!(
             "Building post at path: {}",
             output_path
@@ -89,32 +562,405 @@ impl Post {
                 .unwrap_or("Error; unable to display path")
         );
 
+        if let Some(hook) = &options.hooks.pre_build {
+            hooks::run_hook(
+                "pre-build",
+                hook,
+                &self.path,
+                &output_path,
+                options.ignore_hook_errors,
+            )?;
+        }
         create_path(&output_path)?;
 
-        let html_content = markdown::to_html_with_options(&self.content, &markdown::Options::gfm())
-            .map_err(|e| e.to_string())?;
-
-        let output_file = output_path.join(Path::new("index.html"));
+        let html_content = self.render_page(options)?;
         fs::write(&output_file, html_content)
             .map_err(|e| format!("Failed to write output file: {e}"))?;
 
         // Copy images folder
-        let images_path = self.path.join(Path::new("images"));
-        let output_images_path = output_path.join(Path::new("images"));
-        copy_dir_all(&images_path, &output_images_path)
-            .map_err(|e| format!("Failed to copy images folder: {e}"))?;
+        if !options.no_images {
+            copy_dir_all(&images_path, &output_images_path)
+                .map_err(|e| format!("Failed to copy images folder: {e}"))?;
+        }
+        let mut assets = copied_images;
 
-        Ok(())
+        if let (true, false, Some(header)) = (
+            options.responsive_header,
+            options.no_images,
+            Metadata::header_exists(&self.path),
+        ) {
+            assets.extend(imaging::generate_variants(
+                &header,
+                &output_images_path.join("header"),
+            )?);
+        }
+
+        if options.css_mode == CssMode::Link {
+            let path = output_path.join(render::STYLESHEET);
+            fs::write(&path, stylesheet(options)?)
+                .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+            assets.push(path);
+        }
+
+        if let Some(favicon) = &options.favicon {
+            let path = output_path.join(favicon_name(favicon));
+            fs::copy(favicon, &path)
+                .map_err(|e| format!("Failed to copy favicon {}: {e}", favicon.display()))?;
+            assets.push(path);
+            if options.resize_favicon {
+                assets.extend(imaging::generate_favicons(favicon, &output_path)?);
+            }
+        }
+
+        if let Some(format) = options.compress {
+            assets.extend(compress::compress_dir(&output_path, format)?);
+        }
+
+        let snapshot = output_path.join(SOURCE_SNAPSHOT);
+        fs::write(&snapshot, &self.content)
+            .map_err(|e| format!("Failed to write {}: {e}", snapshot.display()))?;
+
+        // Only record the update once the output has successfully been produced
+        self.metadata.post.update = Some(Utc::now());
+        self.save()?;
+
+        if let Some(hook) = &options.hooks.post_build {
+            hooks::run_hook(
+                "post-build",
+                hook,
+                &self.path,
+                &output_path,
+                options.ignore_hook_errors,
+            )?;
+        }
+
+        Ok(BuildOutput {
+            directory: output_path,
+            index: output_file,
+            assets,
+            planned: vec![],
+        })
+    }
+
+    /// Returns the unified diff of the content of the post since its last build with the
+    /// given options, empty if it did not change, or `None` if the post was never built.
+    pub fn diff_since_build(&self, options: &BuildOptions) -> Result<Option<String>, String> {
+        let snapshot = self.output_path(options).join(SOURCE_SNAPSHOT);
+        if !snapshot.is_file() {
+            return Ok(None);
+        }
+
+        let built = fs::read_to_string(&snapshot)
+            .map_err(|e| format!("Failed to read {}: {e}", snapshot.display()))?;
+        if built == self.content {
+            return Ok(Some(String::new()));
+        }
+        Ok(Some(
+            similar::TextDiff::from_lines(&built, &self.content)
+                .unified_diff()
+                .header("last build", "content.md")
+                .to_string(),
+        ))
+    }
+
+    /// Returns the warnings raised by the post before it is built: a missing or too long
+    /// description, a missing header image, missing images and broken links to other posts
+    /// of the blog. The images never referenced by the content are only logged.
+    pub fn warnings(&self, options: &BuildOptions) -> Result<Vec<String>, String> {
+        let mut warnings: Vec<String> = lint::check_description(&self.metadata)
+            .into_iter()
+            .map(|finding| finding.message)
+            .collect();
+        if Metadata::header_exists(&self.path).is_none() {
+            warnings.push("The post has no header image".to_string());
+        }
+
+        // The content is parsed the way the build renders it, e.g. without the math spans
+        let tree = markdown::to_mdast(&self.content, &render::markdown_options(options).parse)
+            .map_err(|e| format!("Failed to parse content: {e}"))?;
+        warnings.extend(
+            lint::check_missing_images(&tree, &self.path)
+                .into_iter()
+                .map(|finding| finding.message),
+        );
+        for finding in lint::check_orphan_images(&tree, &self.path)? {
+            info!("{}", finding.message);
+        }
+
+        let root = match &options.root {
+            Some(root) => root.clone(),
+            None => std::env::current_dir().map_err(|e| e.to_string())?,
+        };
+        for report in links::check_post_references(self, &root, &options.base_url)? {
+            if let links::LinkStatus::Broken(reason) = report.status {
+                warnings.push(format!("Broken link {} ({reason})", report.link));
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// Returns the directory the post is built into with the given options. Drafts are kept
+    /// apart from the published posts, in the drafts directory.
+    pub fn output_path(&self, options: &BuildOptions) -> PathBuf {
+        let output = match (&options.drafts_dir, &options.output) {
+            (Some(drafts), _) if self.metadata.post.is_draft() => Some(drafts.clone()),
+            (None, Some(output)) if self.metadata.post.is_draft() => Some(output.join(DRAFTS_DIR)),
+            (_, output) => output.clone(),
+        };
+        match &output {
+            Some(output) => output.join(self.relative_path(options.root.as_deref())),
+            None => self.path.join(Path::new("dist/")),
+        }
+    }
+
+    /// Returns the path of the post relative to the root of the blog, which defaults to
+    /// the current directory.
+    pub fn relative_path(&self, root: Option<&Path>) -> PathBuf {
+        let root = root
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        match relative_to(&self.path, &root) {
+            Some(relative) => relative.to_path_buf(),
+            None if self.path.is_relative() => self.path.clone(),
+            // A post outside of the blog is considered to be at its root
+            None => PathBuf::from(self.path.file_name().unwrap_or_default()),
+        }
+    }
+
+    /// Renders the complete HTML page of the post, using the page template. When a base
+    /// URL is set, the links and images of the page are made absolute.
+    pub fn render_page(&self, options: &BuildOptions) -> Result<String, String> {
+        let page_url = self.page_url(options);
+        let mut html = self.render_html(options)?;
+        if let Some(page_url) = &page_url {
+            html = render::absolute_links(&html, &options.base_url, page_url);
+        }
+        let html = render::external_links(&html, &options.base_url);
+        let (mut content, headings) = render::heading_anchors(&html);
+        if options.heading_links {
+            content = render::heading_links(&content);
+        }
+        if options.microformats {
+            if !options.title_from_meta {
+                content = render::entry_name(&content);
+            }
+            content = format!("<div class=\"e-content\">\n{content}</div>\n");
+        }
+        if options.toc {
+            content = format!("{}{content}", render::table_of_contents(&headings));
+        }
+        if options.lazy_images {
+            content = render::lazy_images(&content);
+        }
+
+        let header = match Metadata::header_exists(&self.path) {
+            Some(header_path) => {
+                let url = "images/header/header.jpg";
+                let responsive = if options.responsive_header {
+                    let widths = imaging::variant_widths(&header_path)?;
+                    let width = imaging::image_width(&header_path)?;
+                    format!(
+                        " srcset=\"{}\" sizes=\"100vw\"",
+                        imaging::srcset(url, &widths, width)
+                    )
+                } else {
+                    String::new()
+                };
+                format!(
+                    "<img class=\"{}\" src=\"{url}\"{responsive} alt=\"{}\">",
+                    render::HEADER_CLASS,
+                    escape_xml(&self.metadata.post.title)
+                )
+            }
+            None => String::new(),
+        };
+        let header = match &page_url {
+            Some(page_url) => render::absolute_links(&header, &options.base_url, page_url),
+            None => header,
+        };
+
+        let mut heading = if options.title_from_meta {
+            format!("<h1>{}</h1>", escape_xml(&self.metadata.post.title))
+        } else {
+            String::new()
+        };
+        let entry = if options.microformats {
+            heading = render::entry_name(&heading)
+                + &render::entry_byline(&self.metadata, &options.display);
+            " class=\"h-entry\"".to_string()
+        } else {
+            String::new()
+        };
+
+        let template = match &options.template_dir {
+            Some(dir) => Template::from_dir(dir)?,
+            None => Template::page(),
+        };
+        Ok(template.render(&[
+            ("title", escape_xml(&self.metadata.post.title)),
+            (
+                "style",
+                render::style_tag(&stylesheet(options)?, options.css_mode),
+            ),
+            ("head", self.render_head(options)),
+            ("entry", entry),
+            ("heading", heading),
+            ("header", header),
+            ("content", content),
+            ("related", related::related_html(&options.related)),
+            (
+                "nav",
+                render::post_nav(options.previous.as_ref(), options.next.as_ref()),
+            ),
+            (
+                "footer",
+                self.metadata
+                    .post
+                    .license
+                    .as_deref()
+                    .map(render::license_footer)
+                    .unwrap_or_default(),
+            ),
+        ]))
+    }
+
+    /// Renders the meta tags of the page's head: the description and, when a base URL is
+    /// set, the canonical URL of the page, the OpenGraph tags, the Twitter card tags when
+    /// requested or when the post has a header image, and the structured data.
+    fn render_head(&self, options: &BuildOptions) -> String {
+        let title = &self.metadata.post.title;
+        let description = self.metadata.social_description();
+        let page_url = self.page_url(options);
+        let image = self.metadata.social_image(&self.path);
+        let image = match &page_url {
+            Some(page_url) if !image.is_empty() => {
+                render::absolute_url(&image, &options.base_url, page_url)
+            }
+            _ => image,
+        };
+        let image = image.as_str();
+
+        let mut head = String::new();
+        head.push_str(&render::meta_tag("name", "description", description));
+        head.push_str(&render::meta_tag(
+            "name",
+            "author",
+            &self.metadata.post.authors.join(", "),
+        ));
+        if let Some(page_url) = &page_url {
+            head.push_str(&render::canonical_link(page_url));
+        }
+        if let Some(favicon) = &options.favicon {
+            let sizes: &[u32] = if options.resize_favicon {
+                &imaging::FAVICON_SIZES
+            } else {
+                &[]
+            };
+            head.push_str(&render::favicon_links(&favicon_name(favicon), sizes));
+        }
+        head.push_str(&render::meta_tag("property", "og:type", "article"));
+        head.push_str(&render::meta_tag("property", "og:title", title));
+        head.push_str(&render::meta_tag("property", "og:description", description));
+        head.push_str(&render::meta_tag("property", "og:image", image));
+
+        if options.twitter || Metadata::header_exists(&self.path).is_some() {
+            head.push_str(&render::meta_tag(
+                "name",
+                "twitter:card",
+                "summary_large_image",
+            ));
+            head.push_str(&render::meta_tag("name", "twitter:title", title));
+            head.push_str(&render::meta_tag(
+                "name",
+                "twitter:description",
+                description,
+            ));
+            head.push_str(&render::meta_tag("name", "twitter:image", image));
+        }
+
+        head.push_str(&render::json_ld(&self.metadata, image));
+        if options.math == Some(MathMode::Client) {
+            head.push_str(&render::katex_head());
+        }
+        head
     }
 
-    #[allow(clippy::unused_self)]
-    /// Publishes the post, uploading it to the blog's server.
-    pub fn publish(&mut self) -> Result<(), String> {
-        Err("Not implemented".to_string())
+    /// Renders the markdown content of the post to HTML.
+    pub fn render_html(&self, options: &BuildOptions) -> Result<String, String> {
+        let content = if options.title_from_meta {
+            render::strip_title(&self.content, &self.metadata.post.title)
+        } else {
+            &self.content
+        };
+        let content = if options.emoji {
+            render::expand_emoji(content)?
+        } else {
+            content.to_string()
+        };
+        let html = markdown::to_html_with_options(&content, &render::markdown_options(options))
+            .map_err(|e| e.to_string())?;
+
+        match options.math {
+            Some(MathMode::Server) => render::math_to_mathml(&html),
+            _ => Ok(html),
+        }
     }
 
-    /// Saves the post to disk.
-    pub fn save(&self) -> Result<(), String> {
+    /// Publishes the post, uploading its built output with the given publisher. The post
+    /// must have been built beforehand, with the same output options. The publication date
+    /// is set on the first publication.
+    pub fn publish(
+        &mut self,
+        publisher: &dyn Publisher,
+        options: &BuildOptions,
+    ) -> Result<(), String> {
+        let dist = self.output_path(options);
+        if !dist.join("index.html").is_file() {
+            return Err(format!(
+                "{} has not been built yet, run `blog build` first",
+                self.path_display()
+            ));
+        }
+
+        publisher.publish(self, &dist)?;
+
+        if self.metadata.post.published_date.is_none() {
+            self.metadata.post.published_date = Some(Utc::now());
+        }
+        self.metadata.post.unpublished = false;
+        self.save()
+    }
+
+    /// Reverts the post to a draft, removing its published output with the publisher, if
+    /// any. The publication date is cleared, unless it is kept, in which case the post is
+    /// marked as unpublished.
+    pub fn unpublish(
+        &mut self,
+        publisher: Option<&dyn Publisher>,
+        keep_date: bool,
+    ) -> Result<(), String> {
+        if let Some(publisher) = publisher {
+            publisher.unpublish(self)?;
+        }
+
+        if keep_date {
+            self.metadata.post.unpublished = self.metadata.post.published_date.is_some();
+        } else {
+            self.metadata.post.published_date = None;
+            self.metadata.post.unpublished = false;
+        }
+        self.save()
+    }
+
+    /// Returns the SHA-256 hash of the content of the post, in hexadecimal.
+    pub fn content_hash(&self) -> String {
+        hex(&Sha256::digest(&self.content))
+    }
+
+    /// Saves the post to disk, recording the hash of its content in its metadata.
+    pub fn save(&mut self) -> Result<(), String> {
+        self.metadata.post.content_hash = Some(self.content_hash());
         create_path(&self.path)?;
         let images_path = self.path.join("images");
         create_path(&images_path)?;
@@ -123,14 +969,167 @@ impl Post {
         fs::write(&content_path, &self.content)
             .map_err(|e| format!("Failed to write content file: {e}"))?;
 
-        let metadata_path = format!("{}/metadata.toml", self.path_display());
-        let metadata_toml = toml::to_string(&self.metadata)
-            .map_err(|e| format!("Failed to serialize metadata: {e}"))?;
+        self.metadata.save(&self.path)
+    }
 
-        fs::write(&metadata_path, metadata_toml)
-            .map_err(|e| format!("Failed to write metadata file: {e}"))?;
+    /// Saves the new post to disk. Refuses to overwrite the `content.md` or `metadata.toml`
+    /// of an existing post at its path, unless forced.
+    pub fn create(&mut self, force: bool) -> Result<(), String> {
+        let existing = ["content.md", "metadata.toml"]
+            .iter()
+            .any(|file| self.path.join(file).exists());
+        if existing && !force {
+            return Err(format!(
+                "A post already exists at {}, use --force to overwrite it",
+                self.path.display()
+            ));
+        }
+        self.save()
+    }
 
-        Ok(())
+    /// Removes the post at the given path from the disk, along with everything its
+    /// directory contains. Refuses to delete a directory that is not a post.
+    pub fn delete(path: &Path) -> Result<(), String> {
+        if !is_post_dir(path) {
+            return Err(format!(
+                "{} is not a blog post (no metadata.toml found), refusing to delete it",
+                path.display()
+            ));
+        }
+
+        info!("Deleting post at path: {}", path.display());
+        fs::remove_dir_all(path).map_err(|e| format!("Failed to delete post: {e}"))
+    }
+
+    /// Copies the given image into the post's `images/` directory, optionally slugifying
+    /// its name. Files that are not images, and images of the same name as one of the post,
+    /// are rejected unless forced: a forced image overwrites the one of the same name.
+    /// Returns the path of the copied image.
+    pub fn add_image(&self, file: &Path, slugify: bool, force: bool) -> Result<PathBuf, String> {
+        if !file.is_file() {
+            return Err(format!("{} is not a file", file.display()));
+        }
+        if !force && !is_image(file) {
+            return Err(format!(
+                "{} does not look like an image, use --force to add it anyway",
+                file.display()
+            ));
+        }
+
+        let file_name = file
+            .file_name()
+            .ok_or(format!("Unable to get the name of {}", file.display()))?
+            .to_string_lossy()
+            .to_string();
+        let file_name = match (slugify, file_name.rsplit_once('.')) {
+            (true, Some((stem, extension))) => {
+                format!("{}.{}", slugify!(stem), extension.to_lowercase())
+            }
+            (true, None) => slugify!(file_name.as_str()),
+            (false, _) => file_name,
+        };
+
+        let images_path = self.path.join("images");
+        create_path(&images_path)?;
+
+        let destination = images_path.join(file_name);
+        if !force && destination.exists() {
+            return Err(format!(
+                "{} already exists, use --force to overwrite it",
+                destination.display()
+            ));
+        }
+        info!("Copying {} to {}", file.display(), destination.display());
+        fs::copy(file, &destination).map_err(|e| format!("Failed to copy image: {e}"))?;
+        Ok(destination)
+    }
+
+    /// Returns the paths of the images of the post, relative to its `images/` directory.
+    pub fn list_images(&self) -> Result<Vec<PathBuf>, String> {
+        let images_path = self.path.join("images");
+        Ok(list_files(&images_path)?
+            .into_iter()
+            .filter_map(|image| image.strip_prefix(&images_path).ok().map(Path::to_path_buf))
+            .collect())
+    }
+
+    /// Adds the text at the end of the content, as a new block: a blank line separates it
+    /// from the content, so that it neither continues the last paragraph nor ends up in a
+    /// code block or a heading. The trailing newline of the content is kept.
+    pub fn append_content(&mut self, text: &str) {
+        let newline = self.content.is_empty() || self.content.ends_with('\n');
+        let content = self.content.trim_end();
+        let text = text.trim_matches('\n');
+        self.content = if content.is_empty() {
+            text.to_string()
+        } else {
+            format!("{content}\n\n{text}")
+        };
+        if newline {
+            self.content.push('\n');
+        }
+    }
+
+    /// Adds the text at the start of the content, as a new block separated from it by a
+    /// blank line. The trailing newline of the content is kept.
+    pub fn prepend_content(&mut self, text: &str) {
+        let content = self.content.trim_start_matches('\n');
+        let text = text.trim_matches('\n');
+        self.content = if content.trim().is_empty() {
+            format!("{text}\n")
+        } else {
+            format!("{text}\n\n{content}")
+        };
+    }
+
+    /// Returns the number of words in the post's content.
+    pub fn word_count(&self) -> usize {
+        self.content
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count()
+    }
+
+    /// Returns the estimated reading time of the post, in minutes, at the given reading
+    /// speed unless the post overrides it. A post always takes at least a minute to read.
+    pub fn reading_time(&self, words_per_minute: usize) -> usize {
+        let words_per_minute = self.metadata.post.reading_wpm.unwrap_or(words_per_minute);
+        self.word_count().div_ceil(words_per_minute.max(1)).max(1)
+    }
+
+    /// Returns the first paragraph of the post's content, stripped of its markdown
+    /// syntax and truncated to the given number of characters.
+    pub fn excerpt(&self, max_chars: usize) -> String {
+        let mut in_code_block = false;
+        let mut paragraph: Vec<&str> = vec![];
+        for line in self.content.lines().map(str::trim) {
+            if line.starts_with("```") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+            if in_code_block || line.starts_with('#') || line.starts_with("![") {
+                continue;
+            }
+            if line.is_empty() {
+                if paragraph.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            paragraph.push(line);
+        }
+
+        let text = strip_inline_markdown(&paragraph.join(" "));
+        if text.chars().count() <= max_chars {
+            return text;
+        }
+
+        let truncated: String = text.chars().take(max_chars).collect();
+        let truncated = match truncated.rfind(' ') {
+            Some(index) => &truncated[..index],
+            None => truncated.as_str(),
+        };
+        format!("{}…", truncated.trim_end())
     }
 
     /// Returns a string representation of the post's path. Or an error message if the path is invalid.
@@ -142,10 +1141,63 @@ impl Post {
     }
 }
 
+/// Removes the inline markdown syntax (emphasis, code spans, links) from the given text.
+fn strip_inline_markdown(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '_' | '`' => {}
+            ']' if chars.peek() == Some(&'(') => {
+                // Skip the link target
+                for c in chars.by_ref() {
+                    if c == ')' {
+                        break;
+                    }
+                }
+            }
+            '[' => {}
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Returns the name of the copy of the favicon next to the page, `favicon.<extension>`.
+fn favicon_name(favicon: &Path) -> String {
+    match favicon.extension() {
+        Some(extension) => format!("favicon.{}", extension.to_string_lossy()),
+        None => "favicon".to_string(),
+    }
+}
+
+/// Returns the stylesheet of the build options, or the default theme.
+fn stylesheet(options: &BuildOptions) -> Result<String, String> {
+    match &options.css {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read stylesheet {}: {e}", path.display())),
+        None => Ok(render::DEFAULT_CSS.to_string()),
+    }
+}
+
+/// Appends the components of the relative path to the base URL, with a trailing slash.
+fn join_url(base_url: &str, relative: &Path) -> String {
+    let mut url = base_url.trim_end_matches('/').to_string();
+    for component in relative.components() {
+        url.push('/');
+        url.push_str(&component.as_os_str().to_string_lossy());
+    }
+    url.push('/');
+    url
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Metadata {
     pub post: PostInfo,
     pub opengraph: OpenGraph,
+    /// The credits of the chosen header image, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<HeaderCredit>,
 }
 
 impl Metadata {
@@ -156,6 +1208,58 @@ impl Metadata {
 }
 
 impl Metadata {
+    /// Returns the description shared on social networks.
+    pub fn social_description(&self) -> &str {
+        &self.opengraph.description
+    }
+
+    /// Returns the inconsistencies of the metadata: a missing title, an update before the
+    /// publication, a zero reading speed, duplicated tags or keywords, or more of them than
+    /// the configuration allows.
+    pub fn validate(&self, config: &PostsConfig) -> Vec<String> {
+        let mut problems = vec![];
+        let post = &self.post;
+        if post.title.trim().is_empty() {
+            problems.push("The post has no title".to_string());
+        }
+        if let (Some(published), Some(update)) = (post.published_date, post.update) {
+            if update < published {
+                problems.push("The post was updated before it was published".to_string());
+            }
+        }
+        if post.reading_wpm == Some(0) {
+            problems.push("The reading speed of the post is zero".to_string());
+        }
+
+        for (kind, values, max) in [
+            ("tag", &post.tags, config.max_tags),
+            ("keyword", &self.opengraph.keywords, config.max_keywords),
+        ] {
+            let mut seen = std::collections::HashSet::new();
+            for value in values {
+                if !seen.insert(value.to_lowercase()) {
+                    problems.push(format!("The {kind} `{value}` is listed more than once"));
+                }
+            }
+            if max > 0 && values.len() > max {
+                problems.push(format!(
+                    "The post has {} {kind}s, more than the maximum of {max}",
+                    values.len()
+                ));
+            }
+        }
+        problems
+    }
+
+    /// Returns the image shared on social networks: the OpenGraph image when set, or else
+    /// the header image of the post at the given path, relative to the post.
+    pub fn social_image(&self, post_path: &Path) -> String {
+        if self.opengraph.opengraphimage.is_empty() && Self::header_exists(post_path).is_some() {
+            return "images/header/header.jpg".to_string();
+        }
+        self.opengraph.opengraphimage.clone()
+    }
+
     pub fn header_path(blog_path: &Path) -> PathBuf {
         let header_sub_path: PathBuf = [r"images", "header"].iter().collect();
         blog_path.join(header_sub_path)
@@ -171,50 +1275,97 @@ impl Metadata {
         }
     }
 
-    /// Fetches new candidate header images from pexel
-    pub fn fetch_new_header_images(&self, path: &Path, amount: usize) -> Result<(), String> {
+    /// Fetches new candidate header images from the first of the providers finding some,
+    /// and returns them
+    pub fn fetch_new_header_images(
+        &self,
+        path: &Path,
+        providers: &[Box<dyn ImageProvider>],
+        search: &HeaderSearch,
+    ) -> Result<Vec<Candidate>, String> {
         if self.opengraph.keywords.is_empty() {
             return Err(
                 "Unable to fetch image for the blog post; The post has no keyword".to_string(),
             );
         }
 
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| e.to_string())?;
-
-        let _ = rt.block_on(get_new_candidates(
+        fetch_candidates(
+            providers,
             Self::header_path(path),
             &self.opengraph.keywords,
-            amount,
-        ))?;
+            search,
+        )
+    }
 
-        Ok(())
+    /// Returns the path of the image of the candidate header with the given index, whether
+    /// its name is zero-padded or not. The padded name wins when both exist, then the first
+    /// name in order.
+    pub fn candidate_image(path: &Path, index: usize) -> PathBuf {
+        let candidates = Self::header_path(path).join("candidates");
+        let padded = candidates.join(candidate_name(index));
+        if padded.is_file() {
+            return padded;
+        }
+
+        let mut images = fs::read_dir(&candidates)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|image| {
+                image
+                    .extension()
+                    .is_some_and(|extension| extension == "jpg")
+                    && image
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .and_then(candidate_index)
+                        == Some(index)
+            })
+            .collect::<Vec<_>>();
+        images.sort();
+        images.into_iter().next().unwrap_or(padded)
     }
 
-    pub fn list_header_candidates(path: &Path) -> Result<(), String> {
+    /// Returns the candidate header images of the post, ordered by their index.
+    pub fn list_header_candidates(path: &Path) -> Result<Vec<HeaderCandidate>, String> {
         let header_path = Self::header_path(path).join("candidates");
 
-        let mut index = 1;
-        for path in fs::read_dir(header_path).map_err(|e| e.to_string())? {
-            let path = path.map_err(|e| e.to_string())?;
-            if let Some(extension) = path.path().extension() {
-                if extension == "toml" {
-                    let content = fs::read_to_string(path.path()).map_err(|e| e.to_string())?;
-                    let picture = toml::from_str::<PexelPicture>(content.as_str())
-                        .map_err(|e| e.to_string())?;
-                    println!("{index} - {picture}");
-
-                    index += 1;
-                }
+        let mut candidates = vec![];
+        for entry in fs::read_dir(header_path).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.extension().is_none_or(|extension| extension != "toml") {
+                continue;
             }
+            let Some(index) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(candidate_index)
+            else {
+                continue;
+            };
+
+            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let picture = toml::from_str::<Picture>(content.as_str()).map_err(|e| e.to_string())?;
+            candidates.push(HeaderCandidate::new(index, &picture));
         }
+        candidates.sort_by_key(|candidate| candidate.index);
 
-        Ok(())
+        Ok(candidates)
     }
 
-    pub fn choose_header(path: &Path, index: usize) -> Result<(), String> {
+    /// Saves the metadata in the directory of the post at the given path.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let metadata_toml =
+            toml::to_string(self).map_err(|e| format!("Failed to serialize metadata: {e}"))?;
+
+        fs::write(path.join("metadata.toml"), metadata_toml)
+            .map_err(|e| format!("Failed to write metadata file: {e}"))
+    }
+
+    /// Selects the candidate header image with the given index as the header of the post at
+    /// the given path. The header becomes the image shared on social networks, and the
+    /// credits of the picture are saved along with the metadata.
+    pub fn choose_header(&mut self, path: &Path, index: usize) -> Result<(), String> {
         if Self::header_exists(path).is_some() {
             warn!("A header file has already been selected, it will be overwritten");
         }
@@ -224,9 +1375,8 @@ impl Metadata {
         let chosen_header_picture = header_path.join("header.jpg");
         let chosen_header_metadata = header_path.join("header.toml");
 
-        let candidate_path = header_path.join("candidates");
-        let candidate_header_picture = candidate_path.join(format!("header_{index}.jpg"));
-        let candidate_header_metadata = candidate_path.join(format!("header_{index}.toml"));
+        let candidate_header_picture = Self::candidate_image(path, index);
+        let candidate_header_metadata = candidate_header_picture.with_extension("toml");
 
         if !candidate_header_picture.exists() || !candidate_header_picture.is_file() {
             return Err(format!(
@@ -239,11 +1389,48 @@ impl Metadata {
             ));
         }
 
+        let content = fs::read_to_string(&candidate_header_metadata).map_err(|e| e.to_string())?;
+        let picture = toml::from_str::<Picture>(&content).map_err(|e| e.to_string())?;
+
         // Move header picture & metadata one folder above
         fs::copy(candidate_header_picture, chosen_header_picture).map_err(|e| e.to_string())?;
         fs::copy(candidate_header_metadata, chosen_header_metadata).map_err(|e| e.to_string())?;
 
-        Ok(())
+        self.opengraph.opengraphimage = "images/header/header.jpg".to_string();
+        self.header = Some(HeaderCredit::from(&picture));
+        self.save(path)
+    }
+}
+
+/// (De)serializes the authors of a post as a single string when there is at most one, so
+/// that the metadata of single-author posts keeps its original form, and as a list
+/// otherwise.
+mod authors {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Authors {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    pub fn serialize<S: Serializer>(authors: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+        match authors {
+            [] => "".serialize(serializer),
+            [author] => author.serialize(serializer),
+            authors => authors.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<String>, D::Error> {
+        Ok(match Authors::deserialize(deserializer)? {
+            Authors::One(author) if author.is_empty() => vec![],
+            Authors::One(author) => vec![author],
+            Authors::Many(authors) => authors,
+        })
     }
 }
 
@@ -251,18 +1438,157 @@ impl Metadata {
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct PostInfo {
     pub title: String,
-    pub author: String,
+    /// Written as a single string when the post has one author, as a list otherwise
+    #[serde(rename = "author", default, with = "authors")]
+    pub authors: Vec<String>,
+    /// When the post was created, set once by `new`. Older posts do not have it.
+    pub created_date: Option<DateTime<Utc>>,
     pub published_date: Option<DateTime<Utc>>,
+    /// Whether the post was pulled back after its publication, keeping its publication date
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unpublished: bool,
     pub update: Option<DateTime<Utc>>,
     pub tags: Vec<String>,
+    /// The license the post is published under, e.g. `CC-BY-4.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The reading speed used to estimate the reading time of the post, in words per
+    /// minute, instead of the one of the configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reading_wpm: Option<usize>,
+    /// The SHA-256 hash of `content.md` when the post was last saved, to detect the edits
+    /// made outside of the CLI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// When the draft is due to be published by `publish --due`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A position in the tags of a post
+pub enum TagPosition {
+    First,
+    Last,
+    /// A 0-based index
+    Index(usize),
+}
+
+impl std::str::FromStr for TagPosition {
+    type Err = String;
+
+    fn from_str(position: &str) -> Result<Self, Self::Err> {
+        match position {
+            "first" => Ok(Self::First),
+            "last" => Ok(Self::Last),
+            index => index.parse().map(Self::Index).map_err(|_| {
+                format!("Invalid position `{index}`, expected `first`, `last` or an index")
+            }),
+        }
+    }
 }
 
 impl PostInfo {
-    /// Adds a tag to the post.
-    pub fn add_tag(&mut self, tag: String) -> Result<(), String> {
+    /// Returns whether the post is a draft, i.e. has not been published yet or was
+    /// unpublished.
+    pub fn is_draft(&self) -> bool {
+        self.published_date.is_none() || self.unpublished
+    }
+
+    /// Returns whether the post is a draft scheduled for publication at or before the
+    /// given date.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.is_draft() && self.scheduled_date.is_some_and(|date| date <= now)
+    }
+
+    /// Moves a tag of the post to the given position.
+    pub fn move_tag(&mut self, tag: &str, position: TagPosition) -> Result<(), String> {
+        info!("Moving tag {tag} to {position:?}");
+        let index = self
+            .tags
+            .iter()
+            .position(|x| x == tag)
+            .ok_or(format!("Tag `{tag}` was not found in the post's tags"))?;
+
+        let target = match position {
+            TagPosition::First => 0,
+            TagPosition::Last => self.tags.len() - 1,
+            TagPosition::Index(target) if target < self.tags.len() => target,
+            TagPosition::Index(target) => {
+                return Err(format!(
+                    "Position {target} is out of range, the post has {} tags",
+                    self.tags.len()
+                ))
+            }
+        };
+
+        let tag = self.tags.remove(index);
+        self.tags.insert(target, tag);
+        Ok(())
+    }
+
+    /// Renames a tag of the post, keeping its position. If the post already has the new
+    /// tag, the old one is simply removed.
+    pub fn rename_tag(&mut self, from: &str, to: &str) -> Result<(), String> {
+        info!("Renaming tag {from} to {to}");
+        let index = self
+            .tags
+            .iter()
+            .position(|tag| tag == from)
+            .ok_or(format!("Tag `{from}` was not found in the post's tags"))?;
+
+        if self.tags.iter().any(|tag| tag == to) {
+            self.remove_tag(from)
+        } else {
+            self.tags[index] = to.to_string();
+            Ok(())
+        }
+    }
+
+    /// Merges a tag of the post into another one: the old tag is replaced by the new one,
+    /// or simply removed if the post already has the new tag. Returns whether the post
+    /// had the old tag.
+    pub fn merge_tag(&mut self, from: &str, to: &str) -> Result<bool, String> {
+        if !self.tags.iter().any(|tag| tag == from) {
+            return Ok(false);
+        }
+        self.rename_tag(from, to)?;
+        Ok(true)
+    }
+
+    /// Adds an author to the post.
+    pub fn add_author(&mut self, author: String) -> Result<(), String> {
+        info!("Adding author {author} to post");
+        if self.authors.contains(&author) {
+            Err(format!("`{author}` is already an author of this blog post"))
+        } else {
+            self.authors.push(author);
+            Ok(())
+        }
+    }
+
+    /// Removes an author from the post.
+    pub fn remove_author(&mut self, author: &str) -> Result<(), String> {
+        info!("Removing author {author} from post");
+        let index = self
+            .authors
+            .iter()
+            .position(|x| x == author)
+            .ok_or(format!("`{author}` is not an author of this blog post"))?;
+        self.authors.remove(index);
+        Ok(())
+    }
+
+    /// Adds a tag to the post, unless it already has `max_tags` tags. A maximum of zero
+    /// means unlimited.
+    pub fn add_tag(&mut self, tag: String, max_tags: usize) -> Result<(), String> {
         info!("Adding tag {tag} to post");
         if self.tags.contains(&tag) {
             Err(format!("Tag `{tag}` is already attached to this blog post",))
+        } else if max_tags > 0 && self.tags.len() >= max_tags {
+            Err(format!(
+                "Cannot add tag `{tag}`, the post already has the maximum of {max_tags} tags; remove one first"
+            ))
         } else {
             self.tags.push(tag);
             Ok(())
@@ -296,6 +1622,18 @@ impl PostInfo {
             println!("* {tag}");
         }
     }
+
+    /// Lists the authors of the post.
+    pub fn list_authors(&self) {
+        if self.authors.is_empty() {
+            println!("This post has no authors");
+            return;
+        }
+
+        for author in &self.authors {
+            println!("* {author}");
+        }
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
@@ -307,13 +1645,18 @@ pub struct OpenGraph {
 }
 
 impl OpenGraph {
-    /// Adds a tag to the post.
-    pub fn add_keyword(&mut self, keyword: String) -> Result<(), String> {
+    /// Adds a keyword to the post, unless it already has `max_keywords` keywords. A
+    /// maximum of zero means unlimited.
+    pub fn add_keyword(&mut self, keyword: String, max_keywords: usize) -> Result<(), String> {
         info!("Adding keyword {} to post", keyword);
         if self.keywords.contains(&keyword) {
             Err(format!(
                 "Keyword `{keyword}` is already attached to this blog post"
             ))
+        } else if max_keywords > 0 && self.keywords.len() >= max_keywords {
+            Err(format!(
+                "Cannot add keyword `{keyword}`, the post already has the maximum of {max_keywords} keywords; remove one first"
+            ))
         } else {
             self.keywords.push(keyword);
             Ok(())
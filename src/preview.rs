@@ -0,0 +1,63 @@
+/// This module opens the built page of a post in the browser, to preview the result of a
+/// build.
+use std::path::{Path, PathBuf};
+
+use log::info;
+
+use crate::post::{BuildOptions, BuildOutput, Post};
+
+/// Opens a built page
+pub trait Opener {
+    fn open(&self, page: &Path) -> Result<(), String>;
+}
+
+/// Opens the pages in the default browser of the system
+pub struct BrowserOpener;
+
+impl Opener for BrowserOpener {
+    fn open(&self, page: &Path) -> Result<(), String> {
+        info!("Opening {}", page.display());
+        open::that(page).map_err(|e| format!("Failed to open {}: {e}", page.display()))
+    }
+}
+
+/// Returns the `index.html` page of the post built with the given options, or an error if
+/// the post has not been built.
+pub fn built_page(post: &Post, options: &BuildOptions) -> Result<PathBuf, String> {
+    let page = post.output_path(options).join("index.html");
+    if page.is_file() {
+        Ok(page)
+    } else {
+        Err(format!(
+            "The post has not been built yet, {} does not exist; run `blog build` first or pass \
+             --build",
+            page.display()
+        ))
+    }
+}
+
+/// Opens the built page of the post with the opener. Returns the path of the page.
+pub fn open_post(
+    post: &Post,
+    options: &BuildOptions,
+    opener: &dyn Opener,
+) -> Result<PathBuf, String> {
+    let page = built_page(post, options)?;
+    opener.open(&page)?;
+    Ok(page)
+}
+
+/// Builds the post, then opens its page with the opener when asked to. Nothing is opened
+/// after a dry run, which writes no page.
+pub fn build_post(
+    post: &mut Post,
+    options: &BuildOptions,
+    open: bool,
+    opener: &dyn Opener,
+) -> Result<BuildOutput, String> {
+    let output = post.build(options)?;
+    if open && !options.dry_run {
+        opener.open(&output.index)?;
+    }
+    Ok(output)
+}
@@ -0,0 +1,591 @@
+/// This module handles the publication of built posts to a remote location.
+/// Each backend implements the `Publisher` trait; the backend to use is selected by the
+/// configuration or on the command line.
+///
+/// The S3 backend uploads the files to any S3-compatible storage (AWS, DigitalOcean
+/// Spaces, MinIO, ...) with path-style requests signed with AWS Signature Version 4.
+/// The git backend copies the files into a local repository, then commits and pushes
+/// them, e.g. for sites deployed with GitHub Pages.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use log::info;
+use sha2::{Digest, Sha256};
+
+use crate::config::{GitPublishConfig, PublishConfig};
+use crate::post::{BuildOptions, Post, SOURCE_SNAPSHOT};
+use crate::utils::{block_on, copy_dir_all, hex, list_files};
+
+/// A remote location posts can be published to
+pub trait Publisher {
+    /// Uploads the built output of the post, located in the given directory.
+    fn publish(&self, post: &Post, output: &Path) -> Result<(), String>;
+
+    /// Removes the published output of the post, whatever is left of its local copy.
+    fn unpublish(&self, post: &Post) -> Result<(), String>;
+}
+
+#[derive(Debug, Default)]
+/// The drafts scheduled for publication under a directory, sorted by scheduled date
+pub struct Schedule {
+    /// The drafts whose scheduled date has passed
+    pub due: Vec<Post>,
+    /// The drafts scheduled in the future
+    pub pending: Vec<Post>,
+}
+
+impl Schedule {
+    /// Gathers the scheduled drafts found under the given directory, due at the given date.
+    pub fn load(root: &Path, now: DateTime<Utc>) -> Result<Self, String> {
+        let mut posts: Vec<Post> = Post::load_all(root)?
+            .into_iter()
+            .filter(|post| {
+                post.metadata.post.is_draft() && post.metadata.post.scheduled_date.is_some()
+            })
+            .collect();
+        posts.sort_by_key(|post| post.metadata.post.scheduled_date);
+
+        let (due, pending) = posts
+            .into_iter()
+            .partition(|post| post.metadata.post.is_due(now));
+        Ok(Self { due, pending })
+    }
+}
+
+/// Publishes the due draft. It is built first, as published so that its page shows its
+/// publication date, then published from the output of the build. The post is left as it
+/// was if either step fails.
+pub fn publish_due(
+    post: &mut Post,
+    publisher: &dyn Publisher,
+    options: &BuildOptions,
+) -> Result<(), String> {
+    let (published_date, unpublished) = (
+        post.metadata.post.published_date,
+        post.metadata.post.unpublished,
+    );
+    post.metadata.post.published_date = Some(Utc::now());
+    post.metadata.post.unpublished = false;
+
+    let result = post
+        .build(options)
+        .and_then(|_| post.publish(publisher, options));
+    if result.is_err() {
+        post.metadata.post.published_date = published_date;
+        post.metadata.post.unpublished = unpublished;
+        post.save()?;
+    }
+    result
+}
+
+/// Returns the MIME type of the file, judging by its extension.
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Computes the HMAC-SHA256 of the message with the given key.
+fn hmac_sha256(key: &[u8], message: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Returns the canonical request of AWS Signature Version 4, along with the list of its
+/// signed headers. The headers are given with their names lowercased, sorted by name.
+pub fn canonical_request(
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(&str, &str)],
+    payload_hash: &str,
+) -> (String, String) {
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    (
+        format!("{method}\n{path}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}"),
+        signed_headers,
+    )
+}
+
+/// Derives the AWS Signature Version 4 signing key of the given day (`YYYYMMDD`), region
+/// and service from the secret key.
+pub fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let mut key = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date);
+    for part in [region, service, "aws4_request"] {
+        key = hmac_sha256(&key, part);
+    }
+    key
+}
+
+/// Returns the AWS Signature Version 4 of the canonical request made at the given time
+/// (`YYYYMMDDTHHMMSSZ`), along with its credential scope.
+pub fn signature(
+    secret_key: &str,
+    region: &str,
+    service: &str,
+    amz_date: &str,
+    canonical_request: &str,
+) -> (String, String) {
+    let date = &amz_date[..8];
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = signing_key(secret_key, date, region, service);
+    (hex(&hmac_sha256(&key, &string_to_sign)), scope)
+}
+
+/// Percent-encodes a value of a query string, its slashes included.
+fn query_encode(value: &str) -> String {
+    uri_encode(value).replace('/', "%2F")
+}
+
+/// Returns the unescaped text of the elements with the given tag of the XML document.
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{tag}>"), format!("</{tag}>"));
+    let mut values = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let value = &rest[start + open.len()..];
+        let Some(end) = value.find(&close) else {
+            break;
+        };
+        values.push(
+            value[..end]
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&"),
+        );
+        rest = &value[end + close.len()..];
+    }
+    values
+}
+
+/// Percent-encodes the path of an object, leaving the separators untouched.
+fn uri_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char);
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[derive(Debug)]
+/// Publishes the posts to an S3-compatible bucket
+pub struct S3Publisher {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// The prefix prepended to the key of every uploaded object
+    pub prefix: String,
+    /// The `Cache-Control` header sent with every uploaded object
+    pub cache_control: String,
+}
+
+impl S3Publisher {
+    /// Creates the publisher from the `S3_ENDPOINT`, `S3_BUCKET`, `S3_REGION`,
+    /// `S3_ACCESS_KEY_ID`, `S3_SECRET_ACCESS_KEY` and `S3_PREFIX` environment variables.
+    /// Only the region and the prefix are optional.
+    pub fn from_env(config: &PublishConfig) -> Result<Self, String> {
+        let required = |name: &str| {
+            std::env::var(name).map_err(|_| format!("Missing {name} environment variable"))
+        };
+
+        Ok(Self {
+            endpoint: required("S3_ENDPOINT")?,
+            bucket: required("S3_BUCKET")?,
+            region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            access_key: required("S3_ACCESS_KEY_ID")?,
+            secret_key: required("S3_SECRET_ACCESS_KEY")?,
+            prefix: std::env::var("S3_PREFIX").unwrap_or_default(),
+            cache_control: config.cache_control.clone(),
+        })
+    }
+
+    /// Returns the request of the given method on the object with the given key, or on the
+    /// bucket if the key is empty, signed for the given query and payload.
+    fn signed_request(
+        &self,
+        client: &reqwest::Client,
+        method: reqwest::Method,
+        key: &str,
+        query: &[(&str, String)],
+        payload: &[u8],
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let path = match key {
+            "" => uri_encode(&format!("/{}", self.bucket)),
+            key => uri_encode(&format!("/{}/{key}", self.bucket)),
+        };
+        let mut query = query
+            .iter()
+            .map(|(name, value)| format!("{name}={}", query_encode(value)))
+            .collect::<Vec<_>>();
+        query.sort();
+        let query = query.join("&");
+        let separator = if query.is_empty() { "" } else { "?" };
+        let url = reqwest::Url::parse(&format!(
+            "{}{path}{separator}{query}",
+            self.endpoint.trim_end_matches('/')
+        ))
+        .map_err(|e| format!("Invalid S3 endpoint: {e}"))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err("Invalid S3 endpoint: missing host".to_string()),
+        };
+
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex(&Sha256::digest(payload));
+
+        let (canonical_request, signed_headers) = canonical_request(
+            method.as_str(),
+            &path,
+            &query,
+            &[
+                ("host", &host),
+                ("x-amz-content-sha256", &payload_hash),
+                ("x-amz-date", &amz_date),
+            ],
+            &payload_hash,
+        );
+        let (signature, scope) = signature(
+            &self.secret_key,
+            &self.region,
+            "s3",
+            &amz_date,
+            &canonical_request,
+        );
+
+        Ok(client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header(
+                "Authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                    self.access_key
+                ),
+            ))
+    }
+
+    /// Uploads the given content as the object with the given key.
+    async fn put_object(
+        &self,
+        client: &reqwest::Client,
+        key: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), String> {
+        let mut request = self
+            .signed_request(client, reqwest::Method::PUT, key, &[], &content)?
+            .header("Content-Type", content_type);
+        if !self.cache_control.is_empty() {
+            request = request.header("Cache-Control", &self.cache_control);
+        }
+
+        let response = request
+            .body(content)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to upload {key}: HTTP {} {}",
+                response.status().as_u16(),
+                response.text().await.unwrap_or_default()
+            ))
+        }
+    }
+
+    /// Deletes the object with the given key.
+    async fn delete_object(&self, client: &reqwest::Client, key: &str) -> Result<(), String> {
+        let response = self
+            .signed_request(client, reqwest::Method::DELETE, key, &[], b"")?
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to delete {key}: HTTP {} {}",
+                response.status().as_u16(),
+                response.text().await.unwrap_or_default()
+            ))
+        }
+    }
+
+    /// Lists the keys of the objects whose key starts with the given prefix, following the
+    /// pages of the listing.
+    async fn list_objects(
+        &self,
+        client: &reqwest::Client,
+        prefix: &str,
+    ) -> Result<Vec<String>, String> {
+        let mut keys = vec![];
+        let mut token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = token {
+                query.push(("continuation-token", token));
+            }
+            let response = self
+                .signed_request(client, reqwest::Method::GET, "", &query, b"")?
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            if !status.is_success() {
+                return Err(format!(
+                    "Failed to list {prefix}: HTTP {} {body}",
+                    status.as_u16()
+                ));
+            }
+
+            keys.extend(xml_values(&body, "Key"));
+            let truncated =
+                xml_values(&body, "IsTruncated").first().map(String::as_str) == Some("true");
+            token = xml_values(&body, "NextContinuationToken")
+                .into_iter()
+                .next();
+            if !truncated || token.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+
+    /// Returns the prefix of the keys of the objects of the post.
+    fn post_prefix(&self, post: &Post) -> String {
+        let post_path = post
+            .relative_path(None)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        [self.prefix.trim_matches('/'), &post_path]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Returns the files of the output directory along with the keys of their objects.
+    fn objects(&self, post: &Post, output: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+        let post_prefix = self.post_prefix(post);
+        Ok(list_files(output)?
+            .into_iter()
+            .filter(|file| file.strip_prefix(output) != Ok(Path::new(SOURCE_SNAPSHOT)))
+            .map(|file| {
+                let relative = file.strip_prefix(output).unwrap_or(&file);
+                let key = [post_prefix.as_str()]
+                    .into_iter()
+                    .chain(
+                        relative
+                            .components()
+                            .map(|c| c.as_os_str().to_str().unwrap_or_default()),
+                    )
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                (file, key)
+            })
+            .collect())
+    }
+}
+
+impl Publisher for S3Publisher {
+    fn publish(&self, post: &Post, output: &Path) -> Result<(), String> {
+        let objects = self.objects(post, output)?;
+        let client = reqwest::Client::new();
+
+        block_on(async {
+            for (file, key) in objects {
+                info!("Uploading {} to {key}", file.display());
+                let content = fs::read(&file)
+                    .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+                self.put_object(&client, &key, content, content_type(&file))
+                    .await?;
+            }
+            Ok(())
+        })?
+    }
+
+    fn unpublish(&self, post: &Post) -> Result<(), String> {
+        let post_prefix = self.post_prefix(post);
+        if post_prefix.is_empty() {
+            return Err("Unable to find the prefix of the objects of the post".to_string());
+        }
+        let client = reqwest::Client::new();
+
+        block_on(async {
+            let keys = self
+                .list_objects(&client, &format!("{post_prefix}/"))
+                .await?;
+            if keys.is_empty() {
+                info!("No object under {post_prefix}/, nothing to remove");
+            }
+            for key in keys {
+                info!("Deleting {key}");
+                self.delete_object(&client, &key).await?;
+            }
+            Ok(())
+        })?
+    }
+}
+
+#[derive(Debug)]
+/// Publishes the posts to a git repository
+pub struct GitPublisher {
+    /// The local clone of the repository
+    pub repo: PathBuf,
+    pub branch: String,
+    pub remote: String,
+    /// The commit message, `{title}` is replaced by the title of the post
+    pub message: String,
+}
+
+impl GitPublisher {
+    /// Creates the publisher from the configuration.
+    pub fn from_config(config: &GitPublishConfig) -> Result<Self, String> {
+        if config.repo.is_empty() {
+            return Err("Missing publish.git.repo configuration".to_string());
+        }
+
+        Ok(Self {
+            repo: PathBuf::from(&config.repo),
+            branch: config.branch.clone(),
+            remote: config.remote.clone(),
+            message: config.message.clone(),
+        })
+    }
+
+    /// Runs git with the given arguments in the repository and returns its output.
+    fn git(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.repo)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run git: {e}"))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(format!(
+                "`git {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+impl Publisher for GitPublisher {
+    fn publish(&self, post: &Post, output: &Path) -> Result<(), String> {
+        if !self.repo.join(".git").exists() {
+            return Err(format!("{} is not a git repository", self.repo.display()));
+        }
+
+        let relative = post.relative_path(None);
+        let relative = relative.to_string_lossy();
+        let destination = self.repo.join(relative.as_ref());
+        info!("Copying {} to {}", output.display(), destination.display());
+        copy_dir_all(output, &destination)
+            .map_err(|e| format!("Failed to copy the post into the repository: {e}"))?;
+        let snapshot = destination.join(SOURCE_SNAPSHOT);
+        if snapshot.exists() {
+            fs::remove_file(&snapshot)
+                .map_err(|e| format!("Failed to remove {}: {e}", snapshot.display()))?;
+        }
+
+        // Only the directory of the post is staged and committed, whatever else is pending
+        self.git(&["add", "-A", "--", &relative])?;
+        if self
+            .git(&["status", "--porcelain", "--", &relative])?
+            .trim()
+            .is_empty()
+        {
+            info!("Nothing changed since the last publication, nothing to commit");
+            return Ok(());
+        }
+
+        let message = self.message.replace("{title}", &post.metadata.post.title);
+        self.git(&["commit", "-q", "-m", &message, "--", &relative])?;
+        self.git(&["push", &self.remote, &format!("HEAD:{}", self.branch)])?;
+        Ok(())
+    }
+
+    fn unpublish(&self, post: &Post) -> Result<(), String> {
+        let relative = post.relative_path(None);
+        let relative = relative.to_string_lossy();
+        let destination = self.repo.join(relative.as_ref());
+        if self.git(&["ls-files", "--", &relative])?.trim().is_empty() {
+            info!(
+                "{} is not in the repository, nothing to remove",
+                destination.display()
+            );
+            return Ok(());
+        }
+
+        // Only the removal of the post is staged and committed, whatever else is pending
+        info!("Removing {}", destination.display());
+        self.git(&["rm", "-r", "-q", "--", &relative])?;
+        if destination.exists() {
+            fs::remove_dir_all(&destination)
+                .map_err(|e| format!("Failed to remove the post from the repository: {e}"))?;
+        }
+        let message = format!("Unpublish: {}", post.metadata.post.title);
+        self.git(&["commit", "-q", "-m", &message, "--", &relative])?;
+        self.git(&["push", &self.remote, &format!("HEAD:{}", self.branch)])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+/// This module finds the posts related to a given post, judging by the tags and keywords
+/// they share, to suggest further reading at the end of the built posts.
+use std::collections::HashSet;
+
+use crate::post::Post;
+use crate::utils::escape_xml;
+
+/// Returns the tags and keywords of the post, lowercased.
+fn topics(post: &Post) -> HashSet<String> {
+    post.metadata
+        .post
+        .tags
+        .iter()
+        .chain(&post.metadata.opengraph.keywords)
+        .map(|topic| topic.to_lowercase())
+        .collect()
+}
+
+/// Returns the `count` published posts sharing the most tags and keywords with the given
+/// post, most related first. Ties are broken by recency. The post itself and the posts
+/// sharing nothing with it are left out.
+pub fn related_posts<'a>(post: &Post, posts: &'a [Post], count: usize) -> Vec<&'a Post> {
+    let topics = topics(post);
+    let mut related: Vec<(usize, &Post)> = posts
+        .iter()
+        .filter(|other| other.path != post.path && !other.metadata.post.is_draft())
+        .map(|other| (topics.intersection(&self::topics(other)).count(), other))
+        .filter(|(shared, _)| *shared > 0)
+        .collect();
+
+    related.sort_by(|(a_shared, a), (b_shared, b)| {
+        b_shared.cmp(a_shared).then_with(|| {
+            b.metadata
+                .post
+                .published_date
+                .cmp(&a.metadata.post.published_date)
+        })
+    });
+    related
+        .into_iter()
+        .take(count)
+        .map(|(_, other)| other)
+        .collect()
+}
+
+/// Renders the related posts section of a page, from the title and URL of each post.
+/// Returns an empty string when there are no related posts.
+pub fn related_html(related: &[(String, String)]) -> String {
+    if related.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::from("<aside class=\"related-posts\">\n<h2>Related posts</h2>\n<ul>\n");
+    for (title, url) in related {
+        html.push_str(&format!(
+            "  <li><a href=\"{}\">{}</a></li>\n",
+            escape_xml(url),
+            escape_xml(title)
+        ));
+    }
+    html.push_str("</ul>\n</aside>");
+    html
+}
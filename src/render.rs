@@ -0,0 +1,686 @@
+/// This module holds the passes applied to a post while building it. Each pass is a pure
+/// function taking the HTML (or, for a few of them, the markdown) and returning the
+/// transformed one.
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use clap::ValueEnum;
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+use markdown::mdast::Node;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use slugify::slugify;
+
+use crate::config::DisplayConfig;
+use crate::post::{BuildOptions, Metadata};
+use crate::utils::escape_xml;
+
+/// The version of KaTeX loaded by the client-side math rendering
+const KATEX_URL: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist";
+
+/// The class given to the header image of a post, which is left out of some passes
+pub const HEADER_CLASS: &str = "header-image";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// The ways the math of the posts can be rendered
+pub enum MathMode {
+    /// Keeps the LaTeX source and renders it in the browser with KaTeX
+    Client,
+    /// Converts the LaTeX source to MathML while building
+    Server,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// The ways the stylesheet of the posts can be included in the pages
+pub enum CssMode {
+    /// Inlines the stylesheet in a `<style>` block of the head
+    #[default]
+    Inline,
+    /// Copies the stylesheet next to the page and links to it
+    Link,
+}
+
+/// The theme of the pages, used when no stylesheet is given
+pub const DEFAULT_CSS: &str = include_str!("templates/style.css");
+
+/// The name of the stylesheet copied next to the pages linking to it
+pub const STYLESHEET: &str = "style.css";
+
+/// Renders the tag including the stylesheet in the head of a page.
+pub fn style_tag(css: &str, mode: CssMode) -> String {
+    match mode {
+        CssMode::Inline => format!("  <style>\n{}\n  </style>\n", css.trim_end()),
+        CssMode::Link => format!("  <link rel=\"stylesheet\" href=\"{STYLESHEET}\">\n"),
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// The markdown dialects the posts can be written in
+pub enum Flavor {
+    /// GitHub Flavored Markdown: CommonMark with tables, autolinks, strikethrough, task
+    /// lists and footnotes
+    #[default]
+    Gfm,
+    /// Strict CommonMark, without any extension
+    Commonmark,
+}
+
+/// Returns the options of the markdown renderer for the given flavor.
+pub fn flavor_options(flavor: Flavor) -> markdown::Options {
+    match flavor {
+        Flavor::Gfm => markdown::Options::gfm(),
+        Flavor::Commonmark => markdown::Options::default(),
+    }
+}
+
+/// Returns the options of the markdown renderer: the markdown flavor of the build options,
+/// with the constructs toggled by the configuration and the build options. The footnotes
+/// are only available in GitHub Flavored Markdown.
+pub fn markdown_options(build: &BuildOptions) -> markdown::Options {
+    let config = &build.markdown;
+    let mut options = flavor_options(build.flavor);
+    if build.flavor == Flavor::Gfm {
+        options.parse.constructs.gfm_footnote_definition = config.footnotes;
+        options.parse.constructs.gfm_label_start_footnote = config.footnotes;
+    }
+    options.parse.constructs.math_text = build.math.is_some();
+    options.parse.constructs.math_flow = build.math.is_some();
+    options.compile.gfm_footnote_label = Some(config.footnote_label.clone());
+    options.compile.gfm_footnote_back_label = Some(config.footnote_back_label.clone());
+    options
+}
+
+/// Reverts the escaping of the code rendered by the markdown renderer.
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// Converts the math spans and blocks of the given HTML, as rendered by the markdown
+/// renderer with the math constructs enabled, to MathML. Code, including fenced code
+/// blocks, is left untouched.
+pub fn math_to_mathml(html: &str) -> Result<String, String> {
+    let math = Regex::new(
+        r#"(?s)<pre><code class="language-math math-display">(.*?)</code></pre>|<code class="language-math math-inline">(.*?)</code>"#,
+    )
+    .expect("The math regex is valid");
+
+    let mut error = None;
+    let converted = math.replace_all(html, |captures: &Captures| {
+        let (latex, style) = match (captures.get(1), captures.get(2)) {
+            (Some(display), _) => (display.as_str(), DisplayStyle::Block),
+            (None, Some(inline)) => (inline.as_str(), DisplayStyle::Inline),
+            (None, None) => unreachable!("One of the alternatives matched"),
+        };
+
+        latex_to_mathml(unescape_html(latex).trim(), style)
+            .map(|mathml| {
+                // The converter writes the comparison operators unescaped
+                mathml
+                    .replace("<mo><</mo>", "<mo>&lt;</mo>")
+                    .replace("<mo>></mo>", "<mo>&gt;</mo>")
+                    .replace("<mo>&</mo>", "<mo>&amp;</mo>")
+            })
+            .unwrap_or_else(|e| {
+                error.get_or_insert(format!("Invalid math `{}`: {e}", latex.trim()));
+                captures[0].to_string()
+            })
+    });
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(converted.into_owned()),
+    }
+}
+
+/// Removes the leading `# Title` heading of the given markdown when it matches the given
+/// title, so that the title rendered by the template is not duplicated. Content starting
+/// with anything else is returned as-is.
+pub fn strip_title<'a>(content: &'a str, title: &str) -> &'a str {
+    let trimmed = content.trim_start();
+    let (first, rest) = trimmed.split_once('\n').unwrap_or((trimmed, ""));
+    match first.trim_end().strip_prefix("# ") {
+        Some(heading) if heading.trim() == title.trim() => rest.trim_start(),
+        _ => content,
+    }
+}
+
+/// Collects the byte ranges of the plain text of the markdown tree, leaving out code and
+/// the text of the links showing their own URL.
+fn text_ranges(node: &Node, ranges: &mut Vec<(usize, usize)>) {
+    match node {
+        Node::Text(text) => {
+            if let Some(position) = &text.position {
+                ranges.push((position.start.offset, position.end.offset));
+            }
+        }
+        Node::Link(link) if node.to_string() == link.url => {}
+        _ => {
+            for child in node.children().into_iter().flatten() {
+                text_ranges(child, ranges);
+            }
+        }
+    }
+}
+
+/// Replaces the `:shortcode:` sequences of the given markdown by the corresponding
+/// emoji. Unknown shortcodes, code spans, code blocks and URLs are left untouched.
+pub fn expand_emoji(content: &str) -> Result<String, String> {
+    let tree = markdown::to_mdast(content, &markdown::ParseOptions::gfm())
+        .map_err(|e| format!("Failed to parse content: {e}"))?;
+    let mut ranges = vec![];
+    text_ranges(&tree, &mut ranges);
+
+    let shortcode =
+        Regex::new(r"\S*://\S*|:([a-z0-9_+-]+):").expect("The shortcode regex is valid");
+    let mut expanded = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end) in ranges {
+        expanded.push_str(&content[last..start]);
+        expanded.push_str(
+            &shortcode.replace_all(&content[start..end], |captures: &Captures| {
+                captures
+                    .get(1)
+                    .and_then(|name| emojis::get_by_shortcode(name.as_str()))
+                    .map_or_else(
+                        || captures[0].to_string(),
+                        |emoji| emoji.as_str().to_string(),
+                    )
+            }),
+        );
+        last = end;
+    }
+    expanded.push_str(&content[last..]);
+    Ok(expanded)
+}
+
+/// Returns the tags loading KaTeX and rendering the math of the page with it.
+pub fn katex_head() -> String {
+    format!(
+        r#"<link rel="stylesheet" href="{KATEX_URL}/katex.min.css">
+<script defer src="{KATEX_URL}/katex.min.js"></script>
+<script>
+document.addEventListener("DOMContentLoaded", function () {{
+  document.querySelectorAll("code.language-math").forEach(function (element) {{
+    var display = element.classList.contains("math-display");
+    var target = display ? element.parentElement : element;
+    var container = document.createElement(display ? "div" : "span");
+    katex.render(element.textContent, container, {{ displayMode: display, throwOnError: false }});
+    target.replaceWith(container);
+  }});
+}});
+</script>
+"#
+    )
+}
+
+/// Returns whether the `<img>` tag displays the header of the post.
+fn is_header_image(tag: &str) -> bool {
+    tag.contains(HEADER_CLASS) || tag.contains("images/header/")
+}
+
+/// Adds `loading="lazy"` and `decoding="async"` to the images of the given HTML.
+/// The header image, displayed above the fold, and images already carrying a `loading`
+/// attribute are left untouched.
+pub fn lazy_images(html: &str) -> String {
+    let image = Regex::new(r"<img\b[^>]*?>").expect("The image regex is valid");
+
+    image
+        .replace_all(html, |captures: &Captures| {
+            let tag = &captures[0];
+            if is_header_image(tag) || tag.contains("loading=") {
+                return tag.to_string();
+            }
+
+            let (start, end) = match tag.strip_suffix("/>") {
+                Some(start) => (start.trim_end(), " />"),
+                None => (&tag[..tag.len() - 1], ">"),
+            };
+            let decoding = if tag.contains("decoding=") {
+                ""
+            } else {
+                " decoding=\"async\""
+            };
+            format!("{start} loading=\"lazy\"{decoding}{end}")
+        })
+        .to_string()
+}
+
+/// Makes the links of the given HTML pointing to another host than the site's base URL
+/// open in a new tab, with `target="_blank"` and `rel="noopener noreferrer"`. Relative
+/// links, links to the site and links already carrying a `target` are left untouched.
+pub fn external_links(html: &str, base_url: &str) -> String {
+    let anchor = Regex::new(r"<a\b[^>]*?>").expect("The anchor regex is valid");
+    let href = Regex::new(r#"href="(https?://[^"]*)""#).expect("The href regex is valid");
+    let site_host = reqwest::Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase));
+
+    anchor
+        .replace_all(html, |captures: &Captures| {
+            let tag = &captures[0];
+            let host = href
+                .captures(tag)
+                .and_then(|href| reqwest::Url::parse(&href[1]).ok())
+                .and_then(|url| url.host_str().map(str::to_lowercase));
+            let external = host.is_some() && host != site_host;
+            if !external || tag.contains("target=") {
+                return tag.to_string();
+            }
+
+            let rel = if tag.contains("rel=") {
+                ""
+            } else {
+                " rel=\"noopener noreferrer\""
+            };
+            format!("{} target=\"_blank\"{rel}>", &tag[..tag.len() - 1])
+        })
+        .to_string()
+}
+
+/// Returns whether the link is left alone when making the links absolute: anchors, links to
+/// other hosts and other schemes (`mailto:`, `data:`, ...).
+fn is_absolute(link: &str) -> bool {
+    let scheme = Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*:").expect("The scheme regex is valid");
+    link.is_empty() || link.starts_with('#') || link.starts_with("//") || scheme.is_match(link)
+}
+
+/// Returns the absolute URL of the link found in the page at `page_url`. Site-absolute links
+/// are resolved against the base URL of the site, so that they keep its path, and relative
+/// links against the URL of the page. Absolute links and anchors are returned untouched.
+pub fn absolute_url(link: &str, base_url: &str, page_url: &str) -> String {
+    if is_absolute(link) {
+        link.to_string()
+    } else if link.starts_with('/') {
+        format!("{}{link}", base_url.trim_end_matches('/'))
+    } else {
+        reqwest::Url::parse(page_url)
+            .and_then(|url| url.join(link))
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| link.to_string())
+    }
+}
+
+/// Makes the URLs of the `href`, `src` and `srcset` attributes of the HTML absolute, see
+/// `absolute_url`.
+pub fn absolute_links(html: &str, base_url: &str, page_url: &str) -> String {
+    let attribute =
+        Regex::new(r#"\b(href|src|srcset)="([^"]*)""#).expect("The attribute regex is valid");
+
+    attribute
+        .replace_all(html, |captures: &Captures| {
+            let value = &captures[2];
+            let value = if &captures[1] == "srcset" {
+                value
+                    .split(',')
+                    .map(|candidate| {
+                        let candidate = candidate.trim();
+                        match candidate.split_once(' ') {
+                            Some((url, descriptor)) => {
+                                format!("{} {descriptor}", absolute_url(url, base_url, page_url))
+                            }
+                            None => absolute_url(candidate, base_url, page_url),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            } else {
+                absolute_url(value, base_url, page_url)
+            };
+            format!("{}=\"{value}\"", &captures[1])
+        })
+        .to_string()
+}
+
+/// Returns the anchor a heading with the given text asks for: its slug, or `section` when
+/// it has none.
+fn heading_slug(heading: &str) -> String {
+    let slug = slugify!(heading);
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Returns the anchor ids of the headings with the given texts: their slug, suffixed with
+/// `-1`, `-2`, ... when an earlier heading already uses it.
+pub fn heading_ids<S: AsRef<str>>(headings: &[S]) -> Vec<String> {
+    let mut used = HashSet::new();
+    headings
+        .iter()
+        .map(|heading| {
+            let slug = heading_slug(heading.as_ref());
+            let mut id = slug.clone();
+            let mut suffix = 0;
+            while !used.insert(id.clone()) {
+                suffix += 1;
+                id = format!("{slug}-{suffix}");
+            }
+            id
+        })
+        .collect()
+}
+
+/// Returns the anchors that some of the headings with the given texts ask for but do not
+/// get from [`heading_ids`], along with the indices of the heading holding the anchor and
+/// of the headings suffixed because of it.
+pub fn anchor_collisions<S: AsRef<str>>(headings: &[S]) -> Vec<(String, Vec<usize>)> {
+    let ids = heading_ids(headings);
+    let owners: HashMap<&str, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (id.as_str(), index))
+        .collect();
+
+    let mut anchors: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, heading) in headings.iter().enumerate() {
+        let slug = heading_slug(heading.as_ref());
+        if ids[index] != slug {
+            anchors
+                .entry(slug.clone())
+                .or_insert_with(|| vec![owners[slug.as_str()]])
+                .push(index);
+        }
+    }
+    anchors.into_iter().collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// A heading of the rendered HTML
+pub struct Heading {
+    pub level: u8,
+    pub id: String,
+    /// The inner HTML of the heading
+    pub html: String,
+}
+
+/// Gives an `id` to the headings of the given HTML that do not have any, so that they can
+/// be linked to. Returns the HTML along with its headings.
+pub fn heading_anchors(html: &str) -> (String, Vec<Heading>) {
+    let heading = Regex::new(r"(?s)<h([1-6])>(.*?)</h[1-6]>").expect("The heading regex is valid");
+    let tag = Regex::new(r"<[^>]*>").expect("The tag regex is valid");
+
+    let texts: Vec<String> = heading
+        .captures_iter(html)
+        .map(|captures| unescape_html(&tag.replace_all(&captures[2], "")))
+        .collect();
+    let mut ids = heading_ids(&texts).into_iter();
+
+    let mut headings = vec![];
+    let html = heading
+        .replace_all(html, |captures: &Captures| {
+            let id = ids.next().unwrap_or_default();
+            let level = &captures[1];
+            headings.push(Heading {
+                level: level.parse().unwrap_or(1),
+                id: id.clone(),
+                html: captures[2].to_string(),
+            });
+            format!("<h{level} id=\"{id}\">{}</h{level}>", &captures[2])
+        })
+        .to_string();
+    (html, headings)
+}
+
+/// Appends a link to itself, `<a class="anchor" href="#id">#</a>`, to each heading of the
+/// given HTML given an `id` by `heading_anchors`.
+pub fn heading_links(html: &str) -> String {
+    Regex::new(r#"(?s)<h([1-6]) id="([^"]*)">(.*?)</h[1-6]>"#)
+        .expect("The heading regex is valid")
+        .replace_all(html, |captures: &Captures| {
+            let (level, id) = (&captures[1], &captures[2]);
+            format!(
+                "<h{level} id=\"{id}\">{} <a class=\"anchor\" href=\"#{id}\">#</a></h{level}>",
+                &captures[3]
+            )
+        })
+        .to_string()
+}
+
+/// Renders the table of contents of the given headings, as nested lists linking to them.
+/// The top-level headings, usually the title of the post, are left out.
+pub fn table_of_contents(headings: &[Heading]) -> String {
+    let mut html = String::from("<nav class=\"toc\">\n");
+    let mut levels: Vec<u8> = vec![];
+    for heading in headings.iter().filter(|heading| heading.level > 1) {
+        while levels.last().is_some_and(|&level| level > heading.level) {
+            html.push_str("</li>\n</ul>\n");
+            levels.pop();
+        }
+        if levels.last() == Some(&heading.level) {
+            html.push_str("</li>\n");
+        } else {
+            html.push_str("<ul>\n");
+            levels.push(heading.level);
+        }
+        html.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a>",
+            heading.id,
+            tag_free(&heading.html)
+        ));
+    }
+    for _ in levels {
+        html.push_str("</li>\n</ul>\n");
+    }
+    html.push_str("</nav>\n");
+    html
+}
+
+/// Removes the links of the given inner HTML, which cannot be nested in the links of the
+/// table of contents.
+fn tag_free(html: &str) -> String {
+    Regex::new(r"</?a\b[^>]*>")
+        .expect("The anchor regex is valid")
+        .replace_all(html, "")
+        .to_string()
+}
+
+/// The licenses linked to from the license footer: their SPDX identifier, name and URL
+const LICENSES: &[(&str, &str, &str)] = &[
+    (
+        "CC-BY-4.0",
+        "CC BY 4.0",
+        "https://creativecommons.org/licenses/by/4.0/",
+    ),
+    (
+        "CC-BY-SA-4.0",
+        "CC BY-SA 4.0",
+        "https://creativecommons.org/licenses/by-sa/4.0/",
+    ),
+    (
+        "CC-BY-ND-4.0",
+        "CC BY-ND 4.0",
+        "https://creativecommons.org/licenses/by-nd/4.0/",
+    ),
+    (
+        "CC-BY-NC-4.0",
+        "CC BY-NC 4.0",
+        "https://creativecommons.org/licenses/by-nc/4.0/",
+    ),
+    (
+        "CC-BY-NC-SA-4.0",
+        "CC BY-NC-SA 4.0",
+        "https://creativecommons.org/licenses/by-nc-sa/4.0/",
+    ),
+    (
+        "CC-BY-NC-ND-4.0",
+        "CC BY-NC-ND 4.0",
+        "https://creativecommons.org/licenses/by-nc-nd/4.0/",
+    ),
+    (
+        "CC0-1.0",
+        "CC0 1.0",
+        "https://creativecommons.org/publicdomain/zero/1.0/",
+    ),
+    ("MIT", "MIT", "https://opensource.org/licenses/MIT"),
+    (
+        "Apache-2.0",
+        "Apache 2.0",
+        "https://www.apache.org/licenses/LICENSE-2.0",
+    ),
+];
+
+/// Renders the footer stating the license of the post. Known licenses, given by their SPDX
+/// identifier (e.g. `CC-BY-4.0`, in any case), link to their text; any other license is
+/// written as is.
+pub fn license_footer(license: &str) -> String {
+    let license = match LICENSES
+        .iter()
+        .find(|(id, _, _)| id.eq_ignore_ascii_case(license))
+    {
+        Some((_, name, url)) => format!("<a rel=\"license\" href=\"{url}\">{name}</a>"),
+        None => escape_xml(license),
+    };
+    format!("<footer class=\"license\">This post is licensed under {license}.</footer>")
+}
+
+/// Renders the links to the posts published before and after the post, from their title
+/// and URL. Returns an empty string when there are neither.
+pub fn post_nav(previous: Option<&(String, String)>, next: Option<&(String, String)>) -> String {
+    if previous.is_none() && next.is_none() {
+        return String::new();
+    }
+
+    let link = |rel: &str, label: &str, (title, url): &(String, String)| {
+        format!(
+            "  <a rel=\"{rel}\" href=\"{}\">{label} {}</a>\n",
+            escape_xml(url),
+            escape_xml(title)
+        )
+    };
+    let mut html = String::from("<nav class=\"post-nav\">\n");
+    if let Some(previous) = previous {
+        html.push_str(&link("prev", "Previous:", previous));
+    }
+    if let Some(next) = next {
+        html.push_str(&link("next", "Next:", next));
+    }
+    html.push_str("</nav>");
+    html
+}
+
+/// Renders the `<link>` tag giving the canonical URL of the page.
+pub fn canonical_link(url: &str) -> String {
+    format!("  <link rel=\"canonical\" href=\"{}\">\n", escape_xml(url))
+}
+
+/// Renders the `<link>` tags of the favicon of the page: the copy of the source favicon
+/// and, when they were generated, its resized PNG variants.
+pub fn favicon_links(name: &str, sizes: &[u32]) -> String {
+    let mut links = format!("  <link rel=\"icon\" href=\"{}\">\n", escape_xml(name));
+    for size in sizes {
+        links.push_str(&format!(
+            "  <link rel=\"icon\" type=\"image/png\" sizes=\"{size}x{size}\" href=\"favicon-{size}.png\">\n"
+        ));
+    }
+    links
+}
+
+/// Marks the first level 1 heading of the given HTML as the `p-name` of the microformats2
+/// h-entry of the page.
+pub fn entry_name(html: &str) -> String {
+    Regex::new(r"<h1( [^>]*)?>")
+        .expect("The heading regex is valid")
+        .replacen(html, 1, |captures: &Captures| {
+            format!(
+                "<h1 class=\"p-name\"{}>",
+                captures.get(1).map_or("", |attributes| attributes.as_str())
+            )
+        })
+        .to_string()
+}
+
+/// Renders the byline of the microformats2 h-entry of the page: the authors of the post,
+/// as `p-author h-card`, and its `dt-published` publication date, displayed as configured.
+/// Returns an empty string when the post has neither.
+pub fn entry_byline(metadata: &Metadata, display: &DisplayConfig) -> String {
+    let mut parts = vec![];
+    let authors = metadata
+        .post
+        .authors
+        .iter()
+        .map(|author| {
+            format!(
+                "<span class=\"p-author h-card\">{}</span>",
+                escape_xml(author)
+            )
+        })
+        .collect::<Vec<_>>();
+    if !authors.is_empty() {
+        parts.push(format!("By {}", authors.join(", ")));
+    }
+    if let Some(published) = metadata.post.published_date {
+        parts.push(format!(
+            "<time class=\"dt-published\" datetime=\"{}\">{}</time>",
+            published.to_rfc3339(),
+            escape_xml(&display.format(published))
+        ));
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("<p class=\"byline\">{}</p>", parts.join(", "))
+    }
+}
+
+/// Renders a `<meta>` tag identified by the given attribute (`name` or `property`).
+/// Returns an empty string when there is no content, so that empty tags are skipped.
+pub fn meta_tag(attribute: &str, name: &str, content: &str) -> String {
+    if content.is_empty() {
+        return String::new();
+    }
+    format!(
+        "  <meta {attribute}=\"{name}\" content=\"{}\">\n",
+        escape_xml(content)
+    )
+}
+
+/// Renders the schema.org `BlogPosting` structured data of the post as a JSON-LD
+/// script block. Only the fields that are set are included.
+pub fn json_ld(metadata: &Metadata, image: &str) -> String {
+    let mut data = Map::new();
+    data.insert("@context".to_string(), json!("https://schema.org"));
+    data.insert("@type".to_string(), json!("BlogPosting"));
+
+    let mut insert = |key: &str, value: &str| {
+        if !value.is_empty() {
+            data.insert(key.to_string(), json!(value));
+        }
+    };
+    insert("headline", &metadata.post.title);
+    insert("description", metadata.social_description());
+    insert("image", image);
+    insert("keywords", &metadata.opengraph.keywords.join(", "));
+    if let Some(published) = metadata.post.published_date {
+        insert("datePublished", &published.to_rfc3339());
+    }
+    if let Some(update) = metadata.post.update {
+        insert("dateModified", &update.to_rfc3339());
+    }
+    let authors = metadata
+        .post
+        .authors
+        .iter()
+        .map(|author| json!({"@type": "Person", "name": author}))
+        .collect::<Vec<_>>();
+    match authors.as_slice() {
+        [] => {}
+        [author] => {
+            data.insert("author".to_string(), author.clone());
+        }
+        _ => {
+            data.insert("author".to_string(), Value::Array(authors));
+        }
+    }
+
+    // Escaping the slashes of closing tags keeps the JSON from ending the script early
+    let data = Value::Object(data).to_string().replace("</", "<\\/");
+    format!("  <script type=\"application/ld+json\">{data}</script>\n")
+}
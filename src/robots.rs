@@ -0,0 +1,43 @@
+/// This module generates the `robots.txt` file of the site, telling the crawlers what they
+/// may index and where the sitemap of the site is.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+use crate::config::Config;
+use crate::utils::create_path;
+
+/// Renders the `robots.txt` file: the rules of the configuration, followed by the URL of
+/// the sitemap under the base URL of the site. The sitemap is left out when the site has
+/// no base URL, crawlers requiring an absolute URL.
+pub fn robots_txt(config: &Config) -> String {
+    let robots = &config.robots;
+    let mut content = format!("User-agent: {}\n", robots.user_agent);
+    for path in &robots.allow {
+        content.push_str(&format!("Allow: {path}\n"));
+    }
+    for path in &robots.disallow {
+        content.push_str(&format!("Disallow: {path}\n"));
+    }
+
+    let base_url = config.site.base_url.trim_end_matches('/');
+    if base_url.is_empty() {
+        warn!("The site has no base URL, the sitemap is left out of robots.txt");
+    } else if !robots.sitemap.is_empty() {
+        content.push_str(&format!(
+            "\nSitemap: {base_url}/{}\n",
+            robots.sitemap.trim_start_matches('/')
+        ));
+    }
+    content
+}
+
+/// Writes the `robots.txt` file into the output directory and returns its path.
+pub fn write_robots(output: &Path, config: &Config) -> Result<PathBuf, String> {
+    create_path(output)?;
+    let path = output.join("robots.txt");
+    fs::write(&path, robots_txt(config))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(path)
+}
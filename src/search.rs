@@ -0,0 +1,224 @@
+/// This module searches the posts of a blog for a query, in their content, title, tags
+/// and keywords. The query is matched case-insensitively, either as a plain substring
+/// or as a regular expression.
+///
+/// It also generates the search index of the blog, a JSON file holding the plain text of
+/// every post, for client-side search libraries (lunr, Fuse.js, ...).
+use std::fmt;
+use std::path::Path;
+
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::export::to_plain_text;
+use crate::post::Post;
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// The part of a post a match was found in
+pub enum Field {
+    Title,
+    Tag,
+    Keyword,
+    Content,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Title => write!(f, "title"),
+            Self::Tag => write!(f, "tag"),
+            Self::Keyword => write!(f, "keyword"),
+            Self::Content => write!(f, "content"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+/// A single match of the query
+pub struct Match {
+    pub field: Field,
+    /// The line of the content the match was found on, starting at 1
+    pub line: Option<usize>,
+    /// The matching text: the whole line for the content, the value otherwise
+    pub text: String,
+    /// The lines surrounding the matching line of the content
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+/// The matches of the query in a single post
+pub struct SearchResult {
+    pub title: String,
+    pub path: String,
+    pub matches: Vec<Match>,
+}
+
+impl fmt::Display for SearchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({})", self.title, self.path)?;
+        for found in &self.matches {
+            let Some(line) = found.line else {
+                writeln!(f, "  {}: {}", found.field, found.text)?;
+                continue;
+            };
+
+            let first = line - found.before.len();
+            for (index, context) in found.before.iter().enumerate() {
+                writeln!(f, "  {}-  {context}", first + index)?;
+            }
+            writeln!(f, "  {line}:  {}", found.text)?;
+            for (index, context) in found.after.iter().enumerate() {
+                writeln!(f, "  {}-  {context}", line + 1 + index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+/// A query to search the posts for
+pub struct Query {
+    pattern: Regex,
+    /// Only matches the tags of the posts
+    pub tag_only: bool,
+    /// The number of lines of content shown around the matching lines
+    pub context: usize,
+}
+
+impl Query {
+    /// Creates a case-insensitive query, matching the given text as a substring, or as a
+    /// regular expression if `regex` is set.
+    pub fn new(query: &str, regex: bool) -> Result<Self, String> {
+        let pattern = if regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+
+        Ok(Self {
+            pattern: RegexBuilder::new(&pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| format!("Invalid regular expression: {e}"))?,
+            tag_only: false,
+            context: 1,
+        })
+    }
+
+    /// Returns the matches of the query in the given post.
+    pub fn matches(&self, post: &Post) -> Vec<Match> {
+        let value = |field, text: &str| Match {
+            field,
+            line: None,
+            text: text.to_string(),
+            before: vec![],
+            after: vec![],
+        };
+
+        let mut matches: Vec<Match> = post
+            .metadata
+            .post
+            .tags
+            .iter()
+            .filter(|tag| self.pattern.is_match(tag))
+            .map(|tag| value(Field::Tag, tag))
+            .collect();
+        if self.tag_only {
+            return matches;
+        }
+
+        if self.pattern.is_match(&post.metadata.post.title) {
+            matches.insert(0, value(Field::Title, &post.metadata.post.title));
+        }
+        matches.extend(
+            post.metadata
+                .opengraph
+                .keywords
+                .iter()
+                .filter(|keyword| self.pattern.is_match(keyword))
+                .map(|keyword| value(Field::Keyword, keyword)),
+        );
+
+        let lines: Vec<&str> = post.content.lines().collect();
+        for (index, line) in lines.iter().enumerate() {
+            if !self.pattern.is_match(line) {
+                continue;
+            }
+            let end = (index + 1 + self.context).min(lines.len());
+            matches.push(Match {
+                field: Field::Content,
+                line: Some(index + 1),
+                text: (*line).to_string(),
+                before: lines[index.saturating_sub(self.context)..index]
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                after: lines[index + 1..end]
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            });
+        }
+
+        matches
+    }
+}
+
+/// Searches the given posts for the query. Returns the posts with at least one match,
+/// in the order they are given.
+pub fn search(posts: &[Post], query: &Query) -> Vec<SearchResult> {
+    posts
+        .iter()
+        .filter_map(|post| {
+            let matches = query.matches(post);
+            (!matches.is_empty()).then(|| SearchResult {
+                title: post.metadata.post.title.clone(),
+                path: post.path.display().to_string(),
+                matches,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+/// The entry of a post in the search index
+pub struct IndexEntry {
+    pub title: String,
+    pub url: String,
+    pub tags: Vec<String>,
+    /// The plain text of the content, on a single line
+    pub body: String,
+}
+
+/// Generates the JSON search index of the given posts. The body of each entry is
+/// truncated to `max_chars` characters when given.
+pub fn search_index(
+    posts: &[Post],
+    root: &Path,
+    config: &Config,
+    max_chars: Option<usize>,
+) -> Result<String, String> {
+    let entries = posts
+        .iter()
+        .map(|post| {
+            let text = to_plain_text(&post.content)?;
+            let words = text.split_whitespace();
+            let mut body = words.collect::<Vec<_>>().join(" ");
+            if let Some(max_chars) = max_chars {
+                body = body.chars().take(max_chars).collect();
+            }
+
+            Ok(IndexEntry {
+                title: post.metadata.post.title.clone(),
+                url: post.url(config, root),
+                tags: post.metadata.post.tags.clone(),
+                body,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    serde_json::to_string(&entries).map_err(|e| format!("Failed to serialize the index: {e}"))
+}
@@ -0,0 +1,167 @@
+/// This module spell-checks the prose of a post. The words of the content, code, HTML tags,
+/// URLs and front matter excepted, are looked up in a dictionary, one word per line such
+/// as `/usr/share/dict/words`, and in the wordlist of the project listing its own terms.
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use markdown::mdast::Node;
+use serde::Serialize;
+
+/// The dictionary used by default
+pub const DEFAULT_DICTIONARY: &str = "/usr/share/dict/words";
+
+/// The file listing the words allowed in the posts of the project
+pub const WORDLIST: &str = "wordlist.txt";
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+/// A word of the prose of a post
+pub struct Word {
+    pub word: String,
+    /// The line of the content the word is on
+    pub line: usize,
+}
+
+/// The words known to the spell checker, compared case-insensitively
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Reads the words of the dictionary and of the wordlist of the project, one per line.
+    /// The wordlist may not exist yet.
+    pub fn load(dictionary: &Path, wordlist: &Path) -> Result<Self, String> {
+        let mut words = Self::default();
+        words.extend(read_words(dictionary)?.lines());
+        if wordlist.exists() {
+            words.extend(read_words(wordlist)?.lines());
+        }
+        Ok(words)
+    }
+
+    /// Adds the given words to the dictionary.
+    pub fn extend<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) {
+        self.words.extend(
+            words
+                .into_iter()
+                .map(|word| word.trim().to_lowercase())
+                .filter(|word| !word.is_empty()),
+        );
+    }
+
+    /// Returns whether the word, or the word without its possessive `'s`, is known.
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.words.contains(&word)
+            || word
+                .strip_suffix("'s")
+                .is_some_and(|stem| self.words.contains(stem))
+    }
+}
+
+/// Reads the file listing words, one per line.
+fn read_words(file: &Path) -> Result<String, String> {
+    fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {e}", file.display()))
+}
+
+/// Returns whether the whitespace separated chunk of text is a URL or an e-mail address.
+fn is_url(chunk: &str) -> bool {
+    chunk.contains("://") || chunk.starts_with("www.") || chunk.contains('@')
+}
+
+/// Splits the text, starting at the given line, into words.
+fn text_words(text: &str, first_line: usize, words: &mut Vec<Word>) {
+    for (offset, line) in text.lines().enumerate() {
+        for chunk in line.split_whitespace().filter(|chunk| !is_url(chunk)) {
+            let chunk_words = chunk
+                .split(|c: char| !c.is_alphabetic() && c != '\'' && c != '’')
+                .map(|word| word.trim_matches(['\'', '’']).replace('’', "'"))
+                .filter(|word| !word.is_empty());
+            words.extend(chunk_words.map(|word| Word {
+                word,
+                line: first_line + offset,
+            }));
+        }
+    }
+}
+
+/// Gathers the words of the text nodes of the tree, skipping code, HTML and math.
+fn collect_words(node: &Node, words: &mut Vec<Word>) {
+    match node {
+        Node::Code(_)
+        | Node::InlineCode(_)
+        | Node::Html(_)
+        | Node::Math(_)
+        | Node::InlineMath(_)
+        | Node::Yaml(_)
+        | Node::Toml(_) => {}
+        Node::Text(text) => {
+            let line = text
+                .position
+                .as_ref()
+                .map_or(1, |position| position.start.line);
+            text_words(&text.value, line, words);
+        }
+        _ => {
+            for child in node.children().into_iter().flatten() {
+                collect_words(child, words);
+            }
+        }
+    }
+}
+
+/// Extracts the words of the prose of the markdown content, with their line. Code blocks,
+/// inline code, HTML tags, math, URLs and the front matter are left out.
+pub fn prose_words(content: &str) -> Result<Vec<Word>, String> {
+    let mut options = markdown::ParseOptions::gfm();
+    options.constructs.frontmatter = true;
+    let tree = markdown::to_mdast(content, &options)
+        .map_err(|e| format!("Failed to parse content: {e}"))?;
+
+    let mut words = vec![];
+    collect_words(&tree, &mut words);
+    Ok(words)
+}
+
+/// Returns the words of the prose of the content missing from the dictionary.
+pub fn check(content: &str, dictionary: &Dictionary) -> Result<Vec<Word>, String> {
+    Ok(prose_words(content)?
+        .into_iter()
+        .filter(|word| !dictionary.contains(&word.word))
+        .collect())
+}
+
+/// Appends the word to the given wordlist, creating it if needed. Words already listed are
+/// not added again; returns whether the word was added.
+pub fn add_word(wordlist: &Path, word: &str) -> Result<bool, String> {
+    let word = word.trim();
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err(format!("Invalid word `{word}`"));
+    }
+    let listed = if wordlist.exists() {
+        read_words(wordlist)?
+    } else {
+        String::new()
+    };
+    let mut known = Dictionary::default();
+    known.extend(listed.lines());
+    if known.contains(word) {
+        return Ok(false);
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(wordlist)
+        .map_err(|e| format!("Failed to open {}: {e}", wordlist.display()))?;
+    let separator = if listed.is_empty() || listed.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    writeln!(file, "{separator}{word}")
+        .map_err(|e| format!("Failed to write {}: {e}", wordlist.display()))?;
+    Ok(true)
+}
@@ -0,0 +1,204 @@
+/// This module computes statistics over a whole blog: number of posts, words,
+/// most used tags and keywords, and publication rhythm. It also reports the usage of every
+/// tag and keyword, to help consolidating the taxonomy of the blog.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::post::Post;
+
+/// The number of tags and keywords shown in the human readable summary
+const TOP_ENTRIES: usize = 10;
+
+#[derive(Debug, Default, Serialize)]
+/// Aggregated statistics over a set of posts
+pub struct Stats {
+    pub total_posts: usize,
+    pub published: usize,
+    pub drafts: usize,
+    pub total_words: usize,
+    pub average_reading_time: f64,
+    /// The tags with their number of uses, most used first
+    pub tags: Vec<(String, usize)>,
+    /// The keywords with their number of uses, most used first
+    pub keywords: Vec<(String, usize)>,
+    /// The number of published posts per month of publication (`YYYY-MM`)
+    pub posts_per_month: BTreeMap<String, usize>,
+}
+
+/// Counts the occurrences of each value and sorts them by decreasing count, then by name.
+fn rank<'a>(values: impl Iterator<Item = &'a String>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&String, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    let mut ranking: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(value, count)| (value.clone(), count))
+        .collect();
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranking
+}
+
+impl Stats {
+    /// Computes the statistics of the given posts, their reading time being estimated at
+    /// the given reading speed.
+    pub fn compute(posts: &[Post], words_per_minute: usize) -> Self {
+        let published = posts
+            .iter()
+            .filter(|post| !post.metadata.post.is_draft())
+            .count();
+
+        let mut posts_per_month = BTreeMap::new();
+        for date in posts
+            .iter()
+            .filter_map(|post| post.metadata.post.published_date)
+        {
+            *posts_per_month
+                .entry(date.format("%Y-%m").to_string())
+                .or_default() += 1;
+        }
+
+        let average_reading_time = if posts.is_empty() {
+            0.0
+        } else {
+            #[allow(clippy::cast_precision_loss)]
+            let average = posts
+                .iter()
+                .map(|post| post.reading_time(words_per_minute))
+                .sum::<usize>() as f64
+                / posts.len() as f64;
+            average
+        };
+
+        Self {
+            total_posts: posts.len(),
+            published,
+            drafts: posts.len() - published,
+            total_words: posts.iter().map(Post::word_count).sum(),
+            average_reading_time,
+            tags: rank(posts.iter().flat_map(|post| &post.metadata.post.tags)),
+            keywords: rank(
+                posts
+                    .iter()
+                    .flat_map(|post| &post.metadata.opengraph.keywords),
+            ),
+            posts_per_month,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total_posts == 0 {
+            return writeln!(f, "This blog has no posts yet");
+        }
+
+        writeln!(f, "{:<24}{}", "Posts", self.total_posts)?;
+        writeln!(f, "{:<24}{}", "  Published", self.published)?;
+        writeln!(f, "{:<24}{}", "  Drafts", self.drafts)?;
+        writeln!(f, "{:<24}{}", "Words", self.total_words)?;
+        writeln!(
+            f,
+            "{:<24}{:.1} min",
+            "Average reading time", self.average_reading_time
+        )?;
+
+        for (title, ranking) in [("Tags", &self.tags), ("Keywords", &self.keywords)] {
+            writeln!(f, "\n{title}")?;
+            if ranking.is_empty() {
+                writeln!(f, "  none")?;
+            }
+            for (value, count) in ranking.iter().take(TOP_ENTRIES) {
+                writeln!(f, "  {value:<22}{count}")?;
+            }
+        }
+
+        writeln!(f, "\nPosts per month")?;
+        if self.posts_per_month.is_empty() {
+            writeln!(f, "  none")?;
+        }
+        for (month, count) in &self.posts_per_month {
+            writeln!(f, "  {month:<22}{count}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+/// The usage of a tag or keyword across the blog
+pub struct TermUsage {
+    pub term: String,
+    /// The number of posts using the term
+    pub count: usize,
+    /// Whether a single post uses the term, making it a candidate for consolidation
+    pub single_use: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+/// The usage of all the tags and keywords of a blog, most used first
+pub struct Taxonomy {
+    /// The tags, unless restricted to the keywords
+    pub tags: Option<Vec<TermUsage>>,
+    /// The keywords, unless restricted to the tags
+    pub keywords: Option<Vec<TermUsage>>,
+}
+
+/// Returns the usage of the ranked terms.
+fn usages(ranking: Vec<(String, usize)>) -> Vec<TermUsage> {
+    ranking
+        .into_iter()
+        .map(|(term, count)| TermUsage {
+            term,
+            count,
+            single_use: count == 1,
+        })
+        .collect()
+}
+
+impl Taxonomy {
+    /// Computes the usage of the tags and/or keywords of the given posts.
+    pub fn compute(posts: &[Post], tags: bool, keywords: bool) -> Self {
+        Self {
+            tags: tags
+                .then(|| usages(rank(posts.iter().flat_map(|post| &post.metadata.post.tags)))),
+            keywords: keywords.then(|| {
+                usages(rank(
+                    posts
+                        .iter()
+                        .flat_map(|post| &post.metadata.opengraph.keywords),
+                ))
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Taxonomy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sections = [("Tags", &self.tags), ("Keywords", &self.keywords)];
+        for (index, (title, usages)) in sections.into_iter().enumerate() {
+            let Some(usages) = usages else {
+                continue;
+            };
+            if index > 0 && self.tags.is_some() {
+                writeln!(f)?;
+            }
+            writeln!(f, "{title}")?;
+            if usages.is_empty() {
+                writeln!(f, "  none")?;
+            }
+            for usage in usages {
+                let flag = if usage.single_use {
+                    "  (single use)"
+                } else {
+                    ""
+                };
+                writeln!(f, "  {:<22}{}{flag}", usage.term, usage.count)?;
+            }
+        }
+        Ok(())
+    }
+}
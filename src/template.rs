@@ -0,0 +1,101 @@
+/// This module implements the small template engine used to produce the HTML pages.
+/// Templates are plain HTML documents containing `{{name}}` placeholders that get replaced
+/// by the values given at render time. The values are inserted as-is, escaping them is
+/// the responsibility of the caller.
+///
+/// A template loaded from a directory can also compose the partials of the directory:
+/// each `{{> name}}` include is replaced by the content of its `name.html` file.
+use std::fs;
+use std::path::Path;
+
+pub struct Template {
+    source: String,
+}
+
+/// The default template wrapping every generated page
+const PAGE_TEMPLATE: &str = include_str!("templates/page.html");
+
+/// The name of the main template of a template directory
+pub const MAIN_TEMPLATE: &str = "template";
+
+/// Replaces the `{{> name}}` includes of the source by the partials of the directory,
+/// resolving their own includes. The stack holds the templates being resolved, from the
+/// main one, to detect the cycles.
+fn resolve_includes(dir: &Path, source: &str, stack: &mut Vec<String>) -> Result<String, String> {
+    let mut output = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{{>") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+
+        let name = rest[start + 3..start + end].trim();
+        if stack.iter().any(|template| template == name) {
+            return Err(format!(
+                "The templates include each other: {} -> {name}",
+                stack.join(" -> ")
+            ));
+        }
+        stack.push(name.to_string());
+        output.push_str(&resolve_includes(dir, &read_template(dir, name)?, stack)?);
+        stack.pop();
+        rest = &rest[start + end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Reads the `name.html` template of the directory.
+fn read_template(dir: &Path, name: &str) -> Result<String, String> {
+    let path = dir.join(format!("{name}.html"));
+    fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read template {}: {e}", path.display()))
+}
+
+impl Template {
+    /// Creates a template from the given source.
+    pub fn new<S: AsRef<str>>(source: S) -> Self {
+        Self {
+            source: source.as_ref().to_string(),
+        }
+    }
+
+    /// Returns the default page template.
+    pub fn page() -> Self {
+        Self::new(PAGE_TEMPLATE)
+    }
+
+    /// Loads the `template.html` of the directory, composed with the partials it includes.
+    /// Fails if a partial is missing or if the templates include each other.
+    pub fn from_dir(dir: &Path) -> Result<Self, String> {
+        let mut stack = vec![MAIN_TEMPLATE.to_string()];
+        let source = read_template(dir, MAIN_TEMPLATE)?;
+        Ok(Self::new(resolve_includes(dir, &source, &mut stack)?))
+    }
+
+    /// Renders the template, replacing each placeholder with its value.
+    /// Placeholders without a value are replaced by an empty string.
+    pub fn render(&self, values: &[(&str, String)]) -> String {
+        let mut output = String::with_capacity(self.source.len());
+        let mut rest = self.source.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+
+            let name = rest[start + 2..start + end].trim();
+            if let Some((_, value)) = values.iter().find(|(key, _)| *key == name) {
+                output.push_str(value);
+            }
+            rest = &rest[start + end + 2..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+}
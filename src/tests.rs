@@ -1,23 +1,62 @@
-use chrono::{Datelike, Utc};
+use std::path::Path;
 
-use crate::post::Post;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+
+use crate::config::{Config, SlugConfig};
+use crate::post::{Post, PostFilter, TagPosition};
+use crate::utils::{confirm, expand_home, find_posts, normalize_path, parse_date};
+
+mod backup;
+mod build;
+mod export;
+mod feed;
+mod header;
+mod images;
+mod import;
+mod keywords;
+mod links;
+mod lint;
+mod logger;
+mod pages;
+mod preview;
+mod publish;
+mod related;
+mod render;
+mod robots;
+mod search;
+mod spell;
+mod stats;
+mod template;
+mod validate;
+
+/// Creates and saves a post with the given title under the given root directory.
+pub fn fixture_post(root: &Path, title: &str, published: Option<DateTime<Utc>>) -> Post {
+    let mut post = Post::new(title);
+    post.path = root.join(&post.path);
+    post.metadata.post.published_date = published;
+    post.save().expect("Unable to save fixture post");
+    post
+}
 
 #[test]
 pub fn test_add_keyword() {
     let mut post = Post::new("Test post");
 
     // Test adding a keyword
-    let result = post.metadata.opengraph.add_keyword("test".to_string());
+    let result = post.metadata.opengraph.add_keyword("test".to_string(), 0);
     assert!(result.is_ok());
     assert_eq!(post.metadata.opengraph.keywords, vec!["test".to_string()]);
 
     // Test inserting the same keyword again
-    let result = post.metadata.opengraph.add_keyword("test".to_string());
+    let result = post.metadata.opengraph.add_keyword("test".to_string(), 0);
     assert!(result.is_err());
     assert_eq!(post.metadata.opengraph.keywords, vec!["test".to_string()]);
 
     // Test adding another keyword
-    let result = post.metadata.opengraph.add_keyword("another".to_string());
+    let result = post
+        .metadata
+        .opengraph
+        .add_keyword("another".to_string(), 0);
     assert!(result.is_ok());
     assert_eq!(
         post.metadata.opengraph.keywords,
@@ -30,7 +69,7 @@ pub fn test_remove_keyword() {
     let mut post = Post::new("Test post");
 
     // Test adding a keyword
-    let result = post.metadata.opengraph.add_keyword("test".to_string());
+    let result = post.metadata.opengraph.add_keyword("test".to_string(), 0);
     assert!(result.is_ok());
     assert_eq!(post.metadata.opengraph.keywords, vec!["test".to_string()]);
 
@@ -51,17 +90,17 @@ pub fn test_add_tag() {
     let mut post = Post::new("Test post");
 
     // Test adding a keyword
-    let result = post.metadata.post.add_tag("test".to_string());
+    let result = post.metadata.post.add_tag("test".to_string(), 0);
     assert!(result.is_ok());
     assert_eq!(post.metadata.post.tags, vec!["test".to_string()]);
 
     // Test inserting the same keyword again
-    let result = post.metadata.post.add_tag("test".to_string());
+    let result = post.metadata.post.add_tag("test".to_string(), 0);
     assert!(result.is_err());
     assert_eq!(post.metadata.post.tags, vec!["test".to_string()]);
 
     // Test adding another keyword
-    let result = post.metadata.post.add_tag("another".to_string());
+    let result = post.metadata.post.add_tag("another".to_string(), 0);
     assert!(result.is_ok());
     assert_eq!(
         post.metadata.post.tags,
@@ -74,7 +113,7 @@ pub fn test_remove_tag() {
     let mut post = Post::new("Test post");
 
     // Test adding a keyword
-    let result = post.metadata.post.add_tag("test".to_string());
+    let result = post.metadata.post.add_tag("test".to_string(), 0);
     assert!(result.is_ok());
     assert_eq!(post.metadata.post.tags, vec!["test".to_string()]);
 
@@ -90,6 +129,128 @@ pub fn test_remove_tag() {
     assert_eq!(post.metadata.post.tags, expected);
 }
 
+#[test]
+pub fn test_tag_and_keyword_limits() {
+    let mut post = Post::new("Test");
+    assert!(post.metadata.post.add_tag("a".to_string(), 2).is_ok());
+    assert!(post.metadata.post.add_tag("b".to_string(), 2).is_ok());
+    let error = post.metadata.post.add_tag("c".to_string(), 2).unwrap_err();
+    assert!(error.contains("maximum of 2 tags"));
+    assert_eq!(post.metadata.post.tags, ["a", "b"]);
+
+    assert!(post
+        .metadata
+        .opengraph
+        .add_keyword("a".to_string(), 1)
+        .is_ok());
+    assert!(post
+        .metadata
+        .opengraph
+        .add_keyword("b".to_string(), 1)
+        .is_err());
+    assert_eq!(post.metadata.opengraph.keywords, ["a"]);
+
+    // Unlimited by default
+    let config = crate::config::Config::default();
+    for index in 0..50 {
+        assert!(post
+            .metadata
+            .opengraph
+            .add_keyword(index.to_string(), config.posts.max_keywords)
+            .is_ok());
+    }
+}
+
+#[test]
+pub fn test_move_tag() {
+    let mut post = Post::new("Test");
+    post.metadata.post.tags = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+    post.metadata
+        .post
+        .move_tag("c", TagPosition::First)
+        .unwrap();
+    assert_eq!(post.metadata.post.tags, ["c", "a", "b"]);
+    post.metadata
+        .post
+        .move_tag("c", TagPosition::Index(1))
+        .unwrap();
+    assert_eq!(post.metadata.post.tags, ["a", "c", "b"]);
+    post.metadata
+        .post
+        .move_tag("a", "last".parse().unwrap())
+        .unwrap();
+    assert_eq!(post.metadata.post.tags, ["c", "b", "a"]);
+
+    assert!(post
+        .metadata
+        .post
+        .move_tag("a", TagPosition::Index(3))
+        .is_err());
+    assert!(post
+        .metadata
+        .post
+        .move_tag("d", TagPosition::First)
+        .is_err());
+    assert_eq!(post.metadata.post.tags, ["c", "b", "a"]);
+    assert!("second".parse::<TagPosition>().is_err());
+}
+
+#[test]
+pub fn test_authors() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Co-written", None);
+    let metadata = |authors: &str| {
+        format!(
+            "[post]\ntitle = \"Co-written\"\nauthor = {authors}\ntags = []\n\n\
+             [opengraph]\nshort = \"\"\nopengraphimage = \"\"\ndescription = \"\"\nkeywords = []\n"
+        )
+    };
+
+    // A single author is written as a string, several as a list
+    for (written, authors) in [
+        ("\"\"", vec![]),
+        ("\"Jane Doe\"", vec!["Jane Doe"]),
+        ("[\"Jane Doe\", \"John Doe\"]", vec!["Jane Doe", "John Doe"]),
+    ] {
+        std::fs::write(post.path.join("metadata.toml"), metadata(written)).unwrap();
+        let mut loaded = Post::load(&post.path).unwrap();
+        assert_eq!(loaded.metadata.post.authors, authors);
+        loaded.save().unwrap();
+        let saved = std::fs::read_to_string(post.path.join("metadata.toml")).unwrap();
+        assert!(saved.contains(&format!("author = {written}\n")));
+    }
+
+    let info = &mut post.metadata.post;
+    info.authors = vec![];
+    assert!(info.add_author("Jane Doe".to_string()).is_ok());
+    assert!(info.add_author("John Doe".to_string()).is_ok());
+    assert!(info.add_author("Jane Doe".to_string()).is_err());
+    assert!(info.remove_author("Jane Doe").is_ok());
+    assert!(info.remove_author("Jane Doe").is_err());
+    assert_eq!(info.authors, ["John Doe"]);
+}
+
+#[test]
+pub fn test_reading_time() {
+    let mut post = Post::new("Reading time");
+    post.content = "word ".repeat(450);
+
+    assert_eq!(post.reading_time(200), 3);
+    assert_eq!(post.reading_time(450), 1);
+    assert_eq!(post.reading_time(100), 5);
+
+    // The reading speed of the post takes precedence over the configured one
+    post.metadata.post.reading_wpm = Some(150);
+    assert_eq!(post.reading_time(200), 3);
+    post.metadata.post.reading_wpm = Some(1000);
+    assert_eq!(post.reading_time(200), 1);
+
+    // A post always takes at least a minute to read
+    post.content = String::new();
+    assert_eq!(post.reading_time(200), 1);
+}
+
 #[test]
 pub fn test_post_path() {
     let timestamp = Utc::now();
@@ -108,3 +269,455 @@ pub fn test_post_path() {
         .contains(&format!("{:02}", timestamp.month())));
     assert!(post.path.ends_with("test"));
 }
+
+#[test]
+pub fn test_created_date() {
+    let before = Utc::now();
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Created", None);
+
+    let created = post.metadata.post.created_date.unwrap();
+    assert!(created >= before && created <= Utc::now());
+    let loaded = Post::load(&post.path).unwrap();
+    assert_eq!(loaded.metadata.post.created_date, Some(created));
+
+    // Posts created before the field existed still load
+    std::fs::write(
+        post.path.join("metadata.toml"),
+        "[post]\ntitle = \"Created\"\nauthor = \"\"\ntags = []\n\n\
+         [opengraph]\nshort = \"\"\nopengraphimage = \"\"\ndescription = \"\"\nkeywords = []\n",
+    )
+    .unwrap();
+    let loaded = Post::load(&post.path).unwrap();
+    assert_eq!(loaded.metadata.post.created_date, None);
+}
+
+#[test]
+pub fn test_corrupt_metadata_error() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Corrupt", None);
+    std::fs::write(
+        post.path.join("metadata.toml"),
+        "[post]\ntitle = \"Corrupt\nauthor = \"\"\n",
+    )
+    .unwrap();
+
+    let error = Post::load(&post.path).unwrap_err();
+    assert!(error.contains("metadata.toml"));
+    assert!(error.contains("line 2, column 17"));
+}
+
+#[test]
+pub fn test_repair_post() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "My broken post", None);
+    std::fs::write(post.path.join("metadata.toml"), "not = [valid").unwrap();
+
+    let repaired = Post::repair(&post.path).unwrap();
+    assert_eq!(repaired.metadata.post.title, "My broken post");
+    assert_eq!(repaired.content, "# My broken post");
+    assert_eq!(
+        std::fs::read_to_string(post.path.join("metadata.toml.bak")).unwrap(),
+        "not = [valid"
+    );
+    assert_eq!(
+        Post::load(&post.path).unwrap().metadata.post.title,
+        "My broken post"
+    );
+}
+
+#[test]
+pub fn test_post_path_format() {
+    let mut post = Post::new("Daily post");
+    post.metadata.post.created_date = Some(Utc.with_ymd_and_hms(2024, 3, 9, 23, 59, 59).unwrap());
+    let post = post
+        .with_path_format("{year}/{month}/{day}/{slug}", &SlugConfig::default())
+        .unwrap();
+    assert_eq!(post.path, Path::new("2024/03/09/daily-post"));
+
+    let post = Post::new("Flat post")
+        .with_path_format("{slug}", &SlugConfig::default())
+        .unwrap();
+    assert_eq!(post.path, Path::new("flat-post"));
+
+    assert!(Post::new("No slug")
+        .with_path_format("{year}/{month}", &SlugConfig::default())
+        .is_err());
+}
+
+#[test]
+pub fn test_slug_config() {
+    let ascii = SlugConfig::default();
+    assert_eq!(
+        ascii.slugify("Café au lait, 2 sugars"),
+        "cafe-au-lait-2-sugars"
+    );
+
+    let unicode = SlugConfig {
+        unicode: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        unicode.slugify("Café au lait, 2 sugars"),
+        "café-au-lait-2-sugars"
+    );
+    assert_eq!(unicode.slugify("  Ünïcödé -- Straße  "), "ünïcödé-straße");
+
+    let underscores = SlugConfig {
+        separator: '_',
+        ..Default::default()
+    };
+    let post = Post::new("Café au lait")
+        .with_path_format("{slug}", &underscores)
+        .unwrap();
+    assert_eq!(post.path, Path::new("cafe_au_lait"));
+}
+
+#[test]
+pub fn test_delete_post() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "To delete", None);
+
+    assert!(Post::delete(&post.path).is_ok());
+    assert!(!post.path.exists());
+}
+
+#[test]
+pub fn test_delete_refuses_non_post() {
+    let root = tempfile::tempdir().unwrap();
+    let directory = root.path().join("not-a-post");
+    std::fs::create_dir(&directory).unwrap();
+    std::fs::write(directory.join("content.md"), "# Not a post").unwrap();
+
+    assert!(Post::delete(&directory).is_err());
+    assert!(directory.join("content.md").exists());
+}
+
+#[test]
+pub fn test_confirm() {
+    let mut output = vec![];
+    assert!(confirm("yes\n".as_bytes(), &mut output, "Really?").unwrap());
+    assert_eq!(output, b"Really? [y/N] ");
+    assert!(!confirm("\n".as_bytes(), vec![], "Really?").unwrap());
+    assert!(!confirm("nope\n".as_bytes(), vec![], "Really?").unwrap());
+}
+
+#[test]
+pub fn test_prompt_post() {
+    let mut config = Config::default();
+    config.site.author = "Me".to_string();
+
+    let input = "\n\nA post written interactively\nrust, cli , \nterminal\n";
+    let mut output = vec![];
+    let post = Post::prompt(input.as_bytes(), &mut output, Some("Hello"), &config).unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("Title [Hello]: Authors (comma separated) [Me]: Description: "));
+    assert_eq!(post.metadata.post.title, "Hello");
+    assert!(post.path.ends_with("hello"));
+    assert_eq!(post.metadata.post.authors, ["Me"]);
+    assert_eq!(
+        post.metadata.opengraph.description,
+        "A post written interactively"
+    );
+    assert_eq!(post.metadata.post.tags, ["rust", "cli"]);
+    assert_eq!(post.metadata.opengraph.keywords, ["terminal"]);
+
+    assert!(Post::prompt("\n".as_bytes(), vec![], None, &config).is_err());
+}
+
+#[test]
+pub fn test_expand_home() {
+    let home = Path::new("/home/user");
+    assert_eq!(
+        expand_home("~/blog/post", Some(home)),
+        Path::new("/home/user/blog/post")
+    );
+    assert_eq!(expand_home("~", Some(home)), home);
+    // Only a leading `~` designates the home directory
+    assert_eq!(
+        expand_home("~other/post", Some(home)),
+        Path::new("~other/post")
+    );
+    assert_eq!(
+        expand_home("blog/~/post", Some(home)),
+        Path::new("blog/~/post")
+    );
+    assert_eq!(expand_home("~/post", None), Path::new("~/post"));
+}
+
+#[test]
+pub fn test_normalize_path() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Normalized", None);
+
+    let parent = post.path.parent().unwrap();
+    let detour = parent
+        .join("..")
+        .join(parent.file_name().unwrap())
+        .join("normalized");
+    assert_eq!(
+        normalize_path(&detour),
+        std::fs::canonicalize(&post.path).unwrap()
+    );
+
+    let loaded = Post::load(&detour).unwrap();
+    assert!(!loaded.path.to_string_lossy().contains(".."));
+    assert!(loaded.path.is_absolute());
+}
+
+#[test]
+pub fn test_bulk_tags() {
+    let root = tempfile::tempdir().unwrap();
+    let first = fixture_post(root.path(), "First", None);
+    let mut second = fixture_post(root.path(), "Second", None);
+    second.metadata.post.tags = vec!["news".to_string()];
+    second.save().unwrap();
+
+    let paths = find_posts(root.path()).unwrap();
+    let results = Post::update_all(&paths, false, |post| {
+        let info = &mut post.metadata.post;
+        if info.tags.contains(&"news".to_string()) {
+            return vec![];
+        }
+        info.add_tag("news".to_string(), 0)
+            .err()
+            .into_iter()
+            .collect()
+    });
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().any(|(_, result)| *result == Ok(true)));
+    assert!(results.iter().any(|(_, result)| *result == Ok(false)));
+
+    for post in Post::load_all(root.path()).unwrap() {
+        assert_eq!(post.metadata.post.tags, ["news"]);
+    }
+
+    // A failing post does not stop the others
+    let missing = root.path().join("missing");
+    let results = Post::update_all(&[missing, first.path.clone()], false, |post| {
+        post.metadata
+            .post
+            .rename_tag("news", "updates")
+            .err()
+            .into_iter()
+            .collect()
+    });
+    assert!(results[0].1.is_err());
+    assert_eq!(results[1].1, Ok(true));
+    assert_eq!(
+        Post::load(&first.path).unwrap().metadata.post.tags,
+        ["updates"]
+    );
+}
+
+#[test]
+pub fn test_update_all_fail_fast() {
+    let root = tempfile::tempdir().unwrap();
+    // The broken post comes first in the tree
+    let broken = fixture_post(root.path(), "A broken post", None);
+    std::fs::write(broken.path.join("metadata.toml"), "not = [valid").unwrap();
+    fixture_post(root.path(), "B post", None);
+    fixture_post(root.path(), "C post", None);
+
+    let paths = find_posts(root.path()).unwrap();
+    let tag = |post: &mut Post| {
+        post.metadata
+            .post
+            .add_tag("batch".to_string(), 0)
+            .err()
+            .into_iter()
+            .collect()
+    };
+    assert_eq!(Post::update_all(&paths, true, tag).len(), 1);
+    let results = Post::update_all(&paths, false, tag);
+    assert_eq!(results.len(), 3);
+    assert!(results[0].1.is_err());
+    assert!(results[1..].iter().all(|(_, result)| *result == Ok(true)));
+}
+
+#[test]
+pub fn test_create_existing_post() {
+    let root = tempfile::tempdir().unwrap();
+    let draft = fixture_post(root.path(), "Reused title", None);
+
+    let mut post = Post::new("Reused title");
+    post.path = draft.path.clone();
+    post.content = "# Reused title\n\nA new start.".to_string();
+    assert_eq!(
+        post.create(false),
+        Err(format!(
+            "A post already exists at {}, use --force to overwrite it",
+            draft.path.display()
+        ))
+    );
+    assert_eq!(Post::load(&draft.path).unwrap().content, draft.content);
+
+    post.create(true).unwrap();
+    assert_eq!(Post::load(&draft.path).unwrap().content, post.content);
+
+    // An empty directory is not a post
+    let empty = root.path().join("empty");
+    std::fs::create_dir(&empty).unwrap();
+    post.path = empty;
+    post.create(false).unwrap();
+}
+
+#[test]
+pub fn test_content_hash() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Hashed", None);
+    let saved_hash = |post: &Post| {
+        Post::load(&post.path)
+            .unwrap()
+            .metadata
+            .post
+            .content_hash
+            .unwrap()
+    };
+    let hash = saved_hash(&post);
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, post.content_hash());
+
+    // The hash covers the content only, not the metadata holding it
+    let mut post = Post::load(&post.path).unwrap();
+    post.metadata.post.tags.push("rust".to_string());
+    post.save().unwrap();
+    assert_eq!(saved_hash(&post), hash);
+
+    post.content.push_str("\n\nAn edit.");
+    assert_ne!(post.content_hash(), hash);
+    assert_eq!(saved_hash(&post), hash);
+    post.save().unwrap();
+    assert_eq!(saved_hash(&post), post.content_hash());
+}
+
+#[test]
+pub fn test_append_content() {
+    let mut post = Post::new("Notes");
+    post.content = "# Notes\n\nSome text.\n\n```sh\nblog build\n```\n\n".to_string();
+    post.append_content("**Update:** it works.\n");
+    assert_eq!(
+        post.content,
+        "# Notes\n\nSome text.\n\n```sh\nblog build\n```\n\n**Update:** it works.\n"
+    );
+
+    // A heading is not continued either, and a missing trailing newline stays missing
+    post.content = "# Notes".to_string();
+    post.append_content("More.");
+    assert_eq!(post.content, "# Notes\n\nMore.");
+
+    post.content = String::new();
+    post.append_content("First.");
+    assert_eq!(post.content, "First.\n");
+}
+
+#[test]
+pub fn test_prepend_content() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Notes", None);
+    post.content = "# Notes\n\nSome text.\n".to_string();
+    post.prepend_content("> This post is outdated.\n\n");
+    post.save().unwrap();
+    assert_eq!(
+        Post::load(&post.path).unwrap().content,
+        "> This post is outdated.\n\n# Notes\n\nSome text.\n"
+    );
+}
+
+#[test]
+pub fn test_merge_tag() {
+    let root = tempfile::tempdir().unwrap();
+    for (title, tags) in [
+        ("Old", vec!["rust", "programing"]),
+        ("Both", vec!["programing", "programming"]),
+        ("New", vec!["programming"]),
+        ("Other", vec!["rust"]),
+    ] {
+        let mut post = fixture_post(root.path(), title, None);
+        post.metadata.post.tags = tags.into_iter().map(String::from).collect();
+        post.save().unwrap();
+    }
+
+    let mut merged = 0;
+    let paths = crate::utils::find_posts(root.path()).unwrap();
+    let results = Post::update_all(&paths, false, |post| {
+        merged += usize::from(
+            post.metadata
+                .post
+                .merge_tag("programing", "programming")
+                .unwrap(),
+        );
+        vec![]
+    });
+    assert_eq!(merged, 2);
+    assert_eq!(
+        results
+            .iter()
+            .filter(|(_, result)| *result == Ok(true))
+            .count(),
+        2
+    );
+
+    for post in Post::load_all(root.path()).unwrap() {
+        let tags = &post.metadata.post.tags;
+        assert!(!tags.contains(&"programing".to_string()));
+        match post.metadata.post.title.as_str() {
+            "Old" => assert_eq!(tags, &["rust", "programming"]),
+            "Other" => assert_eq!(tags, &["rust"]),
+            _ => assert_eq!(tags, &["programming"]),
+        }
+    }
+}
+
+#[test]
+pub fn test_post_filter() {
+    let root = tempfile::tempdir().unwrap();
+    for (title, month, day) in [("January", 1, 10), ("February", 2, 20), ("March", 3, 5)] {
+        let date = Utc.with_ymd_and_hms(2024, month, day, 12, 0, 0).unwrap();
+        fixture_post(root.path(), title, Some(date));
+    }
+    fixture_post(root.path(), "Draft", None);
+    let filtered = |filter: PostFilter| -> Vec<String> {
+        let mut posts = Post::load_all(root.path()).unwrap();
+        Post::sort_newest_first(&mut posts);
+        filter
+            .apply(posts)
+            .into_iter()
+            .map(|post| post.metadata.post.title)
+            .collect()
+    };
+
+    assert_eq!(
+        filtered(PostFilter::default()),
+        ["March", "February", "January", "Draft"]
+    );
+    let limited = PostFilter {
+        limit: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(filtered(limited), ["March", "February"]);
+
+    // The cutoff day is included, the drafts are left out
+    let since = PostFilter {
+        since: Some(parse_date("2024-02-20").unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(filtered(since), ["March", "February"]);
+
+    let both = PostFilter {
+        limit: Some(1),
+        since: Some(parse_date("2024-01-01T00:00:00Z").unwrap()),
+    };
+    assert_eq!(filtered(both), ["March"]);
+}
+
+#[test]
+pub fn test_parse_date() {
+    assert_eq!(
+        parse_date("2024-02-20").unwrap(),
+        Utc.with_ymd_and_hms(2024, 2, 20, 0, 0, 0).unwrap()
+    );
+    let e = parse_date("20/02/2024").unwrap_err();
+    assert!(e.contains("Invalid date `20/02/2024`"));
+}
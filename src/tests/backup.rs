@@ -0,0 +1,31 @@
+use std::fs;
+use std::path::Path;
+
+use crate::backup::{archive_entries, backup};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_backup() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Backed up", None);
+    fs::write(post.path.join("images/diagram.png"), b"image").unwrap();
+    fs::create_dir_all(post.path.join("dist")).unwrap();
+    fs::write(post.path.join("dist/index.html"), "<html></html>").unwrap();
+
+    let output = root.path().join("backups");
+    let archive = backup(&post, Some(output.clone())).unwrap();
+    assert!(archive.starts_with(&output));
+
+    let entries = archive_entries(&archive).unwrap();
+    for expected in ["content.md", "metadata.toml", "images/diagram.png"] {
+        assert!(entries.contains(&Path::new("backed-up").join(expected)));
+    }
+    assert!(!entries
+        .iter()
+        .any(|entry| entry.starts_with("backed-up/dist")));
+
+    // A truncated archive is reported
+    let content = fs::read(&archive).unwrap();
+    fs::write(&archive, &content[..content.len() / 2]).unwrap();
+    assert!(archive_entries(&archive).is_err());
+}
@@ -0,0 +1,812 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use flate2::read::GzDecoder;
+
+use crate::build::{build_all, BUILD_CACHE};
+use crate::compress::CompressionFormat;
+use crate::config::{Config, HooksConfig};
+use crate::manifest::Manifest;
+use crate::post::{BuildOptions, PlannedWrite, Post, DRAFTS_DIR, SOURCE_SNAPSHOT};
+use crate::render::{CssMode, MathMode, DEFAULT_CSS};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_build_dry_run() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Dry run", None);
+    fs::write(post.path.join("images/picture.png"), b"picture").unwrap();
+    let metadata_before = fs::read_to_string(post.path.join("metadata.toml")).unwrap();
+
+    let mut post = Post::load(&post.path).unwrap();
+    let options = BuildOptions {
+        dry_run: true,
+        ..Default::default()
+    };
+    let output = post.build(&options).unwrap();
+
+    assert!(post.metadata.post.update.is_none());
+    assert!(!post.path.join("dist").exists());
+    let metadata_after = fs::read_to_string(post.path.join("metadata.toml")).unwrap();
+    assert_eq!(metadata_before, metadata_after);
+
+    // The planned writes are returned for the caller to report
+    let dist = post.path.join("dist");
+    assert_eq!(output.planned.len(), 2);
+    assert!(
+        matches!(&output.planned[0], PlannedWrite::Write(index, Some(_)) if *index == dist.join("index.html"))
+    );
+    assert_eq!(
+        output.planned[1],
+        PlannedWrite::Copy(
+            post.path.join("images/picture.png"),
+            dist.join("images/picture.png")
+        )
+    );
+    assert_eq!(
+        output.planned[1].to_string(),
+        format!(
+            "Would copy {} to {}",
+            post.path.join("images/picture.png").display(),
+            dist.join("images/picture.png").display()
+        )
+    );
+
+    // So are those of all the posts
+    let summary = build_all(
+        root.path(),
+        &BuildOptions {
+            include_drafts: true,
+            ..options
+        },
+    )
+    .unwrap();
+    assert_eq!(summary.planned.len(), 2);
+    assert!(summary.planned[0].to_string().starts_with("Would write "));
+    let output = post.build(&BuildOptions::default()).unwrap();
+    assert!(output.planned.is_empty());
+}
+
+#[test]
+pub fn test_build() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Built post", None);
+    fs::write(post.path.join("images/picture.png"), b"picture").unwrap();
+
+    post.build(&BuildOptions::default()).unwrap();
+
+    let index = fs::read_to_string(post.path.join("dist/index.html")).unwrap();
+    assert!(index.contains(r#"<h1 id="built-post">Built post</h1>"#));
+    assert!(post.path.join("dist/images/picture.png").is_file());
+    assert!(Post::load(&post.path)
+        .unwrap()
+        .metadata
+        .post
+        .update
+        .is_some());
+}
+
+#[test]
+pub fn test_build_options_from_config() {
+    let mut config = Config::default();
+    config.site.base_url = "https://example.com".to_string();
+    config.site.favicon = "favicon.png".to_string();
+    config.markdown.footnotes = false;
+    config.hooks.post_build = Some("true".to_string());
+
+    let options = BuildOptions::from_config(&config);
+    assert_eq!(options.base_url, "https://example.com");
+    assert_eq!(options.favicon, Some(PathBuf::from("favicon.png")));
+    assert!(!options.markdown.footnotes);
+    assert_eq!(options.hooks, config.hooks);
+
+    assert_eq!(BuildOptions::from_config(&Config::default()).favicon, None);
+}
+
+#[test]
+pub fn test_build_output() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Build output", None);
+    fs::create_dir_all(post.path.join("images/gallery")).unwrap();
+    fs::write(post.path.join("images/gallery/picture.png"), b"picture").unwrap();
+
+    let options = BuildOptions {
+        css_mode: CssMode::Link,
+        ..Default::default()
+    };
+    let output = post.build(&options).unwrap();
+    let dist = post.path.join("dist/");
+    assert_eq!(output.directory, dist);
+    assert_eq!(output.index, dist.join("index.html"));
+    assert!(output.index.is_file());
+    assert_eq!(
+        output.assets,
+        vec![
+            dist.join("images/gallery/picture.png"),
+            dist.join("style.css")
+        ]
+    );
+    assert!(output.assets.iter().all(|asset| asset.is_file()));
+}
+
+#[test]
+pub fn test_build_without_images() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Prose only", None);
+    fs::write(post.path.join("images/picture.png"), b"picture").unwrap();
+    post.content
+        .push_str("\n\n![A picture](images/picture.png)\n");
+
+    let options = BuildOptions {
+        no_images: true,
+        ..Default::default()
+    };
+    let output = post.build(&options).unwrap();
+    assert!(output.assets.is_empty());
+    let index = fs::read_to_string(&output.index).unwrap();
+    assert!(index.contains(r#"<img src="images/picture.png" alt="A picture" />"#));
+    assert!(!output.directory.join("images").exists());
+}
+
+#[test]
+pub fn test_failed_build_keeps_update() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Failing build", None);
+    // A file in place of the output directory makes the build fail
+    fs::write(post.path.join("dist"), b"").unwrap();
+
+    let mut post = Post::load(&post.path).unwrap();
+    assert!(post.build(&BuildOptions::default()).is_err());
+    assert!(Post::load(&post.path)
+        .unwrap()
+        .metadata
+        .post
+        .update
+        .is_none());
+}
+
+#[test]
+pub fn test_build_hooks() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Hooked build", None);
+    let log = root.path().join("hooks.log");
+    let hook = |stage: &str| {
+        Some(format!(
+            "echo \"{stage} $BLOG_POST_PATH $BLOG_OUTPUT_PATH\" >> {}",
+            log.display()
+        ))
+    };
+    let options = BuildOptions {
+        hooks: HooksConfig {
+            pre_build: hook("pre"),
+            post_build: hook("post"),
+        },
+        ..Default::default()
+    };
+
+    let mut post = Post::load(&post.path).unwrap();
+    let output = post.build(&options).unwrap();
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        format!(
+            "pre {0} {1}\npost {0} {1}\n",
+            post.path.display(),
+            output.directory.display()
+        )
+    );
+
+    // A failing hook fails the build, unless its errors are ignored
+    let failing = BuildOptions {
+        hooks: HooksConfig {
+            pre_build: Some("exit 3".to_string()),
+            post_build: None,
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        post.build(&failing).err().as_deref(),
+        Some("The pre-build hook `exit 3` failed: exit status: 3")
+    );
+    assert!(post
+        .build(&BuildOptions {
+            ignore_hook_errors: true,
+            ..failing
+        })
+        .is_ok());
+}
+
+#[test]
+pub fn test_strict_build() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Strict build", None);
+    fs::create_dir_all(post.path.join("images/header")).unwrap();
+    fs::write(post.path.join("images/header/header.jpg"), b"header").unwrap();
+
+    // A missing description is only a warning by default
+    post.build(&BuildOptions::default()).unwrap();
+
+    let options = BuildOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let e = post.build(&options).unwrap_err();
+    assert!(e.contains("The post has no description"));
+
+    post.metadata.opengraph.description = "A described post".to_string();
+    post.build(&options).unwrap();
+
+    // So is a missing image
+    post.content = "# Strict build\n\n![A cat](images/cat.png)\n".to_string();
+    let e = post.build(&options).unwrap_err();
+    assert!(e.contains("Image `images/cat.png` does not exist"));
+
+    // The content is checked the way it is rendered: an image inside math is only math
+    post.content = "# Strict build\n\n$$\n![A cat](images/cat.png)\n$$\n".to_string();
+    assert!(post.build(&options).is_err());
+    post.build(&BuildOptions {
+        math: Some(MathMode::Client),
+        ..options
+    })
+    .unwrap();
+}
+
+#[test]
+pub fn test_build_output_directory() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Custom output", Some(Utc::now()));
+    fs::write(post.path.join("images/picture.png"), b"picture").unwrap();
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        root: Some(root.path().to_path_buf()),
+        ..Default::default()
+    };
+    post.build(&options).unwrap();
+
+    let built = output
+        .path()
+        .join(post.path.strip_prefix(root.path()).unwrap());
+    assert_eq!(post.output_path(&options), built);
+    assert!(built.join("index.html").is_file());
+    assert!(built.join("images/picture.png").is_file());
+    assert!(!post.path.join("dist").exists());
+}
+
+#[test]
+pub fn test_build_all() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let published = Some(Utc::now());
+    let first = fixture_post(root.path(), "First post", published);
+    let second = fixture_post(root.path(), "Second post", published);
+    let broken = fixture_post(root.path(), "Broken post", published);
+    fs::write(broken.path.join("metadata.toml"), "not = [valid").unwrap();
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        ..Default::default()
+    };
+    let summary = build_all(root.path(), &options).unwrap();
+
+    assert_eq!(summary.succeeded.len(), 2);
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].0, broken.path);
+    for post in [first, second] {
+        let relative = post.path.strip_prefix(root.path()).unwrap();
+        assert!(output.path().join(relative).join("index.html").is_file());
+    }
+    assert!(summary
+        .to_string()
+        .ends_with("2 posts built, 0 skipped, 1 failed"));
+}
+
+#[test]
+pub fn test_build_all_unreadable_post() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let broken = fixture_post(root.path(), "No content", Some(Utc::now()));
+    fs::remove_file(broken.path.join("content.md")).unwrap();
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        ..Default::default()
+    };
+    let summary = build_all(root.path(), &options).unwrap();
+    assert!(summary.skipped.is_empty());
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].0, broken.path);
+    assert!(summary
+        .to_string()
+        .ends_with("0 posts built, 0 skipped, 1 failed"));
+}
+
+#[test]
+pub fn test_build_all_unhashable_post() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    // The content of the first post disappears once it is built, failing its hash
+    fixture_post(root.path(), "A vanishing post", Some(Utc::now()));
+    fixture_post(root.path(), "B kept post", Some(Utc::now()));
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        hooks: HooksConfig {
+            post_build: Some(
+                "case \"$BLOG_POST_PATH\" in *vanishing*) rm \"$BLOG_POST_PATH/content.md\";; esac"
+                    .to_string(),
+            ),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let summary = build_all(root.path(), &options).unwrap();
+    assert_eq!(summary.succeeded.len(), 2);
+    assert!(summary.failed.is_empty());
+    // The cache is still written, without the post that could not be hashed
+    let cache = fs::read_to_string(output.path().join(BUILD_CACHE)).unwrap();
+    assert!(cache.contains("kept-post"));
+    assert!(!cache.contains("vanishing-post"));
+}
+
+#[test]
+pub fn test_build_all_neighbors() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let day = |day| Some(Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap());
+    let oldest = fixture_post(root.path(), "Oldest post", day(1));
+    let middle = fixture_post(root.path(), "Middle post", day(2));
+    let newest = fixture_post(root.path(), "Newest post", day(3));
+    fixture_post(root.path(), "Draft post", None);
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        base_url: "https://example.com".to_string(),
+        ..Default::default()
+    };
+    build_all(root.path(), &options).unwrap();
+    let page = |post: &Post| {
+        let relative = post.path.strip_prefix(root.path()).unwrap();
+        fs::read_to_string(output.path().join(relative).join("index.html")).unwrap()
+    };
+    let url = |post: &Post| post.url_with_base(&options.base_url, root.path());
+
+    let middle_page = page(&middle);
+    assert!(middle_page.contains(&format!(
+        "<a rel=\"prev\" href=\"{}\">Previous: Oldest post</a>",
+        url(&oldest)
+    )));
+    assert!(middle_page.contains(&format!(
+        "<a rel=\"next\" href=\"{}\">Next: Newest post</a>",
+        url(&newest)
+    )));
+    assert!(!page(&oldest).contains("rel=\"prev\""));
+    assert!(page(&oldest).contains("Next: Middle post"));
+    assert!(!page(&newest).contains("rel=\"next\""));
+    assert!(page(&newest).contains("Previous: Middle post"));
+
+    // Publishing a newer post rebuilds the one it follows, to link to it
+    let latest = fixture_post(root.path(), "Latest post", day(4));
+    let summary = build_all(root.path(), &options).unwrap();
+    assert_eq!(
+        summary.succeeded,
+        vec![latest.path.clone(), newest.path.clone()]
+    );
+    assert!(page(&newest).contains("Next: Latest post"));
+}
+
+#[test]
+pub fn test_build_all_relative_root() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let day = |day| Some(Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap());
+    let older = fixture_post(root.path(), "Older post", day(1));
+    let newer = fixture_post(root.path(), "Newer post", day(2));
+
+    // The same root, relative to the current directory
+    let current = std::env::current_dir().unwrap();
+    let relative: PathBuf = current
+        .ancestors()
+        .skip(1)
+        .map(|_| Path::new(".."))
+        .chain(root.path().strip_prefix("/"))
+        .collect();
+    assert!(relative.is_relative());
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        ..Default::default()
+    };
+    build_all(&relative, &options).unwrap();
+    let page = |post: &Post| {
+        let relative = post.path.strip_prefix(root.path()).unwrap();
+        fs::read_to_string(output.path().join(relative).join("index.html")).unwrap()
+    };
+    assert!(page(&older).contains("Next: Newer post"));
+    assert!(page(&newer).contains("Previous: Older post"));
+}
+
+#[test]
+pub fn test_build_all_fail_fast() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let published = Some(Utc::now());
+    // The broken post comes first in the tree
+    let broken = fixture_post(root.path(), "A broken post", published);
+    fs::write(broken.path.join("metadata.toml"), "not = [valid").unwrap();
+    fixture_post(root.path(), "B post", published);
+    fixture_post(root.path(), "C post", published);
+
+    let build = |fail_fast| {
+        let options = BuildOptions {
+            output: Some(output.path().to_path_buf()),
+            force: true,
+            fail_fast,
+            ..Default::default()
+        };
+        build_all(root.path(), &options).unwrap()
+    };
+    let kept_going = build(false);
+    assert_eq!(
+        (kept_going.succeeded.len(), kept_going.failed.len()),
+        (2, 1)
+    );
+    let failed_fast = build(true);
+    assert_eq!(
+        (failed_fast.succeeded.len(), failed_fast.failed.len()),
+        (0, 1)
+    );
+    assert_eq!(failed_fast.failed[0].0, broken.path);
+}
+
+#[test]
+pub fn test_build_drafts() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let published = fixture_post(root.path(), "Published post", Some(Utc::now()));
+    let draft = fixture_post(root.path(), "Draft post", None);
+    let relative = |post: &Post| post.path.strip_prefix(root.path()).unwrap().to_path_buf();
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        ..Default::default()
+    };
+    let summary = build_all(root.path(), &options).unwrap();
+    assert_eq!(summary.succeeded, vec![published.path.clone()]);
+    assert_eq!(summary.skipped, vec![draft.path.clone()]);
+    assert!(!output.path().join(DRAFTS_DIR).exists());
+
+    let options = BuildOptions {
+        include_drafts: true,
+        force: true,
+        ..options
+    };
+    let summary = build_all(root.path(), &options).unwrap();
+    assert_eq!(summary.succeeded.len(), 2);
+    assert!(output
+        .path()
+        .join(relative(&published))
+        .join("index.html")
+        .is_file());
+    assert!(output
+        .path()
+        .join(DRAFTS_DIR)
+        .join(relative(&draft))
+        .join("index.html")
+        .is_file());
+    assert!(!output.path().join(relative(&draft)).exists());
+
+    // The drafts directory can be moved out of the output
+    let drafts = tempfile::tempdir().unwrap();
+    let mut draft = Post::load(&draft.path).unwrap();
+    let built = draft
+        .build(&BuildOptions {
+            drafts_dir: Some(drafts.path().to_path_buf()),
+            root: Some(root.path().to_path_buf()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        built.index,
+        drafts.path().join(relative(&draft)).join("index.html")
+    );
+    assert!(built.index.is_file());
+}
+
+#[test]
+pub fn test_incremental_build() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let published = Some(Utc::now());
+    fixture_post(root.path(), "Unchanged post", published);
+    let mut modified = fixture_post(root.path(), "Modified post", published);
+
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        ..Default::default()
+    };
+    let summary = build_all(root.path(), &options).unwrap();
+    assert_eq!(summary.succeeded.len(), 2);
+    assert!(output.path().join(BUILD_CACHE).is_file());
+
+    modified = Post::load(&modified.path).unwrap();
+    modified.content.push_str("\n\nA new paragraph");
+    modified.save().unwrap();
+
+    let summary = build_all(root.path(), &options).unwrap();
+    assert_eq!(summary.succeeded, vec![modified.path.clone()]);
+    assert_eq!(summary.skipped.len(), 1);
+
+    let options = BuildOptions {
+        force: true,
+        ..options
+    };
+    let summary = build_all(root.path(), &options).unwrap();
+    assert_eq!(summary.succeeded.len(), 2);
+    assert!(summary.skipped.is_empty());
+}
+
+#[test]
+pub fn test_responsive_header() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Responsive header", None);
+    fs::create_dir_all(post.path.join("images/header")).unwrap();
+    image::RgbImage::new(1000, 500)
+        .save(post.path.join("images/header/header.jpg"))
+        .unwrap();
+
+    let options = BuildOptions {
+        responsive_header: true,
+        ..Default::default()
+    };
+    post.build(&options).unwrap();
+
+    let header = post.path.join("dist/images/header");
+    assert!(header.join("header-480.jpg").is_file());
+    assert!(header.join("header-960.jpg").is_file());
+    // No variant larger than the source is generated
+    assert!(!header.join("header-1600.jpg").exists());
+    assert_eq!(
+        image::image_dimensions(header.join("header-480.jpg")).unwrap(),
+        (480, 240)
+    );
+
+    let index = fs::read_to_string(post.path.join("dist/index.html")).unwrap();
+    assert!(index.contains(
+        r#"srcset="images/header/header-480.jpg 480w, images/header/header-960.jpg 960w, images/header/header.jpg 1000w" sizes="100vw""#
+    ));
+}
+
+#[test]
+pub fn test_build_all_related_posts() {
+    let root = tempfile::tempdir().unwrap();
+    let day = |day| Some(Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap());
+    let mut posts = vec![];
+    for (date, title, tag) in [
+        (1, "Rust one", "rust"),
+        (2, "Rust two", "rust"),
+        (3, "Other", "c"),
+    ] {
+        let mut post = fixture_post(root.path(), title, day(date));
+        post.metadata.post.tags = vec![tag.to_string()];
+        post.save().unwrap();
+        posts.push(post);
+    }
+
+    let options = BuildOptions {
+        related_count: 3,
+        base_url: "https://example.com".to_string(),
+        ..Default::default()
+    };
+    build_all(root.path(), &options).unwrap();
+
+    let built = Post::load_all(root.path())
+        .unwrap()
+        .into_iter()
+        .find(|post| post.metadata.post.title == "Rust one")
+        .unwrap();
+    let page = fs::read_to_string(
+        root.path()
+            .join("dist")
+            .join(built.path.strip_prefix(root.path()).unwrap())
+            .join("index.html"),
+    )
+    .unwrap();
+    assert!(page.contains("<aside class=\"related-posts\">"));
+    assert!(page.contains("rust-two/\">Rust two</a>"));
+    assert!(!page.contains("other/\">Other</a>"));
+
+    // Retagging another post rebuilds the unchanged posts it becomes related to
+    let mut other = Post::load(&posts[2].path).unwrap();
+    other.metadata.post.tags = vec!["rust".to_string()];
+    other.save().unwrap();
+    let summary = build_all(root.path(), &options).unwrap();
+    assert!(summary.succeeded.contains(&built.path));
+    let page = fs::read_to_string(
+        root.path()
+            .join("dist")
+            .join(built.path.strip_prefix(root.path()).unwrap())
+            .join("index.html"),
+    )
+    .unwrap();
+    assert!(page.contains("other/\">Other</a>"));
+}
+
+#[test]
+pub fn test_build_manifest() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Manifest", Some(Utc::now()));
+    fs::write(post.path.join("images/picture.png"), b"picture").unwrap();
+    let options = BuildOptions::default();
+    post.build(&options).unwrap();
+
+    let manifest = Manifest::post(&post, &options, Duration::from_millis(5)).unwrap();
+    let path = root.path().join("manifest.json");
+    manifest.write(&path).unwrap();
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let files = manifest["posts"][0]["files"].as_array().unwrap();
+    let index = fs::read(post.path.join("dist/index.html")).unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0]["path"], "images/picture.png");
+    assert_eq!(files[0]["size"], 7);
+    assert_eq!(files[1]["path"], "index.html");
+    assert_eq!(files[1]["size"], index.len());
+    assert_eq!(files[1]["sha256"].as_str().unwrap().len(), 64);
+    assert_eq!(manifest["options"]["lazy_images"], false);
+    assert_eq!(manifest["duration"], 0.005);
+
+    // Building all the posts lists each of them
+    fixture_post(root.path(), "Other manifest", Some(Utc::now()));
+    let summary = build_all(root.path(), &options).unwrap();
+    let manifest = Manifest::all(&summary, root.path(), &options, Duration::ZERO).unwrap();
+    assert_eq!(manifest.posts.len(), 2);
+    assert!(manifest.posts.iter().all(|post| post
+        .files
+        .iter()
+        .any(|file| file.path.ends_with("index.html"))));
+}
+
+#[test]
+pub fn test_compressed_build() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Compressed", None);
+    fs::write(post.path.join("images/picture.png"), vec![0; 1024]).unwrap();
+
+    let options = BuildOptions {
+        compress: Some(CompressionFormat::Both),
+        ..Default::default()
+    };
+    post.build(&options).unwrap();
+
+    let dist = post.path.join("dist");
+    let index = fs::read_to_string(dist.join("index.html")).unwrap();
+    let mut gzipped = String::new();
+    GzDecoder::new(fs::File::open(dist.join("index.html.gz")).unwrap())
+        .read_to_string(&mut gzipped)
+        .unwrap();
+    assert_eq!(gzipped, index);
+    let mut brotlied = String::new();
+    brotli::Decompressor::new(fs::File::open(dist.join("index.html.br")).unwrap(), 4096)
+        .read_to_string(&mut brotlied)
+        .unwrap();
+    assert_eq!(brotlied, index);
+
+    // Images are not compressed
+    assert!(!dist.join("images/picture.png.gz").exists());
+    assert!(!dist.join("images/picture.png.br").exists());
+}
+
+#[test]
+pub fn test_build_stylesheet() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Styled", None);
+    let index = |post: &Post| fs::read_to_string(post.path.join("dist/index.html")).unwrap();
+
+    // The default theme is inlined when no stylesheet is given
+    post.build(&BuildOptions::default()).unwrap();
+    assert!(index(&post).contains(&format!("<style>\n{}", DEFAULT_CSS.trim_end())));
+
+    let css = root.path().join("theme.css");
+    fs::write(&css, "body { color: rebeccapurple; }\n").unwrap();
+    let options = BuildOptions {
+        css: Some(css),
+        ..Default::default()
+    };
+    post.build(&options).unwrap();
+    assert!(index(&post).contains("<style>\nbody { color: rebeccapurple; }\n  </style>"));
+    assert!(!post.path.join("dist/style.css").exists());
+
+    post.build(&BuildOptions {
+        css_mode: CssMode::Link,
+        ..options
+    })
+    .unwrap();
+    assert!(index(&post).contains(r#"<link rel="stylesheet" href="style.css">"#));
+    assert!(!index(&post).contains("<style>"));
+    assert_eq!(
+        fs::read_to_string(post.path.join("dist/style.css")).unwrap(),
+        "body { color: rebeccapurple; }\n"
+    );
+}
+
+#[test]
+pub fn test_build_favicon() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Favicon", None);
+    let index = |post: &Post| fs::read_to_string(post.path.join("dist/index.html")).unwrap();
+
+    // Without a favicon, no link is added
+    post.build(&BuildOptions::default()).unwrap();
+    assert!(!index(&post).contains(r#"rel="icon""#));
+
+    let favicon = root.path().join("icon.png");
+    image::RgbImage::new(64, 64).save(&favicon).unwrap();
+    let options = BuildOptions {
+        favicon: Some(favicon),
+        resize_favicon: true,
+        ..Default::default()
+    };
+    post.build(&options).unwrap();
+
+    let dist = post.path.join("dist");
+    assert!(dist.join("favicon.png").is_file());
+    assert_eq!(
+        image::image_dimensions(dist.join("favicon-32.png")).unwrap(),
+        (32, 32)
+    );
+    assert_eq!(
+        image::image_dimensions(dist.join("favicon-16.png")).unwrap(),
+        (16, 16)
+    );
+    let index = index(&post);
+    assert!(index.contains(r#"<link rel="icon" href="favicon.png">"#));
+    assert!(
+        index.contains(r#"<link rel="icon" type="image/png" sizes="32x32" href="favicon-32.png">"#)
+    );
+}
+
+#[test]
+pub fn test_diff_since_build() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Diffed", None);
+    let options = BuildOptions::default();
+    assert_eq!(post.diff_since_build(&options).unwrap(), None);
+
+    post.content = "# Diffed\n\nFirst paragraph.\n".to_string();
+    post.build(&options).unwrap();
+    assert_eq!(
+        post.diff_since_build(&options).unwrap(),
+        Some(String::new())
+    );
+
+    // The snapshot is kept with the output, out of the source of the post
+    assert!(post.path.join("dist").join(SOURCE_SNAPSHOT).is_file());
+    assert!(!post.path.join(SOURCE_SNAPSHOT).exists());
+    let output = tempfile::tempdir().unwrap();
+    let custom = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        root: Some(root.path().to_path_buf()),
+        ..Default::default()
+    };
+    assert_eq!(post.diff_since_build(&custom).unwrap(), None);
+    post.build(&custom).unwrap();
+    assert_eq!(post.diff_since_build(&custom).unwrap(), Some(String::new()));
+    let snapshot = post.output_path(&custom).join(SOURCE_SNAPSHOT);
+    assert!(snapshot.starts_with(output.path()) && snapshot.is_file());
+
+    post.content = "# Diffed\n\nSecond paragraph.\n".to_string();
+    post.save().unwrap();
+    let diff = Post::load(&post.path)
+        .unwrap()
+        .diff_since_build(&options)
+        .unwrap()
+        .unwrap();
+    assert!(diff.starts_with("--- last build\n+++ content.md\n"));
+    assert!(diff.contains("-First paragraph.\n"));
+    assert!(diff.contains("+Second paragraph.\n"));
+}
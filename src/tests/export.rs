@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::Path;
+
+use crate::export::{to_pdf, to_plain_text, CommandConverter, PdfConverter, PdfTool};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_plain_text_headings() {
+    let text = to_plain_text("# A *title*\n\nSome text.\n\n## Section").unwrap();
+    assert_eq!(text, "A TITLE\n\nSome text.\n\nSECTION\n");
+}
+
+#[test]
+pub fn test_plain_text_links() {
+    let text = to_plain_text(
+        "Read [the docs](https://example.com/docs) or <https://example.com>.\n\n* first\n* **second**",
+    )
+    .unwrap();
+    assert_eq!(
+        text,
+        "Read the docs (https://example.com/docs) or https://example.com.\n\n- first\n- second\n"
+    );
+}
+
+#[test]
+pub fn test_plain_text_code() {
+    let text = to_plain_text(
+        "Code:\n\n```rust\nfn main() {\n    println!(\"# not a title\");\n}\n```\n\nInline `code`.",
+    )
+    .unwrap();
+    assert_eq!(
+        text,
+        "Code:\n\n    fn main() {\n        println!(\"# not a title\");\n    }\n\nInline code.\n"
+    );
+}
+
+/// A converter copying the HTML page instead of converting it
+struct StubConverter;
+
+impl PdfConverter for StubConverter {
+    fn convert(&self, html: &Path, pdf: &Path) -> Result<(), String> {
+        fs::copy(html, pdf).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+#[test]
+pub fn test_pdf_export() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Printable post", None);
+
+    let pdf = to_pdf(&post, &StubConverter, None).unwrap();
+    assert_eq!(pdf, post.path.join("export.pdf"));
+    assert!(fs::read_to_string(&pdf)
+        .unwrap()
+        .contains(r#"<h1 id="printable-post">Printable post</h1>"#));
+    // The intermediate page is cleaned up
+    assert!(!post.path.join(".export.html").exists());
+
+    let custom = root.path().join("custom.pdf");
+    assert_eq!(
+        to_pdf(&post, &StubConverter, Some(custom.clone())).unwrap(),
+        custom
+    );
+    assert!(custom.is_file());
+}
+
+#[test]
+pub fn test_pdf_missing_tool() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Printable post", None);
+    let converter = CommandConverter::new(PdfTool::Chromium, Some("./no-such-browser".to_string()));
+
+    let error = to_pdf(&post, &converter, None).unwrap_err();
+    assert!(error.contains("`./no-such-browser` was not found"));
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::config::Config;
+use crate::feed::{atom, json};
+use crate::post::Post;
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_atom_feed() {
+    let root = tempfile::tempdir().unwrap();
+    let published = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let mut post = fixture_post(root.path(), "Published post", Some(published));
+    post.metadata.post.update = Some(Utc.with_ymd_and_hms(2024, 3, 5, 8, 30, 0).unwrap());
+    post.metadata.opengraph.description = "A <short> description".to_string();
+    post.save().unwrap();
+    fixture_post(root.path(), "Draft post", None);
+
+    let mut config = Config::default();
+    config.site.title = "My blog".to_string();
+    config.site.base_url = "https://example.com".to_string();
+
+    let posts = Post::load_published(root.path()).unwrap();
+    assert_eq!(posts.len(), 1);
+
+    let feed = atom(&posts, root.path(), &config);
+    assert!(feed.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+    assert_eq!(feed.matches("<entry>").count(), 1);
+    assert!(feed.contains("<title>Published post</title>"));
+    assert!(feed.contains("<summary>A &lt;short&gt; description</summary>"));
+    assert!(!feed.contains("Draft post"));
+
+    let url = format!(
+        "{}/",
+        post.path.strip_prefix(root.path()).unwrap().display()
+    );
+    assert!(feed.contains(&format!("<id>https://example.com/{url}</id>")));
+
+    // Every timestamp must be a valid RFC-3339 date
+    for element in ["updated", "published"] {
+        let open = format!("<{element}>");
+        let close = format!("</{element}>");
+        for chunk in feed.split(&open).skip(1) {
+            let value = chunk.split(&close).next().unwrap();
+            assert!(DateTime::parse_from_rfc3339(value).is_ok());
+        }
+    }
+    assert!(feed.contains("<published>2024-03-01T12:00:00+00:00</published>"));
+    assert!(feed.contains("<updated>2024-03-05T08:30:00+00:00</updated>"));
+}
+
+#[test]
+pub fn test_json_feed() {
+    let root = tempfile::tempdir().unwrap();
+    let published = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let mut post = fixture_post(root.path(), "First post", Some(published));
+    post.metadata.post.tags = vec!["rust".to_string()];
+    post.save().unwrap();
+    fixture_post(root.path(), "Second post", Some(published));
+    fixture_post(root.path(), "Draft post", None);
+
+    let posts = Post::load_published(root.path()).unwrap();
+    let feed = json(&posts, root.path(), &Config::default()).unwrap();
+
+    let feed: serde_json::Value = serde_json::from_str(&feed).unwrap();
+    assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+
+    let items = feed["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    let first = items
+        .iter()
+        .find(|item| item["title"] == "First post")
+        .unwrap();
+    assert_eq!(first["content_html"], "<h1>First post</h1>");
+    assert_eq!(first["tags"], serde_json::json!(["rust"]));
+    assert_eq!(first["date_published"], "2024-03-01T12:00:00+00:00");
+}
+
+#[test]
+pub fn test_json_feed_absolute_links() {
+    let root = tempfile::tempdir().unwrap();
+    let published = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let mut post = fixture_post(root.path(), "With links", Some(published));
+    post.content = "![Diagram](images/diagram.png) [Tags](/tags/)\n".to_string();
+    post.save().unwrap();
+
+    let mut config = Config::default();
+    config.site.base_url = "https://example.com/blog".to_string();
+    let posts = Post::load_published(root.path()).unwrap();
+    let feed = json(&posts, root.path(), &config).unwrap();
+
+    let feed: serde_json::Value = serde_json::from_str(&feed).unwrap();
+    let item = &feed["items"][0];
+    let content = item["content_html"].as_str().unwrap();
+    let url = item["url"].as_str().unwrap();
+    assert!(content.contains(&format!("src=\"{url}images/diagram.png\"")));
+    assert!(content.contains("href=\"https://example.com/blog/tags/\""));
+}
@@ -0,0 +1,745 @@
+use std::fs;
+
+use mockito::Matcher;
+
+use crate::config::{Config, ImageProviderKind};
+use crate::header::{
+    candidates_listing, fetch_candidates, progress_bar, providers, ApiKeys, HeaderCandidate,
+    HeaderCredit, HeaderSearch, ImageProvider, PexelProvider, Picture, UnsplashProvider,
+};
+use crate::imaging::ansi_thumbnail;
+use crate::post::{Metadata, Post};
+use crate::tests::fixture_post;
+use crate::utils::{load_env, read_env_file};
+
+/// Returns the content of the metadata file of a candidate picture from pexel.
+fn candidate_toml(photographer: &str, width: usize) -> String {
+    format!(
+        r#"width = {width}
+height = 600
+url = "https://www.pexels.com/photo/{photographer}/"
+photographer = "{photographer}"
+photographer_url = "https://www.pexels.com/@{photographer}"
+alt = "A picture by {photographer}"
+
+[src]
+landscape = "https://images.pexels.com/photos/{photographer}.jpg"
+"#
+    )
+}
+
+#[test]
+pub fn test_list_header_candidates() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "With header", None);
+    let candidates = Metadata::header_path(&post.path).join("candidates");
+    fs::create_dir_all(&candidates).unwrap();
+    for (index, photographer, width) in [(10, "carla", 1200), (2, "bob", 1000), (1, "alice", 800)] {
+        fs::write(
+            candidates.join(format!("header_{index}.toml")),
+            candidate_toml(photographer, width),
+        )
+        .unwrap();
+        fs::write(candidates.join(format!("header_{index}.jpg")), b"image").unwrap();
+    }
+
+    let listed = Metadata::list_header_candidates(&post.path).unwrap();
+    let expected = [(1, "alice", 800), (2, "bob", 1000), (10, "carla", 1200)]
+        .into_iter()
+        .map(|(index, photographer, width)| HeaderCandidate {
+            index,
+            photographer: photographer.to_string(),
+            photographer_url: format!("https://www.pexels.com/@{photographer}"),
+            url: format!("https://www.pexels.com/photo/{photographer}/"),
+            alt: format!("A picture by {photographer}"),
+            width,
+            height: 600,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(listed, expected);
+    assert_eq!(
+        listed[0].to_string(),
+        "1 - Picture by alice - https://www.pexels.com/photo/alice/ `A picture by alice` (800x600)"
+    );
+}
+
+#[test]
+pub fn test_candidates_listing() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Listing", None);
+    let candidates = Metadata::header_path(&post.path).join("candidates");
+    fs::create_dir_all(&candidates).unwrap();
+    for (index, photographer) in [(2, "bob"), (1, "alice")] {
+        fs::write(
+            candidates.join(format!("header_{index}.toml")),
+            candidate_toml(photographer, 800),
+        )
+        .unwrap();
+        image::RgbImage::from_pixel(8, 4, image::Rgb([255, 0, 0]))
+            .save(Metadata::candidate_image(&post.path, index))
+            .unwrap();
+    }
+    let listed = Metadata::list_header_candidates(&post.path).unwrap();
+
+    // Without thumbnails, the listing is the text of the candidates
+    let text = candidates_listing(&listed, None);
+    assert_eq!(
+        text.lines().collect::<Vec<_>>(),
+        [listed[0].to_string(), listed[1].to_string()]
+    );
+
+    let thumbnail = |candidate: &HeaderCandidate| {
+        ansi_thumbnail(&Metadata::candidate_image(&post.path, candidate.index), 4)
+    };
+    let previewed = candidates_listing(&listed, Some(&thumbnail));
+    assert!(previewed.starts_with("\x1b[38;2;"));
+    // Each 4 columns wide preview of a 2:1 image is a single line of half blocks
+    assert_eq!(previewed.lines().count(), 4);
+    assert_eq!(
+        previewed
+            .lines()
+            .next()
+            .unwrap()
+            .matches('\u{2580}')
+            .count(),
+        4
+    );
+    assert_eq!(previewed.lines().nth(1).unwrap(), listed[0].to_string());
+}
+
+/// Returns the pexel provider querying the given server.
+fn pexels(server_url: &str) -> Vec<Box<dyn ImageProvider>> {
+    vec![Box::new(PexelProvider {
+        api_url: server_url.to_string(),
+        api_key: "key".to_string(),
+    })]
+}
+
+/// Returns a pexel search response listing the given photographers' pictures, of the
+/// given sizes, served by the given server.
+fn search_response(server_url: &str, photographers: &[(&str, usize, usize)]) -> String {
+    let photos = photographers
+        .iter()
+        .map(|(photographer, width, height)| {
+            format!(
+                r#"{{"width": {width}, "height": {height}, "url": "https://www.pexels.com/photo/{photographer}/", "photographer": "{photographer}", "photographer_url": "https://www.pexels.com/@{photographer}", "alt": "", "src": {{"landscape": "{server_url}/images/{photographer}.jpg"}}}}"#
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(r#"{{"photos": [{}]}}"#, photos.join(", "))
+}
+
+#[test]
+pub fn test_fetch_candidates_pages() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "With header", None);
+    let header_path = Metadata::header_path(&post.path);
+    let keywords = vec!["rust".to_string()];
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    let mut pages = vec![];
+    for (page, photographers) in [("1", ["alice", "bob"]), ("2", ["carla", "dan"])] {
+        let pictures = photographers.map(|photographer| (photographer, 1200, 600));
+        pages.push(
+            server
+                .mock("GET", "/search")
+                .match_header("authorization", "key")
+                .match_query(Matcher::AllOf(vec![
+                    Matcher::UrlEncoded("query".into(), "rust".into()),
+                    Matcher::UrlEncoded("per_page".into(), "2".into()),
+                    Matcher::UrlEncoded("page".into(), page.into()),
+                ]))
+                .with_body(search_response(&url, &pictures))
+                .create(),
+        );
+        for photographer in photographers {
+            server
+                .mock("GET", format!("/images/{photographer}.jpg").as_str())
+                .with_body(photographer)
+                .create();
+        }
+    }
+
+    for page in [1, 2] {
+        let fetched = fetch_candidates(
+            &pexels(&url),
+            header_path.clone(),
+            &keywords,
+            &HeaderSearch {
+                amount: 2,
+                page,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(fetched.len(), 2);
+    }
+    for mock in pages {
+        mock.assert();
+    }
+
+    let listed = Metadata::list_header_candidates(&post.path).unwrap();
+    let listed = listed
+        .iter()
+        .map(|candidate| (candidate.index, candidate.photographer.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(listed, [(1, "alice"), (2, "bob"), (3, "carla"), (4, "dan")]);
+    assert_eq!(
+        fs::read(header_path.join("candidates/header_003.jpg")).unwrap(),
+        b"carla"
+    );
+}
+
+#[test]
+pub fn test_fetch_candidates_per_keyword() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Per keyword", None);
+    let keywords = vec!["rust".to_string(), "cargo".to_string()];
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    let mut searches = vec![];
+    for (keyword, photographers) in [("rust", ["alice", "bob"]), ("cargo", ["bob", "carla"])] {
+        let pictures = photographers.map(|photographer| (photographer, 1200, 600));
+        searches.push(
+            server
+                .mock("GET", "/search")
+                .match_query(Matcher::UrlEncoded("query".into(), keyword.into()))
+                .with_body(search_response(&url, &pictures))
+                .expect(1)
+                .create(),
+        );
+    }
+    for photographer in ["alice", "bob", "carla"] {
+        server
+            .mock("GET", format!("/images/{photographer}.jpg").as_str())
+            .with_body(photographer)
+            .create();
+    }
+
+    let search = HeaderSearch {
+        amount: 3,
+        per_keyword: true,
+        ..Default::default()
+    };
+    let fetched = fetch_candidates(
+        &pexels(&url),
+        Metadata::header_path(&post.path),
+        &keywords,
+        &search,
+    )
+    .unwrap();
+    for search in searches {
+        search.assert();
+    }
+
+    // Bob is found for both keywords but saved once
+    let fetched = fetched
+        .iter()
+        .map(|candidate| (candidate.index, candidate.picture.photographer()))
+        .collect::<Vec<_>>();
+    assert_eq!(fetched, [(1, "alice"), (2, "bob"), (3, "carla")]);
+}
+
+#[test]
+pub fn test_fetch_candidates_quietly() {
+    // A hidden progress bar draws nothing, the downloads are logged instead
+    assert!(progress_bar(false).is_hidden());
+
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Quiet fetch", None);
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    server
+        .mock("GET", "/search")
+        .match_query(Matcher::Any)
+        .with_body(search_response(
+            &url,
+            &[("alice", 1200, 600), ("bob", 1200, 600)],
+        ))
+        .create();
+    for photographer in ["alice", "bob"] {
+        server
+            .mock("GET", format!("/images/{photographer}.jpg").as_str())
+            .with_body(photographer)
+            .create();
+    }
+
+    let search = HeaderSearch {
+        amount: 2,
+        progress: false,
+        ..Default::default()
+    };
+    let fetched = fetch_candidates(
+        &pexels(&url),
+        Metadata::header_path(&post.path),
+        &["rust".to_string()],
+        &search,
+    )
+    .unwrap();
+    assert_eq!(fetched.len(), 2);
+    assert_eq!(fs::read(&fetched[1].image).unwrap(), b"bob");
+}
+
+#[test]
+pub fn test_padded_candidate_names() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Padded", None);
+    let header_path = Metadata::header_path(&post.path);
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    server
+        .mock("GET", "/search")
+        .match_query(Matcher::Any)
+        .with_body(search_response(
+            &url,
+            &[("alice", 1200, 600), ("bob", 1200, 600)],
+        ))
+        .create();
+    for photographer in ["alice", "bob"] {
+        server
+            .mock("GET", format!("/images/{photographer}.jpg").as_str())
+            .with_body(photographer)
+            .create();
+    }
+
+    // The indices are padded to the same width whatever the amount and the page
+    let fetched = fetch_candidates(
+        &pexels(&url),
+        header_path.clone(),
+        &["rust".to_string()],
+        &HeaderSearch {
+            amount: 10,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let images = fetched
+        .iter()
+        .map(|candidate| candidate.image.clone())
+        .collect::<Vec<_>>();
+    let candidates = header_path.join("candidates");
+    assert_eq!(
+        images,
+        [
+            candidates.join("header_001.jpg"),
+            candidates.join("header_002.jpg")
+        ]
+    );
+    assert!(candidates.join("header_002.toml").is_file());
+
+    // The candidates of older posts are not padded
+    fs::write(
+        candidates.join("header_7.toml"),
+        candidate_toml("carla", 800),
+    )
+    .unwrap();
+    fs::write(candidates.join("header_7.jpg"), b"carla").unwrap();
+    let listed = Metadata::list_header_candidates(&post.path).unwrap();
+    let indices = listed
+        .iter()
+        .map(|candidate| candidate.index)
+        .collect::<Vec<_>>();
+    assert_eq!(indices, [1, 2, 7]);
+
+    post.metadata.choose_header(&post.path, 2).unwrap();
+    assert_eq!(fs::read(header_path.join("header.jpg")).unwrap(), b"bob");
+    post.metadata.choose_header(&post.path, 7).unwrap();
+    assert_eq!(fs::read(header_path.join("header.jpg")).unwrap(), b"carla");
+
+    // The padded name wins over the other names of the same index
+    fs::write(candidates.join("header_07.jpg"), b"older").unwrap();
+    assert_eq!(
+        Metadata::candidate_image(&post.path, 7),
+        candidates.join("header_07.jpg")
+    );
+    fs::write(candidates.join("header_007.jpg"), b"padded").unwrap();
+    assert_eq!(
+        Metadata::candidate_image(&post.path, 7),
+        candidates.join("header_007.jpg")
+    );
+}
+
+#[test]
+pub fn test_fetch_amount_default() {
+    assert_eq!(Config::default().header.amount, 10);
+    let config: Config = toml::from_str("[header]\namount = 25\n").unwrap();
+    assert_eq!(config.header.amount, 25);
+    assert_eq!(config.header.providers, [ImageProviderKind::Pexels]);
+}
+
+#[test]
+pub fn test_fetch_amount_clamped() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Many headers", None);
+    let header_path = Metadata::header_path(&post.path);
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    let search = server
+        .mock("GET", "/search")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("per_page".into(), "80".into()),
+            Matcher::UrlEncoded("page".into(), "2".into()),
+        ]))
+        .with_body(search_response(&url, &[("alice", 1200, 600)]))
+        .create();
+    server
+        .mock("GET", "/images/alice.jpg")
+        .with_body("alice")
+        .create();
+
+    let fetched = fetch_candidates(
+        &pexels(&url),
+        header_path.clone(),
+        &["rust".to_string()],
+        &HeaderSearch {
+            amount: 500,
+            page: 2,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    search.assert();
+    // The candidates of the second page are numbered after the 80 of the first one
+    assert_eq!(fetched[0].index, 81);
+
+    let zero = fetch_candidates(
+        &pexels(&url),
+        header_path,
+        &["rust".to_string()],
+        &HeaderSearch {
+            amount: 0,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        zero.err().as_deref(),
+        Some("The amount of images to fetch must be at least 1")
+    );
+}
+
+#[test]
+pub fn test_fetch_dry_run() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "With header", None);
+    let header_path = Metadata::header_path(&post.path);
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    let search = server
+        .mock("GET", "/search")
+        .match_query(Matcher::Any)
+        .with_body(search_response(
+            &url,
+            &[("alice", 1920, 1080), ("bob", 1200, 600)],
+        ))
+        .create();
+    let images = server
+        .mock("GET", Matcher::Regex("^/images/".to_string()))
+        .expect(0)
+        .create();
+
+    let fetched = fetch_candidates(
+        &pexels(&url),
+        header_path.clone(),
+        &["rust".to_string()],
+        &HeaderSearch {
+            amount: 2,
+            dry_run: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    search.assert();
+    images.assert();
+    assert!(!header_path.join("candidates").exists());
+
+    let candidates = fetched
+        .iter()
+        .map(|candidate| HeaderCandidate::new(candidate.index, &candidate.picture))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        candidates_listing(&candidates, None),
+        "1 - Picture by alice - https://www.pexels.com/photo/alice/ `` (1920x1080)\n\
+         2 - Picture by bob - https://www.pexels.com/photo/bob/ `` (1200x600)\n"
+    );
+}
+
+#[test]
+pub fn test_fetch_candidates_filters() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "With header", None);
+    let header_path = Metadata::header_path(&post.path);
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    let pictures = [
+        ("alice", 1920, 1080),
+        ("bob", 640, 1080),
+        ("carla", 1920, 480),
+        ("dan", 1280, 720),
+    ];
+    let search = server
+        .mock("GET", "/search")
+        .match_query(Matcher::UrlEncoded("color".into(), "#ff0000".into()))
+        .with_body(search_response(&url, &pictures))
+        .create();
+    for (photographer, _, _) in pictures {
+        server
+            .mock("GET", format!("/images/{photographer}.jpg").as_str())
+            .with_body(photographer)
+            .create();
+    }
+
+    let fetched = fetch_candidates(
+        &pexels(&url),
+        header_path.clone(),
+        &["rust".to_string()],
+        &HeaderSearch {
+            amount: 4,
+            color: Some("#ff0000".to_string()),
+            min_width: 1280,
+            min_height: 720,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    search.assert();
+    assert_eq!(fetched.len(), 2);
+    for (candidate, (index, photographer)) in fetched.iter().zip([(1, "alice"), (2, "dan")]) {
+        assert_eq!(candidate.index, index);
+        assert_eq!(candidate.picture.photographer(), photographer);
+        assert_eq!(
+            candidate.image,
+            header_path.join(format!("candidates/header_{index:03}.jpg"))
+        );
+        assert_eq!(fs::read(&candidate.image).unwrap(), photographer.as_bytes());
+        let saved: Picture =
+            toml::from_str(&fs::read_to_string(&candidate.metadata).unwrap()).unwrap();
+        assert_eq!(saved.photographer(), photographer);
+        assert_eq!(saved.url(), candidate.picture.url());
+    }
+
+    let listed = Metadata::list_header_candidates(&post.path).unwrap();
+    let listed = listed
+        .iter()
+        .map(|candidate| (candidate.index, candidate.photographer.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(listed, [(1, "alice"), (2, "dan")]);
+}
+
+#[test]
+pub fn test_fetch_candidates_http_errors() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Failing search", None);
+    for (status, body, expected) in [
+        (
+            401,
+            "Unauthorized",
+            "HTTP 401, the API key was refused, check PEXEL_API_KEY (Unauthorized)",
+        ),
+        (
+            429,
+            "Rate limit exceeded",
+            "HTTP 429, rate limited, try again later (Rate limit exceeded)",
+        ),
+        (500, "Oops", "HTTP 500 Oops"),
+    ] {
+        let mut server = mockito::Server::new();
+        server
+            .mock("GET", "/search")
+            .match_query(Matcher::Any)
+            .with_status(status)
+            .with_body(body)
+            .create();
+
+        let Err(e) = fetch_candidates(
+            &pexels(&server.url()),
+            Metadata::header_path(&post.path),
+            &["rust".to_string()],
+            &HeaderSearch::default(),
+        ) else {
+            panic!("The search should fail with HTTP {status}");
+        };
+        assert!(
+            e.contains(&format!("Failed to search images on pexels: {expected}")),
+            "{e}"
+        );
+    }
+}
+
+#[test]
+pub fn test_fetch_candidates_fallback() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "With header", None);
+    let header_path = Metadata::header_path(&post.path);
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    let pexels = server
+        .mock("GET", "/search")
+        .match_query(Matcher::Any)
+        .with_status(429)
+        .with_body("Rate limit exceeded")
+        .create();
+    let unsplash = server
+        .mock("GET", "/unsplash/search/photos")
+        .match_header("authorization", "Client-ID access")
+        .match_query(Matcher::UrlEncoded("query".into(), "rust ferris".into()))
+        .with_body(format!(
+            r#"{{"results": [{{"width": 1600, "height": 900, "alt_description": "A crab",
+                "urls": {{"regular": "{url}/images/crab.jpg"}},
+                "links": {{"html": "https://unsplash.com/photos/crab"}},
+                "user": {{"name": "Erin", "links": {{"html": "https://unsplash.com/@erin"}}}}}}]}}"#
+        ))
+        .create();
+    server
+        .mock("GET", "/images/crab.jpg")
+        .with_body("crab")
+        .create();
+
+    let providers: Vec<Box<dyn ImageProvider>> = vec![
+        Box::new(PexelProvider {
+            api_url: url.clone(),
+            api_key: "key".to_string(),
+        }),
+        Box::new(UnsplashProvider {
+            api_url: format!("{url}/unsplash"),
+            access_key: "access".to_string(),
+        }),
+    ];
+    let keywords = ["rust".to_string(), "ferris".to_string()];
+    let fetched = fetch_candidates(
+        &providers,
+        header_path.clone(),
+        &keywords,
+        &HeaderSearch::default(),
+    )
+    .unwrap();
+    unsplash.assert();
+    let images = fetched
+        .iter()
+        .map(|candidate| &candidate.image)
+        .collect::<Vec<_>>();
+    assert_eq!(images, [&header_path.join("candidates/header_001.jpg")]);
+
+    let listed = Metadata::list_header_candidates(&post.path).unwrap();
+    assert_eq!(
+        listed,
+        [HeaderCandidate {
+            index: 1,
+            photographer: "Erin".to_string(),
+            photographer_url: "https://unsplash.com/@erin".to_string(),
+            url: "https://unsplash.com/photos/crab".to_string(),
+            alt: "A crab".to_string(),
+            width: 1600,
+            height: 900,
+        }]
+    );
+
+    // The errors of the user are not worth trying another provider
+    assert!(fetch_candidates(
+        &providers,
+        header_path,
+        &keywords,
+        &HeaderSearch {
+            page: 0,
+            ..Default::default()
+        },
+    )
+    .is_err());
+    pexels.assert();
+}
+
+#[test]
+pub fn test_choose_header() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "With header", None);
+    let candidates = Metadata::header_path(&post.path).join("candidates");
+    fs::create_dir_all(&candidates).unwrap();
+    for (index, photographer) in [(1, "alice"), (2, "bob")] {
+        fs::write(
+            candidates.join(format!("header_{index}.toml")),
+            candidate_toml(photographer, 1200),
+        )
+        .unwrap();
+        fs::write(candidates.join(format!("header_{index}.jpg")), photographer).unwrap();
+    }
+
+    assert!(post.metadata.choose_header(&post.path, 3).is_err());
+    post.metadata.choose_header(&post.path, 2).unwrap();
+    assert_eq!(
+        fs::read(Metadata::header_exists(&post.path).unwrap()).unwrap(),
+        b"bob"
+    );
+
+    let post = Post::load(&post.path).unwrap();
+    assert_eq!(
+        post.metadata.opengraph.opengraphimage,
+        "images/header/header.jpg"
+    );
+    assert_eq!(
+        post.metadata.header,
+        Some(HeaderCredit {
+            photographer: "bob".to_string(),
+            photographer_url: "https://www.pexels.com/@bob".to_string(),
+            url: "https://www.pexels.com/photo/bob/".to_string(),
+        })
+    );
+}
+
+#[test]
+pub fn test_env_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let env_file = dir.path().join(".env.test");
+    fs::write(&env_file, "PEXEL_API_KEY=key-from-env-file\n").unwrap();
+
+    // The variables are read from the file, the environment is left untouched
+    let variables = read_env_file(&env_file).unwrap();
+    let keys = ApiKeys::from_source(|name| variables.get(name).cloned());
+    assert_eq!(keys.pexels.as_deref(), Some("key-from-env-file"));
+    assert_eq!(keys.unsplash, None);
+
+    // An explicit env file must exist
+    assert!(load_env(Some(&dir.path().join(".env.missing"))).is_err());
+}
+
+#[test]
+pub fn test_injected_api_key() {
+    // The keys are given to the providers, the environment is never read
+    let keys = ApiKeys {
+        pexels: Some("injected-key".to_string()),
+        unsplash: None,
+    };
+    let created = providers(
+        &[ImageProviderKind::Unsplash, ImageProviderKind::Pexels],
+        &keys,
+    )
+    .unwrap();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].name(), "pexels");
+    let Err(e) = providers(&[ImageProviderKind::Unsplash], &keys) else {
+        panic!("The unsplash provider has no key");
+    };
+    assert!(e.contains("Missing UNSPLASH_ACCESS_KEY"));
+
+    let mut server = mockito::Server::new();
+    let search = server
+        .mock("GET", "/search")
+        .match_header("authorization", "injected-key")
+        .match_query(Matcher::Any)
+        .with_body(search_response(&server.url(), &[("alice", 1200, 600)]))
+        .create();
+    let provider = PexelProvider {
+        api_url: server.url(),
+        ..PexelProvider::new("injected-key".to_string())
+    };
+    let pictures = provider
+        .search(&["rust".to_string()], &HeaderSearch::default())
+        .unwrap();
+    assert_eq!(pictures.len(), 1);
+    search.assert();
+}
@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_add_image() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "With images", None);
+    let source = root.path().join("My Diagram.PNG");
+    fs::write(&source, b"image").unwrap();
+
+    let copied = post.add_image(&source, false, false).unwrap();
+    assert_eq!(copied, post.path.join("images/My Diagram.PNG"));
+    assert_eq!(fs::read(&copied).unwrap(), b"image");
+
+    let copied = post.add_image(&source, true, false).unwrap();
+    assert_eq!(copied, post.path.join("images/my-diagram.png"));
+
+    // Non-image files are only accepted when forced
+    let notes = root.path().join("notes.txt");
+    fs::write(&notes, b"notes").unwrap();
+    assert!(post.add_image(&notes, false, false).is_err());
+    assert!(post.add_image(&notes, false, true).is_ok());
+
+    // An image of the same name is only overwritten when forced
+    fs::write(&source, b"other").unwrap();
+    assert!(post.add_image(&source, false, false).is_err());
+    assert_eq!(
+        fs::read(post.path.join("images/My Diagram.PNG")).unwrap(),
+        b"image"
+    );
+    let copied = post.add_image(&source, false, true).unwrap();
+    assert_eq!(fs::read(&copied).unwrap(), b"other");
+}
+
+#[test]
+pub fn test_list_images() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "With images", None);
+    assert!(post.list_images().unwrap().is_empty());
+
+    fs::write(post.path.join("images/b.png"), b"").unwrap();
+    fs::write(post.path.join("images/a.jpg"), b"").unwrap();
+    assert_eq!(
+        post.list_images().unwrap(),
+        vec![PathBuf::from("a.jpg"), PathBuf::from("b.png")]
+    );
+}
@@ -0,0 +1,132 @@
+use std::fs;
+
+use chrono::{TimeZone, Utc};
+
+use crate::config::Config;
+use crate::import::{import, split_front_matter, FrontMatter};
+use crate::post::Post;
+
+#[test]
+pub fn test_import_title_from_heading() {
+    let root = tempfile::tempdir().unwrap();
+    let file = root.path().join("notes.md");
+    fs::write(
+        &file,
+        "# Loose notes\n\nSome text\n\n![A diagram](diagram.png)\n",
+    )
+    .unwrap();
+    fs::write(root.path().join("diagram.png"), b"image").unwrap();
+
+    let blog = root.path().join("blog");
+    let post = import(&file, None, &blog, &Config::default()).unwrap();
+    assert_eq!(post.metadata.post.title, "Loose notes");
+    assert!(post.path.starts_with(&blog));
+    assert!(post.path.ends_with("loose-notes"));
+
+    let post = Post::load(&post.path).unwrap();
+    assert_eq!(
+        post.content,
+        "# Loose notes\n\nSome text\n\n![A diagram](images/diagram.png)\n"
+    );
+    assert_eq!(
+        fs::read(post.path.join("images/diagram.png")).unwrap(),
+        b"image"
+    );
+
+    // The same post cannot be imported twice
+    assert!(import(&file, None, &blog, &Config::default()).is_err());
+}
+
+#[test]
+pub fn test_import_images_of_the_same_name() {
+    let root = tempfile::tempdir().unwrap();
+    for (directory, content) in [("a", "first"), ("b", "second"), ("c d", "third")] {
+        fs::create_dir_all(root.path().join(directory)).unwrap();
+        fs::write(root.path().join(directory).join("pic.png"), content).unwrap();
+    }
+    fs::write(root.path().join("pic.png.old"), b"old").unwrap();
+    let file = root.path().join("pictures.md");
+    fs::write(
+        &file,
+        "# Pictures\n\n![First](a/pic.png)\n\n![Second](b/pic.png \"The second\")\n\n\
+         ![Third](<c d/pic.png>)\n\n![Again](a/pic.png)\n\n[Old](pic.png.old)\n",
+    )
+    .unwrap();
+
+    let post = import(&file, None, &root.path().join("blog"), &Config::default()).unwrap();
+    assert_eq!(
+        Post::load(&post.path).unwrap().content,
+        "# Pictures\n\n![First](images/pic.png)\n\n![Second](images/pic-1.png \"The second\")\n\n\
+         ![Third](images/pic-2.png)\n\n![Again](images/pic.png)\n\n[Old](pic.png.old)\n"
+    );
+    for (name, content) in [
+        ("pic.png", "first"),
+        ("pic-1.png", "second"),
+        ("pic-2.png", "third"),
+    ] {
+        assert_eq!(
+            fs::read_to_string(post.path.join("images").join(name)).unwrap(),
+            content
+        );
+    }
+}
+
+#[test]
+pub fn test_import_title_from_flag() {
+    let root = tempfile::tempdir().unwrap();
+    let file = root.path().join("my-first-draft.md");
+    fs::write(
+        &file,
+        "---\ntitle: \"From the front matter\"\ndate: 2023-05-04\ntags: [rust, cli]\nkeywords:\n  - terminal\n  - blog\n---\n\nNo heading here\n",
+    )
+    .unwrap();
+
+    let post = import(&file, Some("Given title"), root.path(), &Config::default()).unwrap();
+    assert_eq!(post.metadata.post.title, "Given title");
+    assert!(post.path.ends_with("2023/05/given-title"));
+    assert_eq!(post.content, "# Given title\n\nNo heading here\n");
+    assert_eq!(post.metadata.post.tags, ["rust", "cli"]);
+    assert_eq!(post.metadata.opengraph.keywords, ["terminal", "blog"]);
+
+    // Without a flag, the title of the front matter comes first, then the name of the file
+    let post = import(&file, None, root.path(), &Config::default()).unwrap();
+    assert_eq!(post.metadata.post.title, "From the front matter");
+    fs::write(&file, "No heading here\n").unwrap();
+    let post = import(&file, None, root.path(), &Config::default()).unwrap();
+    assert_eq!(post.metadata.post.title, "my first draft");
+}
+
+#[test]
+pub fn test_split_toml_front_matter() {
+    let (front_matter, content) =
+        split_front_matter("+++\nauthor = \"Me\"\ndescription = 'Short'\n+++\n# Title\n").unwrap();
+    assert_eq!(
+        front_matter,
+        Some(FrontMatter {
+            authors: vec!["Me".to_string()],
+            description: Some("Short".to_string()),
+            ..Default::default()
+        })
+    );
+    assert_eq!(content, "# Title\n");
+
+    let (front_matter, content) = split_front_matter(
+        "+++\ntitle = \"Re: colons\"\ndate = 2023-05-04T10:00:00Z\ntags = [\"a\", \"b\"]\n+++\n",
+    )
+    .unwrap();
+    assert_eq!(
+        front_matter,
+        Some(FrontMatter {
+            title: Some("Re: colons".to_string()),
+            tags: vec!["a".to_string(), "b".to_string()],
+            date: Some(Utc.with_ymd_and_hms(2023, 5, 4, 10, 0, 0).unwrap()),
+            ..Default::default()
+        })
+    );
+    assert_eq!(content, "");
+    assert!(split_front_matter("+++\ntitle = [\n+++\n").is_err());
+
+    let (front_matter, content) = split_front_matter("---\nunterminated\n").unwrap();
+    assert_eq!(front_matter, None);
+    assert_eq!(content, "---\nunterminated\n");
+}
@@ -0,0 +1,49 @@
+use crate::keywords::{apply_suggestions, suggest};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_suggest_keywords() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Suggestions", None);
+    post.content = "# Rust lifetimes\n\nLifetimes tell the compiler how long references live. \
+                    The compiler checks the lifetimes of the references, and Rust's borrow \
+                    checker rejects references outliving their data.\n\n\
+                    ```rust\nfn compiler<'a>(compiler: &'a str) -> &'a str { compiler }\n```\n"
+        .to_string();
+    post.metadata.opengraph.keywords = vec!["Borrow".to_string()];
+
+    let suggestions = suggest(&post, 4).unwrap();
+    let terms: Vec<(&str, usize)> = suggestions
+        .iter()
+        .map(|suggestion| (suggestion.term.as_str(), suggestion.count))
+        .collect();
+    assert_eq!(
+        terms,
+        [
+            ("lifetimes", 3),
+            ("references", 3),
+            ("compiler", 2),
+            ("rust", 2)
+        ]
+    );
+    assert!(suggestions[0].frequency > 0.0 && suggestions[0].frequency < 1.0);
+
+    let added = apply_suggestions(&mut post, &suggestions, 2, 0).unwrap();
+    assert_eq!(added, ["lifetimes", "references"]);
+    assert_eq!(
+        post.metadata.opengraph.keywords,
+        ["Borrow", "lifetimes", "references"]
+    );
+
+    // The keywords the post already has are not suggested again
+    let terms: Vec<String> = suggest(&post, 2)
+        .unwrap()
+        .into_iter()
+        .map(|suggestion| suggestion.term)
+        .collect();
+    assert_eq!(terms, ["compiler", "rust"]);
+
+    // The maximum number of keywords is enforced
+    let suggestions = suggest(&post, 2).unwrap();
+    assert!(apply_suggestions(&mut post, &suggestions, 2, 4).is_err());
+}
@@ -0,0 +1,87 @@
+use std::fs;
+
+use crate::links::{check_links, check_post_references, extract_links, LinkStatus};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_extract_links() {
+    let html = r#"<a href="https://example.com">link</a><img src='images/a.png' alt="">"#;
+    assert_eq!(
+        extract_links(html),
+        vec![
+            "https://example.com".to_string(),
+            "images/a.png".to_string()
+        ]
+    );
+}
+
+#[test]
+pub fn test_missing_local_file() {
+    let post = tempfile::tempdir().unwrap();
+    fs::create_dir(post.path().join("images")).unwrap();
+    fs::write(post.path().join("images/present.png"), b"").unwrap();
+
+    let html =
+        r##"<img src="images/present.png"><img src="images/missing.png"><a href="#top">top</a>"##;
+    let reports = check_links(html, post.path(), false).unwrap();
+
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].status, LinkStatus::Ok);
+    assert_eq!(reports[1].link, "images/missing.png");
+    assert!(matches!(reports[1].status, LinkStatus::Broken(_)));
+    assert_eq!(reports[2].status, LinkStatus::Skipped);
+}
+
+#[test]
+pub fn test_broken_external_link() {
+    let mut server = mockito::Server::new();
+    let missing = server.mock("HEAD", "/missing").with_status(404).create();
+    let present = server.mock("HEAD", "/present").with_status(200).create();
+
+    let html = format!(
+        r#"<a href="{0}/missing">a</a><a href="{0}/present">b</a>"#,
+        server.url()
+    );
+    let post = tempfile::tempdir().unwrap();
+
+    // External links are left alone unless requested
+    let reports = check_links(&html, post.path(), false).unwrap();
+    assert!(reports
+        .iter()
+        .all(|report| report.status == LinkStatus::Skipped));
+
+    let reports = check_links(&html, post.path(), true).unwrap();
+    assert_eq!(
+        reports[0].status,
+        LinkStatus::Broken("HTTP 404".to_string())
+    );
+    assert_eq!(reports[1].status, LinkStatus::Ok);
+    missing.assert();
+    present.assert();
+}
+
+#[test]
+pub fn test_post_references() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Referencing", None);
+    let target = fixture_post(root.path(), "Target", None);
+    let relative = target
+        .path
+        .strip_prefix(root.path())
+        .unwrap()
+        .display()
+        .to_string();
+    post.content = format!(
+        "[Sibling](../target/) [Absolute](/blog/{relative}/index.html) [Dangling](../../../2019/01/renamed/) [Image](images/a.png) [Site](https://example.com)"
+    );
+
+    let reports = check_post_references(&post, root.path(), "https://example.com/blog").unwrap();
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].status, LinkStatus::Ok);
+    assert_eq!(reports[1].status, LinkStatus::Ok);
+    assert_eq!(reports[2].link, "../../../2019/01/renamed/");
+    assert_eq!(
+        reports[2].status,
+        LinkStatus::Broken("no post at 2019/01/renamed".to_string())
+    );
+}
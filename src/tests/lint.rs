@@ -0,0 +1,126 @@
+use crate::lint::{
+    check_anchor_collisions, check_description, check_duplicate_headings, check_heading_levels,
+    check_image_alt, check_keywords, check_missing_images, check_orphan_images, lint, Severity,
+};
+use crate::post::Metadata;
+use crate::tests::fixture_post;
+
+/// Parses the given markdown
+fn tree(content: &str) -> markdown::mdast::Node {
+    markdown::to_mdast(content, &markdown::ParseOptions::gfm()).unwrap()
+}
+
+#[test]
+pub fn test_lint_clean_post() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Clean", None);
+    post.content =
+        "# Clean\n\n## Part one\n\n![A cat](images/cat.png)\n\n### Detail\n\n## Part two\n"
+            .to_string();
+    post.metadata.opengraph.keywords = vec!["cats".to_string()];
+    post.metadata.opengraph.description = "A post about cats".to_string();
+    std::fs::write(post.path.join("images/cat.png"), b"cat").unwrap();
+
+    assert!(lint(&post).unwrap().is_empty());
+}
+
+#[test]
+pub fn test_lint_missing_images() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Missing images", None);
+    std::fs::write(post.path.join("images/cat.png"), b"cat").unwrap();
+
+    let content = "# Title\n\n![A cat](./images/cat.png)\n\n![A dog](images/dog.png#large)\n\n\
+                   ![Remote](https://example.com/bird.png)\n";
+    let findings = check_missing_images(&tree(content), &post.path);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, Severity::Error);
+    assert_eq!(findings[0].line, Some(5));
+    assert!(findings[0].message.contains("images/dog.png"));
+
+    // The URLs are percent-decoded
+    std::fs::write(post.path.join("images/my cat.png"), b"cat").unwrap();
+    let content = "![A cat](images/my%20cat.png)\n\n![A dog](<images/my dog.png>)\n";
+    let findings = check_missing_images(&tree(content), &post.path);
+    assert_eq!(findings.len(), 1);
+    assert!(findings[0].message.contains("images/my dog.png"));
+}
+
+#[test]
+pub fn test_lint_orphan_images() {
+    let root = tempfile::tempdir().unwrap();
+    let post = fixture_post(root.path(), "Orphan images", None);
+    std::fs::create_dir_all(post.path.join("images/header")).unwrap();
+    std::fs::write(post.path.join("images/header/header.jpg"), b"header").unwrap();
+    std::fs::write(post.path.join("images/cat.png"), b"cat").unwrap();
+    std::fs::write(post.path.join("images/dog.png"), b"dog").unwrap();
+
+    let findings =
+        check_orphan_images(&tree("# Title\n\n![A cat](images/cat.png)\n"), &post.path).unwrap();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, Severity::Info);
+    assert!(findings[0].message.contains("images/dog.png"));
+}
+
+#[test]
+pub fn test_lint_heading_levels() {
+    let findings = check_heading_levels(&tree("# Title\n\n### Too deep\n\n## Fine\n\n# Back up\n"));
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, Severity::Error);
+    assert_eq!(findings[0].line, Some(3));
+}
+
+#[test]
+pub fn test_lint_duplicate_headings() {
+    let findings = check_duplicate_headings(&tree("# Title\n\n## Usage\n\n## usage\n"));
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].line, Some(5));
+    assert!(findings[0].message.contains("line 3"));
+}
+
+#[test]
+pub fn test_lint_image_alt() {
+    let findings = check_image_alt(&tree("![](images/a.png)\n\n![Described](images/b.png)\n"));
+    assert_eq!(findings.len(), 1);
+    assert!(findings[0].message.contains("images/a.png"));
+}
+
+#[test]
+pub fn test_lint_metadata() {
+    let mut metadata = Metadata::default();
+    assert_eq!(check_keywords(&metadata).len(), 1);
+    assert_eq!(check_description(&metadata).len(), 1);
+
+    metadata.opengraph.keywords = vec!["rust".to_string()];
+    metadata.opengraph.description = "a".repeat(161);
+    assert!(check_keywords(&metadata).is_empty());
+    let findings = check_description(&metadata);
+    assert_eq!(findings[0].severity, Severity::Warning);
+    assert!(findings[0].message.contains("161 characters"));
+
+    metadata.opengraph.description = "a".repeat(160);
+    assert!(check_description(&metadata).is_empty());
+}
+
+#[test]
+pub fn test_lint_anchor_collisions() {
+    let findings = check_anchor_collisions(&tree("# Title\n\n## Usage\n\n## Usage\n\n## Other\n"));
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, Severity::Warning);
+    assert!(findings[0].message.contains("lines 3, 5"));
+    assert!(findings[0].message.contains("`#usage`"));
+
+    // The suffixed anchors collide with the headings asking for them
+    let findings = check_anchor_collisions(&tree("# Usage\n\n## Usage\n\n## Usage 1\n"));
+    assert_eq!(findings.len(), 2);
+    assert!(findings[0].message.contains("lines 1, 3"));
+    assert!(findings[0].message.contains("`#usage`"));
+    assert!(findings[1].message.contains("lines 3, 5"));
+    assert!(findings[1].message.contains("`#usage-1`"));
+
+    // The headings without a slug fall back to `section`
+    let findings = check_anchor_collisions(&tree("# Title\n\n## !!!\n\n## Section\n"));
+    assert_eq!(findings.len(), 1);
+    assert!(findings[0].message.contains("lines 3, 5"));
+    assert!(findings[0].message.contains("`#section`"));
+}
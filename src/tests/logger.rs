@@ -0,0 +1,33 @@
+use std::fs;
+
+use log::{Level, LevelFilter, Log, Record};
+
+use crate::logger::Logger;
+
+#[test]
+pub fn test_log_file() {
+    let root = tempfile::tempdir().unwrap();
+    let path = root.path().join("blog.log");
+    let logger = Logger::new(Some(LevelFilter::Warn), Some(&path)).unwrap();
+
+    for (level, message) in [
+        (Level::Error, "an error"),
+        (Level::Warn, "a warning"),
+        (Level::Info, "some information"),
+        (Level::Debug, "some details"),
+    ] {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+    logger.flush();
+
+    let lines = fs::read_to_string(&path).unwrap();
+    let lines = lines.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("[ERROR] an error"));
+    assert!(lines[1].ends_with("[WARN] a warning"));
+}
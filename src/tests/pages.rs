@@ -0,0 +1,159 @@
+use std::fs;
+
+use chrono::{TimeZone, Utc};
+
+use crate::config::Config;
+use crate::pages::{archive_pages, index_page, listing_html, tag_pages};
+use crate::post::Post;
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_tag_pages() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let published = Some(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap());
+
+    for (title, tags) in [
+        ("Rust post", vec!["rust", "Programming Languages"]),
+        ("Python post", vec!["python", "Programming Languages"]),
+        ("Another rust post", vec!["rust"]),
+    ] {
+        let mut post = fixture_post(root.path(), title, published);
+        post.metadata.post.tags = tags.into_iter().map(String::from).collect();
+        post.save().unwrap();
+    }
+
+    let posts = Post::load_published(root.path()).unwrap();
+    let pages = tag_pages(&posts, root.path(), output.path(), &Config::default()).unwrap();
+    assert_eq!(pages.len(), 3);
+
+    let tag_page = |slug: &str| {
+        fs::read_to_string(output.path().join("tags").join(slug).join("index.html")).unwrap()
+    };
+
+    let rust = tag_page("rust");
+    assert!(rust.contains(">Rust post</a>"));
+    assert!(rust.contains(">Another rust post</a>"));
+    assert!(!rust.contains(">Python post</a>"));
+
+    let languages = tag_page("programming-languages");
+    assert!(languages.contains(">Rust post</a>"));
+    assert!(languages.contains(">Python post</a>"));
+    assert!(!languages.contains(">Another rust post</a>"));
+    assert!(languages.contains("href=\"/"));
+    assert!(languages.contains("/rust-post/\""));
+
+    let python = tag_page("python");
+    assert!(python.contains(">Python post</a>"));
+    assert_eq!(python.matches("<li>").count(), 1);
+}
+
+#[test]
+pub fn test_archive_pages() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    for (title, year, month) in [
+        ("Winter post", 2023, 12),
+        ("New year post", 2024, 1),
+        ("Another january post", 2024, 1),
+        ("Spring post", 2024, 3),
+    ] {
+        let date = Utc.with_ymd_and_hms(year, month, 10, 12, 0, 0).unwrap();
+        fixture_post(root.path(), title, Some(date));
+    }
+    fixture_post(root.path(), "Draft post", None);
+
+    let posts = Post::load_published(root.path()).unwrap();
+    let pages = archive_pages(&posts, root.path(), output.path(), &Config::default()).unwrap();
+    assert_eq!(pages.len(), 5);
+    let page =
+        |path: &str| fs::read_to_string(output.path().join(path).join("index.html")).unwrap();
+
+    let january = page("2024/01");
+    assert!(january.contains("<h1>January 2024</h1>"));
+    assert!(january.contains(">New year post</a>"));
+    assert!(january.contains(">Another january post</a>"));
+    assert_eq!(january.matches("<li>").count(), 2);
+    assert_eq!(page("2023/12").matches("<li>").count(), 1);
+
+    // The year page lists its months, newest first
+    let year = page("2024");
+    let march = year.find("<a href=\"03/\">March</a>").unwrap();
+    let january = year.find("<a href=\"01/\">January</a>").unwrap();
+    assert!(march < january);
+    assert!(year.contains(">Spring post</a>"));
+    assert!(!year.contains(">Winter post</a>"));
+    assert!(!year.contains(">Draft post</a>"));
+}
+
+#[test]
+pub fn test_index_page() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+
+    for (title, year) in [
+        ("Middle post", 2023),
+        ("Newest post", 2024),
+        ("Oldest post", 2022),
+    ] {
+        let date = Utc.with_ymd_and_hms(year, 6, 1, 12, 0, 0).unwrap();
+        let mut post = fixture_post(root.path(), title, Some(date));
+        post.content = format!("# {title}\n\nThe *content* of the [post](https://example.com).");
+        post.save().unwrap();
+    }
+    fixture_post(root.path(), "Draft post", None);
+
+    let posts = Post::load_published(root.path()).unwrap();
+    let page = index_page(
+        &posts,
+        root.path(),
+        output.path(),
+        &Config::default(),
+        false,
+    )
+    .unwrap();
+    let index = fs::read_to_string(page).unwrap();
+
+    let newest = index.find(">Newest post</a>").unwrap();
+    let middle = index.find(">Middle post</a>").unwrap();
+    let oldest = index.find(">Oldest post</a>").unwrap();
+    assert!(newest < middle && middle < oldest);
+    assert!(!index.contains("Draft post"));
+    assert!(index.contains("<time datetime=\"2024-06-01T12:00:00+00:00\">2024-06-01</time>"));
+    assert!(index.contains("1 min read"));
+    assert!(index.contains("<p>The content of the post.</p>"));
+
+    index_page(&posts, root.path(), output.path(), &Config::default(), true).unwrap();
+    let index = fs::read_to_string(output.path().join("index.html")).unwrap();
+    let year_2024 = index.find("<h2>2024</h2>").unwrap();
+    let year_2023 = index.find("<h2>2023</h2>").unwrap();
+    assert!(year_2024 < index.find(">Newest post</a>").unwrap());
+    assert!(index.find(">Newest post</a>").unwrap() < year_2023);
+}
+
+#[test]
+pub fn test_display_timezone() {
+    let root = tempfile::tempdir().unwrap();
+    // Late evening in UTC is already the next day in Tokyo
+    let published = Utc.with_ymd_and_hms(2024, 3, 1, 22, 30, 0).unwrap();
+    let post = fixture_post(root.path(), "Late post", Some(published));
+
+    let mut config = Config::default();
+    config.display.timezone = chrono_tz::Asia::Tokyo;
+    config.display.date_format = "%d/%m/%Y %H:%M".to_string();
+    assert_eq!(config.display.format(published), "02/03/2024 07:30");
+
+    let listing = listing_html(&[&post], root.path(), &config);
+    assert!(
+        listing.contains("<time datetime=\"2024-03-01T22:30:00+00:00\">02/03/2024 07:30</time>")
+    );
+    // The stored date is untouched
+    assert_eq!(
+        Post::load(&post.path).unwrap().metadata.post.published_date,
+        Some(published)
+    );
+
+    let config: Config = toml::from_str("[display]\ntimezone = \"Europe/Brussels\"\n").unwrap();
+    assert_eq!(config.display.format(published), "2024-03-01");
+    assert!(toml::from_str::<Config>("[display]\ntimezone = \"Mars/Olympus\"\n").is_err());
+}
@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+
+use crate::post::BuildOptions;
+use crate::preview::{build_post, open_post, Opener};
+use crate::tests::fixture_post;
+
+/// Records the pages it is asked to open
+#[derive(Default)]
+struct RecordingOpener {
+    opened: RefCell<Vec<PathBuf>>,
+}
+
+impl Opener for RecordingOpener {
+    fn open(&self, page: &Path) -> Result<(), String> {
+        self.opened.borrow_mut().push(page.to_path_buf());
+        Ok(())
+    }
+}
+
+#[test]
+pub fn test_open_post() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Preview", Some(Utc::now()));
+    let opener = RecordingOpener::default();
+    let options = BuildOptions::default();
+
+    let e = open_post(&post, &options, &opener).unwrap_err();
+    assert!(e.contains("The post has not been built yet"));
+    assert!(opener.opened.borrow().is_empty());
+
+    post.build(&options).unwrap();
+    let page = open_post(&post, &options, &opener).unwrap();
+    assert_eq!(page, post.path.join("dist/index.html"));
+    assert_eq!(*opener.opened.borrow(), [page]);
+}
+
+#[test]
+pub fn test_build_and_open() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Built preview", Some(Utc::now()));
+    let opener = RecordingOpener::default();
+
+    build_post(&mut post, &BuildOptions::default(), false, &opener).unwrap();
+    assert!(opener.opened.borrow().is_empty());
+
+    let dry_run = BuildOptions {
+        dry_run: true,
+        ..Default::default()
+    };
+    build_post(&mut post, &dry_run, true, &opener).unwrap();
+    assert!(opener.opened.borrow().is_empty());
+
+    let output = build_post(&mut post, &BuildOptions::default(), true, &opener).unwrap();
+    assert_eq!(output.index, post.path.join("dist/index.html"));
+    assert_eq!(*opener.opened.borrow(), [output.index]);
+}
@@ -0,0 +1,346 @@
+use std::fs;
+
+use chrono::{TimeZone, Utc};
+use mockito::Matcher;
+
+use crate::post::{BuildOptions, Post};
+use crate::publish::{
+    canonical_request, content_type, publish_due, signature, signing_key, GitPublisher,
+    S3Publisher, Schedule,
+};
+use crate::tests::fixture_post;
+
+fn publisher(endpoint: String) -> S3Publisher {
+    S3Publisher {
+        endpoint,
+        bucket: "bucket".to_string(),
+        region: "us-east-1".to_string(),
+        access_key: "access".to_string(),
+        secret_key: "secret".to_string(),
+        prefix: "blog".to_string(),
+        cache_control: "public, max-age=60".to_string(),
+    }
+}
+
+#[test]
+pub fn test_content_type() {
+    assert_eq!(
+        content_type(std::path::Path::new("index.html")),
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(content_type(std::path::Path::new("a.JPG")), "image/jpeg");
+    assert_eq!(
+        content_type(std::path::Path::new("archive")),
+        "application/octet-stream"
+    );
+}
+
+#[test]
+pub fn test_sigv4_vectors() {
+    // The signing key derivation example of the AWS documentation
+    assert_eq!(
+        signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam"
+        )
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>(),
+        "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+    );
+
+    // The GET object example of the S3 documentation
+    let empty = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let (request, signed_headers) = canonical_request(
+        "GET",
+        "/test.txt",
+        "",
+        &[
+            ("host", "examplebucket.s3.amazonaws.com"),
+            ("range", "bytes=0-9"),
+            ("x-amz-content-sha256", empty),
+            ("x-amz-date", "20130524T000000Z"),
+        ],
+        empty,
+    );
+    assert_eq!(signed_headers, "host;range;x-amz-content-sha256;x-amz-date");
+    assert_eq!(
+        request,
+        format!(
+            "GET\n/test.txt\n\nhost:examplebucket.s3.amazonaws.com\nrange:bytes=0-9\n\
+             x-amz-content-sha256:{empty}\nx-amz-date:20130524T000000Z\n\n\
+             host;range;x-amz-content-sha256;x-amz-date\n{empty}"
+        )
+    );
+    assert_eq!(
+        signature(
+            "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
+            "us-east-1",
+            "s3",
+            "20130524T000000Z",
+            &request
+        ),
+        (
+            "f0e8bdb87c964420e857bd35b5d6ed310bd44f0170aba48dd91039c6036bdb41".to_string(),
+            "20130524/us-east-1/s3/aws4_request".to_string()
+        )
+    );
+}
+
+#[test]
+pub fn test_s3_publish() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Published post", None);
+    fs::write(post.path.join("images/picture.png"), b"picture").unwrap();
+    post.build(&BuildOptions::default()).unwrap();
+
+    let mut server = mockito::Server::new();
+    let index = server
+        .mock("PUT", "/bucket/blog/published-post/index.html")
+        .match_header("content-type", "text/html; charset=utf-8")
+        .match_header("cache-control", "public, max-age=60")
+        .match_header(
+            "authorization",
+            Matcher::Regex("^AWS4-HMAC-SHA256 Credential=access/".to_string()),
+        )
+        .with_status(200)
+        .create();
+    let picture = server
+        .mock("PUT", "/bucket/blog/published-post/images/picture.png")
+        .match_header("content-type", "image/png")
+        .match_body("picture")
+        .with_status(200)
+        .create();
+
+    post.publish(&publisher(server.url()), &BuildOptions::default())
+        .unwrap();
+    index.assert();
+    picture.assert();
+    assert!(Post::load(&post.path)
+        .unwrap()
+        .metadata
+        .post
+        .published_date
+        .is_some());
+}
+
+#[test]
+pub fn test_publish_requires_build() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Unbuilt post", None);
+
+    let result = post.publish(
+        &publisher("http://127.0.0.1:1".to_string()),
+        &BuildOptions::default(),
+    );
+    assert!(result.unwrap_err().contains("has not been built"));
+}
+
+/// Runs git with the given arguments in the given directory
+fn git(directory: &std::path::Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+pub fn test_git_publish() {
+    let remote = tempfile::tempdir().unwrap();
+    git(remote.path(), &["init", "--bare", "-q"]);
+
+    let repo = tempfile::tempdir().unwrap();
+    git(repo.path(), &["init", "-q"]);
+    git(repo.path(), &["config", "user.name", "Blog"]);
+    git(repo.path(), &["config", "user.email", "blog@example.com"]);
+    git(
+        repo.path(),
+        &["remote", "add", "origin", remote.path().to_str().unwrap()],
+    );
+
+    // The changes pending in the repository are left out of the publication
+    fs::write(repo.path().join("unrelated.txt"), "pending").unwrap();
+
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Git post", None);
+    post.build(&BuildOptions::default()).unwrap();
+
+    let publisher = GitPublisher {
+        repo: repo.path().to_path_buf(),
+        branch: "pages".to_string(),
+        remote: "origin".to_string(),
+        message: "Publish: {title}".to_string(),
+    };
+    post.publish(&publisher, &BuildOptions::default()).unwrap();
+
+    assert!(repo.path().join("git-post/index.html").is_file());
+    let log = git(remote.path(), &["log", "--format=%s", "pages"]);
+    assert_eq!(log.trim(), "Publish: Git post");
+    let files = git(repo.path(), &["show", "--name-only", "--format=", "HEAD"]);
+    assert_eq!(files.trim(), "git-post/index.html");
+
+    // Publishing again without changes does not create an empty commit
+    post.publish(&publisher, &BuildOptions::default()).unwrap();
+    let log = git(remote.path(), &["log", "--format=%s", "pages"]);
+    assert_eq!(log.lines().count(), 1);
+
+    // Unpublishing only commits the removal of the post, leaving the other changes staged
+    git(repo.path(), &["add", "unrelated.txt"]);
+    post.unpublish(Some(&publisher), false).unwrap();
+    assert!(!repo.path().join("git-post").exists());
+    let log = git(remote.path(), &["log", "--format=%s", "pages"]);
+    assert_eq!(log.lines().next(), Some("Unpublish: Git post"));
+    let files = git(repo.path(), &["show", "--name-only", "--format=", "HEAD"]);
+    assert_eq!(files.trim(), "git-post/index.html");
+    let status = git(repo.path(), &["status", "--porcelain"]);
+    assert_eq!(status.trim(), "A  unrelated.txt");
+}
+
+#[test]
+pub fn test_unpublish() {
+    let root = tempfile::tempdir().unwrap();
+    let published = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+    let mut post = fixture_post(root.path(), "Pulled back", Some(published));
+    assert!(!post.metadata.post.is_draft());
+    post.unpublish(None, false).unwrap();
+    let post = Post::load(&post.path).unwrap();
+    assert!(post.metadata.post.is_draft());
+    assert_eq!(post.metadata.post.published_date, None);
+
+    let mut post = fixture_post(root.path(), "Kept date", Some(published));
+    post.unpublish(None, true).unwrap();
+    let mut post = Post::load(&post.path).unwrap();
+    assert!(post.metadata.post.is_draft());
+    assert_eq!(post.metadata.post.published_date, Some(published));
+
+    // Publishing the post back keeps its original date
+    post.build(&BuildOptions::default()).unwrap();
+    let mut server = mockito::Server::new();
+    server.mock("PUT", Matcher::Any).with_status(200).create();
+    post.publish(&publisher(server.url()), &BuildOptions::default())
+        .unwrap();
+    assert!(!post.metadata.post.is_draft());
+    assert_eq!(post.metadata.post.published_date, Some(published));
+
+    // The objects listed under the prefix of the post are deleted from the bucket, even
+    // when the local output is gone
+    fs::remove_dir_all(post.path.join("dist")).unwrap();
+    let first_page = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::Exact(
+            "list-type=2&prefix=blog%2Fkept-date%2F".to_string(),
+        ))
+        .match_header(
+            "authorization",
+            Matcher::Regex("^AWS4-HMAC-SHA256 Credential=access/".to_string()),
+        )
+        .with_body(
+            "<ListBucketResult><IsTruncated>true</IsTruncated>\
+             <Contents><Key>blog/kept-date/index.html</Key></Contents>\
+             <NextContinuationToken>next&amp;page</NextContinuationToken></ListBucketResult>",
+        )
+        .create();
+    let second_page = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::UrlEncoded(
+            "continuation-token".to_string(),
+            "next&page".to_string(),
+        ))
+        .with_body(
+            "<ListBucketResult><IsTruncated>false</IsTruncated>\
+             <Contents><Key>blog/kept-date/old.png</Key></Contents></ListBucketResult>",
+        )
+        .create();
+    let deleted = ["index.html", "old.png"].map(|file| {
+        server
+            .mock("DELETE", format!("/bucket/blog/kept-date/{file}").as_str())
+            .with_status(204)
+            .create()
+    });
+    post.unpublish(Some(&publisher(server.url())), true)
+        .unwrap();
+    first_page.assert();
+    second_page.assert();
+    deleted.iter().for_each(|mock| mock.assert());
+}
+
+#[test]
+pub fn test_schedule() {
+    let root = tempfile::tempdir().unwrap();
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+    let published = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    for (title, published, scheduled) in [
+        (
+            "Past due",
+            None,
+            Some(Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap()),
+        ),
+        (
+            "Future",
+            None,
+            Some(Utc.with_ymd_and_hms(2024, 7, 1, 8, 0, 0).unwrap()),
+        ),
+        ("Published", Some(published), Some(published)),
+        ("Unscheduled", None, None),
+    ] {
+        let mut post = fixture_post(root.path(), title, published);
+        post.metadata.post.scheduled_date = scheduled;
+        post.save().unwrap();
+    }
+
+    let schedule = Schedule::load(root.path(), now).unwrap();
+    let titles = |posts: &[Post]| {
+        posts
+            .iter()
+            .map(|post| post.metadata.post.title.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(titles(&schedule.due), ["Past due"]);
+    assert_eq!(titles(&schedule.pending), ["Future"]);
+}
+
+#[test]
+pub fn test_publish_due() {
+    let root = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Due post", None);
+    post.metadata.post.scheduled_date = Some(Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap());
+    post.save().unwrap();
+    let options = BuildOptions {
+        output: Some(output.path().to_path_buf()),
+        root: Some(root.path().to_path_buf()),
+        ..Default::default()
+    };
+
+    // The publication failing, the post is left a draft
+    let mut due = Schedule::load(root.path(), Utc::now()).unwrap().due;
+    let unreachable = publisher("http://127.0.0.1:1".to_string());
+    assert!(publish_due(&mut due[0], &unreachable, &options).is_err());
+    assert!(Post::load(&post.path).unwrap().metadata.post.is_draft());
+
+    // The never built draft is built into the output, then uploaded from there
+    let mut server = mockito::Server::new();
+    let index = server
+        .mock("PUT", "/bucket/blog/due-post/index.html")
+        .match_body(Matcher::Regex("<title>Due post</title>".to_string()))
+        .with_status(200)
+        .create();
+    let mut due = Schedule::load(root.path(), Utc::now()).unwrap().due;
+    publish_due(&mut due[0], &publisher(server.url()), &options).unwrap();
+    index.assert();
+
+    let relative = post.path.strip_prefix(root.path()).unwrap();
+    assert!(output.path().join(relative).join("index.html").is_file());
+    assert!(!Post::load(&post.path).unwrap().metadata.post.is_draft());
+    assert!(Schedule::load(root.path(), Utc::now())
+        .unwrap()
+        .due
+        .is_empty());
+}
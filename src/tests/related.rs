@@ -0,0 +1,50 @@
+use chrono::{TimeZone, Utc};
+
+use crate::post::Post;
+use crate::related::{related_html, related_posts};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_related_posts() {
+    let root = tempfile::tempdir().unwrap();
+    let date = |day| Some(Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap());
+
+    for (title, published, tags, keywords) in [
+        ("Current", date(20), vec!["rust", "cli"], vec!["clap"]),
+        ("Two shared", date(2), vec!["rust"], vec!["clap"]),
+        ("One shared, old", date(3), vec!["cli"], vec![]),
+        ("One shared, recent", date(10), vec!["Rust"], vec![]),
+        ("Unrelated", date(15), vec!["cooking"], vec![]),
+        ("Draft", None, vec!["rust", "cli"], vec!["clap"]),
+    ] {
+        let mut post = fixture_post(root.path(), title, published);
+        post.metadata.post.tags = tags.into_iter().map(String::from).collect();
+        post.metadata.opengraph.keywords = keywords.into_iter().map(String::from).collect();
+        post.save().unwrap();
+    }
+
+    let posts = Post::load_all(root.path()).unwrap();
+    let current = posts
+        .iter()
+        .find(|post| post.metadata.post.title == "Current")
+        .unwrap();
+
+    let titles = |count| -> Vec<&str> {
+        related_posts(current, &posts, count)
+            .into_iter()
+            .map(|post| post.metadata.post.title.as_str())
+            .collect()
+    };
+    assert_eq!(
+        titles(5),
+        ["Two shared", "One shared, recent", "One shared, old"]
+    );
+    assert_eq!(titles(2), ["Two shared", "One shared, recent"]);
+}
+
+#[test]
+pub fn test_related_html() {
+    assert_eq!(related_html(&[]), "");
+    let html = related_html(&[("A & B".to_string(), "/2024/03/a-b/".to_string())]);
+    assert!(html.contains("<li><a href=\"/2024/03/a-b/\">A &amp; B</a></li>"));
+}
@@ -0,0 +1,496 @@
+use std::fs;
+
+use chrono::{TimeZone, Utc};
+
+use crate::config::{DisplayConfig, MarkdownConfig};
+use crate::post::{BuildOptions, Post};
+use crate::render::{
+    absolute_links, expand_emoji, external_links, heading_anchors, heading_ids, heading_links,
+    json_ld, lazy_images, license_footer, strip_title, table_of_contents, Flavor, MathMode,
+};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_lazy_images() {
+    let html = lazy_images(r#"<p><img src="images/a.png" alt="A"></p><img src="b.png"/>"#);
+    assert_eq!(
+        html,
+        r#"<p><img src="images/a.png" alt="A" loading="lazy" decoding="async"></p><img src="b.png" loading="lazy" decoding="async" />"#
+    );
+
+    // Images that already choose their loading strategy are left alone
+    let eager = r#"<img src="a.png" loading="eager">"#;
+    assert_eq!(lazy_images(eager), eager);
+}
+
+#[test]
+pub fn test_lazy_images_skip_header() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Lazy images", None);
+    post.content = "# Lazy images\n\n![A diagram](images/diagram.png)".to_string();
+    fs::create_dir_all(post.path.join("images/header")).unwrap();
+    fs::write(post.path.join("images/header/header.jpg"), b"").unwrap();
+
+    let options = BuildOptions {
+        lazy_images: true,
+        ..Default::default()
+    };
+    let page = post.render_page(&options).unwrap();
+
+    assert!(page.contains(
+        r#"<img src="images/diagram.png" alt="A diagram" loading="lazy" decoding="async" />"#
+    ));
+    assert!(page.contains(
+        r#"<img class="header-image" src="images/header/header.jpg" alt="Lazy images">"#
+    ));
+}
+
+#[test]
+pub fn test_twitter_card() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Twitter & cards", None);
+    post.metadata.opengraph.description = "A description".to_string();
+    post.metadata.opengraph.opengraphimage = "https://example.com/header.jpg".to_string();
+
+    // Without header image, the twitter tags must be requested
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(page.contains(r#"<meta property="og:title" content="Twitter &amp; cards">"#));
+    assert!(!page.contains("twitter:"));
+
+    let options = BuildOptions {
+        twitter: true,
+        ..Default::default()
+    };
+    let page = post.render_page(&options).unwrap();
+    assert!(page.contains(r#"<meta name="twitter:card" content="summary_large_image">"#));
+    assert!(page.contains(r#"<meta name="twitter:title" content="Twitter &amp; cards">"#));
+    assert!(page.contains(r#"<meta name="twitter:description" content="A description">"#));
+    assert!(
+        page.contains(r#"<meta name="twitter:image" content="https://example.com/header.jpg">"#)
+    );
+
+    // Empty fields are skipped
+    post.metadata.opengraph.description = String::new();
+    let page = post.render_page(&options).unwrap();
+    assert!(!page.contains("twitter:description"));
+}
+
+#[test]
+pub fn test_json_ld() {
+    let mut post = Post::new("Structured </script> data");
+    post.metadata.post.published_date = Some(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap());
+    post.metadata.post.update = Some(Utc.with_ymd_and_hms(2024, 3, 2, 12, 0, 0).unwrap());
+    post.metadata.post.authors = vec!["Jane Doe".to_string()];
+    post.metadata.opengraph.keywords = vec!["rust".to_string(), "blog".to_string()];
+
+    let block = json_ld(&post.metadata, &post.metadata.social_image(&post.path));
+    let json = block
+        .trim()
+        .strip_prefix(r#"<script type="application/ld+json">"#)
+        .and_then(|block| block.strip_suffix("</script>"))
+        .unwrap();
+    assert!(!json.contains("</script>"));
+
+    let data: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(data["@type"], "BlogPosting");
+    assert_eq!(data["headline"], "Structured </script> data");
+    assert_eq!(data["datePublished"], "2024-03-01T12:00:00+00:00");
+    assert_eq!(data["dateModified"], "2024-03-02T12:00:00+00:00");
+    assert_eq!(data["author"]["name"], "Jane Doe");
+    assert_eq!(data["keywords"], "rust, blog");
+
+    // Every author is listed
+    post.metadata.post.authors.push("John Doe".to_string());
+    let block = json_ld(&post.metadata, "");
+    assert!(block.contains(
+        r#""author":[{"@type":"Person","name":"Jane Doe"},{"@type":"Person","name":"John Doe"}]"#
+    ));
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(page.contains(r#"<meta name="author" content="Jane Doe, John Doe">"#));
+    // Missing fields are left out
+    assert!(data.get("description").is_none());
+    assert!(data.get("image").is_none());
+}
+
+#[test]
+pub fn test_footnotes() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Footnotes", None);
+    post.content = "A claim[^source].\n\n[^source]: The source.\n".to_string();
+
+    let html = post.render_html(&BuildOptions::default()).unwrap();
+    assert!(html.contains(r##"<a href="#user-content-fn-source""##));
+    assert!(html.contains(r#"<section data-footnotes="" class="footnotes">"#));
+    assert!(html.contains(r#"id="user-content-fn-source""#));
+    assert!(html.contains(r##"<a href="#user-content-fnref-source" data-footnote-backref="" aria-label="Back to content""##));
+
+    let options = BuildOptions {
+        markdown: MarkdownConfig {
+            footnotes: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(!post.render_html(&options).unwrap().contains("footnotes"));
+}
+
+#[test]
+pub fn test_flavors() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Flavors", None);
+    post.content = "| a | b |\n|---|---|\n| 1 | 2 |\n\nSee https://example.com\n".to_string();
+
+    let html = post.render_html(&BuildOptions::default()).unwrap();
+    assert!(html.contains("<table>"));
+    assert!(html.contains(r#"<a href="https://example.com">"#));
+
+    let options = BuildOptions {
+        flavor: Flavor::Commonmark,
+        ..Default::default()
+    };
+    let html = post.render_html(&options).unwrap();
+    assert!(!html.contains("<table>"));
+    assert!(html.contains("<p>| a | b |"));
+    assert!(!html.contains("<a href"));
+}
+
+#[test]
+pub fn test_math() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Math", None);
+    post.content =
+        "Inline $a < b$ math.\n\n$$\nx^2\n$$\n\n```sh\necho $HOME $PATH\n```\n".to_string();
+
+    let server = BuildOptions {
+        math: Some(MathMode::Server),
+        ..Default::default()
+    };
+    let html = post.render_html(&server).unwrap();
+    assert!(html.contains(r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="inline">"#));
+    assert!(html.contains(r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">"#));
+    assert!(html.contains("<mo>&lt;</mo>"));
+    assert!(html.contains("<msup><mi>x</mi><mn>2</mn></msup>"));
+    assert!(html.contains("echo $HOME $PATH"));
+
+    let client = BuildOptions {
+        math: Some(MathMode::Client),
+        ..Default::default()
+    };
+    let html = post.render_html(&client).unwrap();
+    assert!(html.contains(r#"<code class="language-math math-inline">a &lt; b</code>"#));
+    assert!(html.contains(r#"<pre><code class="language-math math-display">x^2"#));
+    assert!(html.contains("echo $HOME $PATH"));
+    assert!(post.render_page(&client).unwrap().contains("katex.min.js"));
+
+    // Without the option, dollars are plain text
+    let html = post.render_html(&BuildOptions::default()).unwrap();
+    assert!(html.contains("Inline $a &lt; b$ math."));
+}
+
+#[test]
+pub fn test_expand_emoji() {
+    assert_eq!(expand_emoji("Launch :rocket:!").unwrap(), "Launch 🚀!");
+    assert_eq!(
+        expand_emoji("An :unknown_shortcode: stays").unwrap(),
+        "An :unknown_shortcode: stays"
+    );
+    assert_eq!(
+        expand_emoji("Type `:rocket:` to get :rocket:").unwrap(),
+        "Type `:rocket:` to get 🚀"
+    );
+    assert_eq!(
+        expand_emoji("```\n:rocket:\n```\n\nSee https://example.com/:rocket:/ :tada:").unwrap(),
+        "```\n:rocket:\n```\n\nSee https://example.com/:rocket:/ 🎉"
+    );
+}
+
+#[test]
+pub fn test_external_links() {
+    let base = "https://blog.example.com/posts";
+    assert_eq!(
+        external_links(r#"<a href="https://rust-lang.org/learn">Rust</a>"#, base),
+        r#"<a href="https://rust-lang.org/learn" target="_blank" rel="noopener noreferrer">Rust</a>"#
+    );
+
+    let internal = r##"<a href="https://blog.example.com/other/">Other</a> <a href="../other/">Relative</a> <a href="#top">Top</a>"##;
+    assert_eq!(external_links(internal, base), internal);
+
+    // Without a base URL, every absolute link is external
+    assert!(
+        external_links(r#"<a href="http://blog.example.com/">Blog</a>"#, "")
+            .contains("target=\"_blank\"")
+    );
+}
+
+#[test]
+pub fn test_absolute_links() {
+    let base = "https://example.com/blog";
+    let page = "https://example.com/blog/2024/05/my-post/";
+
+    assert_eq!(
+        absolute_links(r#"<img src="images/diagram.png" alt="">"#, base, page),
+        r#"<img src="https://example.com/blog/2024/05/my-post/images/diagram.png" alt="">"#
+    );
+    assert_eq!(
+        absolute_links(
+            r#"<a href="../other/">Other</a> <a href="/tags/rust/">Rust</a>"#,
+            base,
+            page
+        ),
+        r#"<a href="https://example.com/blog/2024/05/other/">Other</a> <a href="https://example.com/blog/tags/rust/">Rust</a>"#
+    );
+    assert_eq!(
+        absolute_links(r#"<img srcset="a.jpg 480w, b.jpg 960w">"#, base, page),
+        r#"<img srcset="https://example.com/blog/2024/05/my-post/a.jpg 480w, https://example.com/blog/2024/05/my-post/b.jpg 960w">"#
+    );
+
+    let untouched = r##"<a href="https://rust-lang.org/">Rust</a> <a href="#usage">Usage</a> <a href="mailto:me@example.com">Me</a> <img src="//cdn.example.com/a.png">"##;
+    assert_eq!(absolute_links(untouched, base, page), untouched);
+}
+
+#[test]
+pub fn test_absolute_page() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Absolute post", None);
+    post.content = "![Diagram](images/diagram.png) [Rust](https://rust-lang.org/)".to_string();
+    post.metadata.opengraph.opengraphimage = "images/header/header.jpg".to_string();
+
+    let options = BuildOptions {
+        root: Some(root.path().to_path_buf()),
+        base_url: "https://example.com/blog/".to_string(),
+        ..Default::default()
+    };
+    let url = post.page_url(&options).unwrap();
+    assert!(url.starts_with("https://example.com/blog/20") && url.ends_with("/absolute-post/"));
+
+    let page = post.render_page(&options).unwrap();
+    assert!(page.contains(&format!(r#"src="{url}images/diagram.png""#)));
+    assert!(page.contains(r#"href="https://rust-lang.org/""#));
+    assert!(page.contains(&format!(
+        r#"property="og:image" content="{url}images/header/header.jpg""#
+    )));
+
+    // Without a base URL, the links stay relative
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(page.contains(r#"src="images/diagram.png""#));
+    assert!(page.contains(r#"content="images/header/header.jpg""#));
+}
+
+#[test]
+pub fn test_og_image_fallback() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Header fallback", None);
+    let options = BuildOptions {
+        root: Some(root.path().to_path_buf()),
+        base_url: "https://example.com".to_string(),
+        ..Default::default()
+    };
+
+    // Without a header, there is no image to share
+    let page = post.render_page(&options).unwrap();
+    assert!(!page.contains("og:image"));
+
+    std::fs::create_dir_all(post.path.join("images/header")).unwrap();
+    std::fs::write(post.path.join("images/header/header.jpg"), b"header").unwrap();
+    let url = post.page_url(&options).unwrap();
+    let page = post.render_page(&options).unwrap();
+    assert!(page.contains(&format!(
+        r#"<meta property="og:image" content="{url}images/header/header.jpg">"#
+    )));
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(page.contains(r#"<meta property="og:image" content="images/header/header.jpg">"#));
+
+    // An explicit image is kept
+    post.metadata.opengraph.opengraphimage = "https://cdn.example.com/cover.png".to_string();
+    let page = post.render_page(&options).unwrap();
+    assert!(
+        page.contains(r#"<meta property="og:image" content="https://cdn.example.com/cover.png">"#)
+    );
+    assert!(!page.contains("images/header/header.jpg\">"));
+}
+
+#[test]
+pub fn test_description_and_canonical() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Canonical post", None);
+    post.metadata.opengraph.description = "A post about \"links\"".to_string();
+
+    let options = BuildOptions {
+        root: Some(root.path().to_path_buf()),
+        base_url: "https://example.com/blog".to_string(),
+        ..Default::default()
+    };
+    let page = post.render_page(&options).unwrap();
+    assert!(page.contains(r#"<meta name="description" content="A post about &quot;links&quot;">"#));
+    assert!(page.contains(&format!(
+        r#"<link rel="canonical" href="{}">"#,
+        post.page_url(&options).unwrap()
+    )));
+
+    // Without a base URL, there is no canonical URL
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(page.contains(r#"<meta name="description""#));
+    assert!(!page.contains("rel=\"canonical\""));
+}
+
+#[test]
+pub fn test_license_footer() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Licensed post", None);
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(!page.contains("class=\"license\""));
+
+    post.metadata.post.license = Some("cc-by-sa-4.0".to_string());
+    post.save().unwrap();
+    let post = Post::load(&post.path).unwrap();
+    assert_eq!(post.metadata.post.license.as_deref(), Some("cc-by-sa-4.0"));
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(page.contains(
+        r#"<footer class="license">This post is licensed under <a rel="license" href="https://creativecommons.org/licenses/by-sa/4.0/">CC BY-SA 4.0</a>.</footer>"#
+    ));
+
+    assert_eq!(
+        license_footer("All rights <reserved>"),
+        r#"<footer class="license">This post is licensed under All rights &lt;reserved&gt;.</footer>"#
+    );
+}
+
+#[test]
+pub fn test_strip_title() {
+    assert_eq!(strip_title("# My post\n\nHello", "My post"), "Hello");
+    // A differing heading is kept
+    assert_eq!(
+        strip_title("# Another title\n\nHello", "My post"),
+        "# Another title\n\nHello"
+    );
+    assert_eq!(
+        strip_title("Hello\n\n# My post", "My post"),
+        "Hello\n\n# My post"
+    );
+    assert_eq!(strip_title("## My post\n", "My post"), "## My post\n");
+}
+
+#[test]
+pub fn test_title_from_meta() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Titled", None);
+    post.content.push_str("\n\nHello");
+    let options = BuildOptions {
+        title_from_meta: true,
+        ..Default::default()
+    };
+
+    let page = post.render_page(&options).unwrap();
+    assert_eq!(page.matches("<h1").count(), 1);
+    assert!(page.contains("<h1>Titled</h1>"));
+    assert!(page.contains("<p>Hello</p>"));
+    assert_eq!(
+        post.render_page(&BuildOptions::default())
+            .unwrap()
+            .matches("<h1")
+            .count(),
+        1
+    );
+}
+
+#[test]
+pub fn test_microformats() {
+    let root = tempfile::tempdir().unwrap();
+    let published = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let mut post = fixture_post(root.path(), "Entry", Some(published));
+    post.metadata.post.authors = vec!["Jane Doe".to_string()];
+    post.content.push_str("\n\nHello");
+    let options = BuildOptions {
+        microformats: true,
+        ..Default::default()
+    };
+
+    let page = post.render_page(&options).unwrap();
+    assert!(page.contains("<main class=\"h-entry\">"));
+    assert!(page.contains("<h1 class=\"p-name\" id=\"entry\">Entry</h1>"));
+    assert!(page.contains("<div class=\"e-content\">\n<h1"));
+    assert!(page.contains("<span class=\"p-author h-card\">Jane Doe</span>"));
+    assert!(page.contains(
+        "<time class=\"dt-published\" datetime=\"2024-03-01T12:00:00+00:00\">2024-03-01</time>"
+    ));
+    // The structured data is still there
+    assert!(page.contains("application/ld+json"));
+
+    // The byline follows the display timezone and format
+    let page = post
+        .render_page(&BuildOptions {
+            display: DisplayConfig {
+                timezone: chrono_tz::Asia::Tokyo,
+                date_format: "%d/%m/%Y %H:%M".to_string(),
+            },
+            ..options.clone()
+        })
+        .unwrap();
+    assert!(page.contains(
+        "<time class=\"dt-published\" datetime=\"2024-03-01T12:00:00+00:00\">01/03/2024 21:00</time>"
+    ));
+
+    let page = post
+        .render_page(&BuildOptions {
+            title_from_meta: true,
+            ..options
+        })
+        .unwrap();
+    assert!(page.contains("<h1 class=\"p-name\">Entry</h1>"));
+    assert_eq!(page.matches("p-name").count(), 1);
+
+    let page = post.render_page(&BuildOptions::default()).unwrap();
+    assert!(page.contains("<main>"));
+    assert!(!page.contains("h-entry") && !page.contains("e-content"));
+}
+
+#[test]
+pub fn test_heading_ids() {
+    let ids = heading_ids(&["Setup", "Usage", "Usage", "Usage 1", "Usage!"]);
+    assert_eq!(ids, ["setup", "usage", "usage-1", "usage-1-1", "usage-2"]);
+    assert_eq!(
+        heading_ids(&["Setup", "Usage", "Usage", "Usage 1", "Usage!"]),
+        ids
+    );
+
+    let (html, headings) =
+        heading_anchors("<h1>Title</h1>\n<h2>Usage</h2>\n<h3>Notes</h3>\n<h2>Usage</h2>\n");
+    assert_eq!(
+        html,
+        "<h1 id=\"title\">Title</h1>\n<h2 id=\"usage\">Usage</h2>\n<h3 id=\"notes\">Notes</h3>\n<h2 id=\"usage-1\">Usage</h2>\n"
+    );
+    assert_eq!(
+        table_of_contents(&headings),
+        "<nav class=\"toc\">\n<ul>\n<li><a href=\"#usage\">Usage</a><ul>\n<li><a href=\"#notes\">Notes</a></li>\n</ul>\n</li>\n<li><a href=\"#usage-1\">Usage</a></li>\n</ul>\n</nav>\n"
+    );
+}
+
+#[test]
+pub fn test_heading_links() {
+    let (html, headings) =
+        heading_anchors("<h2>Usage</h2>\n<h3>The <code>--toc</code> flag</h3>\n");
+    assert_eq!(
+        heading_links(&html),
+        "<h2 id=\"usage\">Usage <a class=\"anchor\" href=\"#usage\">#</a></h2>\n<h3 id=\"the-toc-flag\">The <code>--toc</code> flag <a class=\"anchor\" href=\"#the-toc-flag\">#</a></h3>\n"
+    );
+
+    // The links of the table of contents point to the same ids
+    let toc = table_of_contents(&headings);
+    assert!(toc.contains("href=\"#usage\""));
+    assert!(toc.contains("href=\"#the-toc-flag\""));
+
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Linked headings", None);
+    post.content = "# Linked headings\n\n## First\n\n## Second\n".to_string();
+    let page = post
+        .render_page(&BuildOptions {
+            heading_links: true,
+            toc: true,
+            ..Default::default()
+        })
+        .unwrap();
+    for id in ["linked-headings", "first", "second"] {
+        assert!(page.contains(&format!("id=\"{id}\"")));
+        assert!(page.contains(&format!("<a class=\"anchor\" href=\"#{id}\">#</a>")));
+    }
+}
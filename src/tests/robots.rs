@@ -0,0 +1,25 @@
+use std::fs;
+
+use crate::config::Config;
+use crate::robots::{robots_txt, write_robots};
+
+#[test]
+pub fn test_robots() {
+    let output = tempfile::tempdir().unwrap();
+    let mut config = Config::default();
+    config.site.base_url = "https://example.com/blog/".to_string();
+
+    let path = write_robots(output.path(), &config).unwrap();
+    assert_eq!(path, output.path().join("robots.txt"));
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        "User-agent: *\nAllow: /\n\nSitemap: https://example.com/blog/sitemap.xml\n"
+    );
+
+    config.robots.disallow = vec!["/drafts/".to_string()];
+    assert!(robots_txt(&config).contains("Disallow: /drafts/\n"));
+
+    // Without a base URL, the sitemap has no absolute URL
+    config.site.base_url = String::new();
+    assert!(!robots_txt(&config).contains("Sitemap:"));
+}
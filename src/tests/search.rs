@@ -0,0 +1,94 @@
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::post::Post;
+use crate::search::{search, search_index, Field, Query};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_search() {
+    let root = tempfile::tempdir().unwrap();
+    for (title, content, tags) in [
+        (
+            "Async Rust",
+            "# Async\n\nFutures are lazy.\nThey need an executor.",
+            vec!["rust"],
+        ),
+        ("Gardening", "# Tomatoes\n\nThey need sun.", vec!["garden"]),
+        ("Rusty tools", "# Tools\n\nAn old hammer.", vec!["tools"]),
+    ] {
+        let mut post = fixture_post(root.path(), title, None);
+        post.content = content.to_string();
+        post.metadata.post.tags = tags.into_iter().map(String::from).collect();
+        post.save().unwrap();
+    }
+    let mut posts = Post::load_all(root.path()).unwrap();
+    posts.sort_by(|a, b| a.metadata.post.title.cmp(&b.metadata.post.title));
+
+    let titles = |query: &Query| -> Vec<String> {
+        search(&posts, query)
+            .into_iter()
+            .map(|result| result.title)
+            .collect()
+    };
+
+    let query = Query::new("NEED", false).unwrap();
+    assert_eq!(titles(&query), ["Async Rust", "Gardening"]);
+    let results = search(&posts, &query);
+    let found = &results[0].matches[0];
+    assert_eq!(found.field, Field::Content);
+    assert_eq!(found.line, Some(4));
+    assert_eq!(found.before, ["Futures are lazy."]);
+    assert!(found.after.is_empty());
+
+    assert_eq!(
+        titles(&Query::new("rust", false).unwrap()),
+        ["Async Rust", "Rusty tools"]
+    );
+    assert_eq!(
+        titles(&Query::new(r"\bham+er\b", true).unwrap()),
+        ["Rusty tools"]
+    );
+    // Regular expression syntax is literal without --regex
+    assert!(titles(&Query::new("ham+er", false).unwrap()).is_empty());
+
+    let mut query = Query::new("rust", false).unwrap();
+    query.tag_only = true;
+    assert_eq!(titles(&query), ["Async Rust"]);
+
+    assert!(Query::new("(", true).is_err());
+}
+
+#[test]
+pub fn test_search_index() {
+    let root = tempfile::tempdir().unwrap();
+    let published = Some(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap());
+    let mut post = fixture_post(root.path(), "Indexed", published);
+    post.content =
+        "# Indexed\n\nSome *emphasized* text with a [link](https://example.com).".to_string();
+    post.metadata.post.tags = vec!["rust".to_string()];
+    post.save().unwrap();
+    fixture_post(root.path(), "Draft", None);
+
+    let mut config = Config::default();
+    config.site.base_url = "https://example.com/blog".to_string();
+    let posts = Post::load_published(root.path()).unwrap();
+
+    let index: Value =
+        serde_json::from_str(&search_index(&posts, root.path(), &config, None).unwrap()).unwrap();
+    let entries = index.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["title"], "Indexed");
+    assert_eq!(entries[0]["url"], post.url(&config, root.path()));
+    assert_eq!(entries[0]["tags"], json!(["rust"]));
+    assert_eq!(
+        entries[0]["body"],
+        "INDEXED Some emphasized text with a link (https://example.com)."
+    );
+
+    let index: Value =
+        serde_json::from_str(&search_index(&posts, root.path(), &config, Some(12)).unwrap())
+            .unwrap();
+    assert_eq!(index[0]["body"], "INDEXED Some");
+}
@@ -0,0 +1,62 @@
+use std::fs;
+
+use crate::spell::{add_word, check, prose_words, Dictionary, Word};
+
+#[test]
+pub fn test_prose_words() {
+    let content = "---\ntitle: Frontmatter wordz\n---\n# A title\n\nSome `inline` code, see \
+                   https://exmaple.com or <span>tagz</span>.\n\n```rust\nlet typpo = 1;\n```\n\n\
+                   It's the post’s end.\n";
+    let words: Vec<(String, usize)> = prose_words(content)
+        .unwrap()
+        .into_iter()
+        .map(|Word { word, line }| (word, line))
+        .collect();
+
+    let expected = [
+        ("A", 4),
+        ("title", 4),
+        ("Some", 6),
+        ("code", 6),
+        ("see", 6),
+        ("or", 6),
+        ("tagz", 6),
+        ("It's", 12),
+        ("the", 12),
+        ("post's", 12),
+        ("end", 12),
+    ];
+    let expected: Vec<(String, usize)> = expected
+        .into_iter()
+        .map(|(word, line)| (word.to_string(), line))
+        .collect();
+    assert_eq!(words, expected);
+}
+
+#[test]
+pub fn test_spell_check() {
+    let dir = tempfile::tempdir().unwrap();
+    let dictionary = dir.path().join("words");
+    let wordlist = dir.path().join("wordlist.txt");
+    fs::write(&dictionary, "a\nthis\nis\npost\nabout\nwith\ncode\n").unwrap();
+
+    let content = "This is a post abuot Rust.\n\n```\nwith sommething\n```\n\nWith code.\n";
+    let unknown = check(content, &Dictionary::load(&dictionary, &wordlist).unwrap()).unwrap();
+    let unknown: Vec<&str> = unknown.iter().map(|word| word.word.as_str()).collect();
+    assert_eq!(unknown, ["abuot", "Rust"]);
+
+    // The terms of the project are allowed once added to the wordlist
+    assert!(add_word(&wordlist, "rust").unwrap());
+    assert!(!add_word(&wordlist, "Rust").unwrap());
+    assert!(add_word(&wordlist, "two words").is_err());
+    assert_eq!(fs::read_to_string(&wordlist).unwrap(), "rust\n");
+
+    let unknown = check(content, &Dictionary::load(&dictionary, &wordlist).unwrap()).unwrap();
+    assert_eq!(
+        unknown,
+        [Word {
+            word: "abuot".to_string(),
+            line: 1
+        }]
+    );
+}
@@ -0,0 +1,92 @@
+use chrono::{TimeZone, Utc};
+
+use crate::post::Post;
+use crate::stats::{Stats, Taxonomy, TermUsage};
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_stats() {
+    let root = tempfile::tempdir().unwrap();
+    let march = Some(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap());
+    let april = Some(Utc.with_ymd_and_hms(2024, 4, 1, 12, 0, 0).unwrap());
+
+    for (title, published, tags, words) in [
+        ("First", march, vec!["rust", "cli"], 10),
+        ("Second", march, vec!["rust"], 300),
+        ("Third", april, vec!["rust", "web"], 90),
+        ("Draft", None, vec!["cli"], 0),
+    ] {
+        let mut post = fixture_post(root.path(), title, published);
+        post.content = vec!["word"; words].join(" ");
+        post.metadata.post.tags = tags.into_iter().map(String::from).collect();
+        post.metadata.opengraph.keywords = vec!["blog".to_string()];
+        post.save().unwrap();
+    }
+
+    let stats = Stats::compute(&Post::load_all(root.path()).unwrap(), 200);
+    assert_eq!(stats.total_posts, 4);
+    assert_eq!(stats.published, 3);
+    assert_eq!(stats.drafts, 1);
+    assert_eq!(stats.total_words, 400);
+    // 1 + 2 + 1 + 1 minutes
+    assert!((stats.average_reading_time - 1.25).abs() < f64::EPSILON);
+    assert_eq!(
+        stats.tags,
+        vec![
+            ("rust".to_string(), 3),
+            ("cli".to_string(), 2),
+            ("web".to_string(), 1)
+        ]
+    );
+    assert_eq!(stats.keywords, vec![("blog".to_string(), 4)]);
+    assert_eq!(stats.posts_per_month.get("2024-03"), Some(&2));
+    assert_eq!(stats.posts_per_month.get("2024-04"), Some(&1));
+}
+
+#[test]
+pub fn test_stats_empty_blog() {
+    let root = tempfile::tempdir().unwrap();
+
+    let stats = Stats::compute(&Post::load_all(root.path()).unwrap(), 200);
+    assert_eq!(stats.total_posts, 0);
+    assert!(stats.average_reading_time.abs() < f64::EPSILON);
+    assert_eq!(stats.to_string(), "This blog has no posts yet\n");
+}
+
+#[test]
+pub fn test_taxonomy() {
+    let root = tempfile::tempdir().unwrap();
+    for (title, tags, keywords) in [
+        ("First", vec!["rust", "cli"], vec!["cargo"]),
+        ("Second", vec!["rust", "web"], vec!["cargo", "wasm"]),
+        ("Third", vec!["rust", "cli"], vec![]),
+    ] {
+        let mut post = fixture_post(root.path(), title, None);
+        post.metadata.post.tags = tags.into_iter().map(String::from).collect();
+        post.metadata.opengraph.keywords = keywords.into_iter().map(String::from).collect();
+        post.save().unwrap();
+    }
+    let posts = Post::load_all(root.path()).unwrap();
+    let usage = |term: &str, count| TermUsage {
+        term: term.to_string(),
+        count,
+        single_use: count == 1,
+    };
+
+    let taxonomy = Taxonomy::compute(&posts, true, true);
+    assert_eq!(
+        taxonomy.tags.as_deref().unwrap(),
+        [usage("rust", 3), usage("cli", 2), usage("web", 1)]
+    );
+    assert_eq!(
+        taxonomy.keywords.as_deref().unwrap(),
+        [usage("cargo", 2), usage("wasm", 1)]
+    );
+    assert!(taxonomy
+        .to_string()
+        .contains("  web                   1  (single use)\n"));
+
+    let tags_only = Taxonomy::compute(&posts, true, false);
+    assert!(tags_only.keywords.is_none());
+    assert!(!tags_only.to_string().contains("Keywords"));
+}
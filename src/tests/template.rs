@@ -0,0 +1,60 @@
+use std::fs;
+
+use crate::post::BuildOptions;
+use crate::template::Template;
+use crate::tests::fixture_post;
+
+#[test]
+pub fn test_template_partials() {
+    let templates = tempfile::tempdir().unwrap();
+    fs::write(
+        templates.path().join("template.html"),
+        "<main>{{content}}</main>\n{{> footer}}",
+    )
+    .unwrap();
+    fs::write(
+        templates.path().join("footer.html"),
+        "<footer>{{>nav}} {{license}}</footer>\n",
+    )
+    .unwrap();
+    fs::write(templates.path().join("nav.html"), "<nav>Home</nav>").unwrap();
+
+    let template = Template::from_dir(templates.path()).unwrap();
+    assert_eq!(
+        template.render(&[
+            ("content", "Hello".to_string()),
+            ("license", "CC-BY".to_string())
+        ]),
+        "<main>Hello</main>\n<footer><nav>Home</nav> CC-BY</footer>\n"
+    );
+
+    // The built pages are wrapped in the template of the directory
+    let root = tempfile::tempdir().unwrap();
+    let mut post = fixture_post(root.path(), "Templated", None);
+    let output = post
+        .build(&BuildOptions {
+            template_dir: Some(templates.path().to_path_buf()),
+            ..Default::default()
+        })
+        .unwrap();
+    let page = fs::read_to_string(output.index).unwrap();
+    assert!(page.starts_with("<main>"));
+    assert!(page.ends_with("<footer><nav>Home</nav> </footer>\n"));
+}
+
+#[test]
+pub fn test_template_include_errors() {
+    let templates = tempfile::tempdir().unwrap();
+    fs::write(templates.path().join("template.html"), "{{> header}}").unwrap();
+    fs::write(templates.path().join("header.html"), "{{> nav}}").unwrap();
+    fs::write(templates.path().join("nav.html"), "{{> header}}").unwrap();
+    assert_eq!(
+        Template::from_dir(templates.path()).err().as_deref(),
+        Some("The templates include each other: template -> header -> nav -> header")
+    );
+
+    fs::write(templates.path().join("nav.html"), "{{> missing}}").unwrap();
+    let error = Template::from_dir(templates.path()).err().unwrap();
+    assert!(error.starts_with("Failed to read template"));
+    assert!(error.contains("missing.html"));
+}
@@ -0,0 +1,76 @@
+use std::fs;
+
+use chrono::{Duration, Utc};
+
+use crate::config::PostsConfig;
+use crate::tests::fixture_post;
+use crate::validate::validate_all;
+
+#[test]
+pub fn test_validate_blog() {
+    let root = tempfile::tempdir().unwrap();
+    let mut good = fixture_post(root.path(), "Good post", Some(Utc::now()));
+    good.content = "# Good post\n\n![A cat](images/cat.png)\n".to_string();
+    good.metadata.opengraph.keywords = vec!["cats".to_string()];
+    good.metadata.opengraph.description = "A post about cats".to_string();
+    good.save().unwrap();
+    fs::write(good.path.join("images/cat.png"), b"cat").unwrap();
+
+    let config = PostsConfig::default();
+    let report = validate_all(root.path(), &config, false).unwrap();
+    assert!(report.passed());
+    assert!(report.to_string().ends_with("1 posts checked, 0 failed\n"));
+
+    let mut broken = fixture_post(root.path(), "Broken post", Some(Utc::now()));
+    broken.content = "# Broken post\n\n![A dog](images/dog.png)\n".to_string();
+    broken.metadata.post.update = Some(Utc::now() - Duration::days(1));
+    broken.metadata.post.tags = vec!["rust".to_string(), "Rust".to_string()];
+    broken.save().unwrap();
+    // A post failing to load is reported too
+    let corrupt = root.path().join("corrupt");
+    fs::create_dir_all(&corrupt).unwrap();
+    fs::write(corrupt.join("metadata.toml"), "not = [valid").unwrap();
+
+    let report = validate_all(root.path(), &config, false).unwrap();
+    assert!(!report.passed());
+    assert_eq!(report.posts.len(), 3);
+
+    let failed: Vec<_> = report.posts.iter().filter(|post| !post.passed()).collect();
+    assert_eq!(failed.len(), 2);
+    assert!(report
+        .posts
+        .iter()
+        .any(|post| post.path == good.path && post.passed()));
+
+    let broken_report = failed.iter().find(|post| post.path == broken.path).unwrap();
+    let messages: Vec<&str> = broken_report
+        .findings
+        .iter()
+        .map(|finding| finding.message.as_str())
+        .collect();
+    assert!(messages.contains(&"The post was updated before it was published"));
+    assert!(messages.contains(&"The tag `Rust` is listed more than once"));
+    assert!(messages.contains(&"Image `images/dog.png` does not exist"));
+
+    let corrupt_report = failed.iter().find(|post| post.path == corrupt).unwrap();
+    assert!(corrupt_report.error.is_some());
+    assert!(report.to_string().ends_with("3 posts checked, 2 failed\n"));
+}
+
+#[test]
+pub fn test_validate_fail_fast() {
+    let root = tempfile::tempdir().unwrap();
+    // The broken post comes first in the tree
+    let broken = fixture_post(root.path(), "A broken post", Some(Utc::now()));
+    fs::write(broken.path.join("metadata.toml"), "not = [valid").unwrap();
+    fixture_post(root.path(), "B post", Some(Utc::now()));
+    fixture_post(root.path(), "C post", Some(Utc::now()));
+
+    let config = PostsConfig::default();
+    let report = validate_all(root.path(), &config, false).unwrap();
+    assert_eq!(report.posts.len(), 3);
+    let report = validate_all(root.path(), &config, true).unwrap();
+    assert_eq!(report.posts.len(), 1);
+    assert_eq!(report.posts[0].path, broken.path);
+    assert!(!report.passed());
+}
@@ -1,8 +1,14 @@
+use std::collections::HashMap;
 use std::fs::DirBuilder;
-use std::path::Path;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 use std::{fs, io};
 
-use log::info;
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{info, warn};
+
+/// The environment variable selecting the `.env.<name>` file to load
+pub const ENV_VAR: &str = "BLOG_ENV";
 
 /// Creates a directory at the given path if it does not exist.
 pub fn create_path(path: &Path) -> Result<(), String> {
@@ -30,3 +36,247 @@ pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<
     }
     Ok(())
 }
+
+/// Recursively lists the files contained in the given directory, in a stable order.
+/// A missing directory contains no files.
+pub fn list_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = vec![];
+    if !path.is_dir() {
+        return Ok(files);
+    }
+
+    let mut entries = fs::read_dir(path)
+        .map_err(|e| format!("Failed to read directory {}: {e}", path.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read directory {}: {e}", path.display()))?;
+    entries.sort_by_key(fs::DirEntry::path);
+
+    for entry in entries {
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            files.extend(list_files(&entry_path)?);
+        } else {
+            files.push(entry_path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Expands a leading `~` in the given path to the given home directory.
+pub fn expand_home(path: &str, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Normalizes a path given by the user: expands a leading `~` to the home directory and
+/// resolves `..` components and symbolic links. A path that does not exist is only
+/// expanded.
+pub fn normalize_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let path = expand_home(&path.as_ref().to_string_lossy(), home.as_deref());
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+/// Returns the given path relative to the given root, comparing their normalized forms
+/// if needed. Returns `None` if the path is not under the root.
+pub fn relative_to<'a>(path: &'a Path, root: &Path) -> Option<&'a Path> {
+    path.strip_prefix(root)
+        .or_else(|_| path.strip_prefix(normalize_path(root)))
+        .ok()
+}
+
+/// The extensions of the files considered as images
+const IMAGE_EXTENSIONS: [&str; 9] = [
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "avif", "bmp", "ico",
+];
+
+/// Returns whether the given file is an image, judging by its extension.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Returns whether the given directory is a post, i.e. holds a `metadata.toml` file.
+pub fn is_post_dir(path: &Path) -> bool {
+    path.join("metadata.toml").is_file()
+}
+
+/// Asks the given yes/no question on `output` and reads the answer from `input`.
+/// Anything but an explicit yes is considered a no.
+pub fn confirm<R: BufRead, W: Write>(
+    mut input: R,
+    mut output: W,
+    question: &str,
+) -> Result<bool, String> {
+    write!(output, "{question} [y/N] ").map_err(|e| e.to_string())?;
+    output.flush().map_err(|e| e.to_string())?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer).map_err(|e| e.to_string())?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Asks the given question on `output`, showing the default answer between brackets, and
+/// reads the answer from `input`. An empty answer is the default one.
+pub fn prompt<R: BufRead, W: Write>(
+    mut input: R,
+    mut output: W,
+    question: &str,
+    default: &str,
+) -> Result<String, String> {
+    if default.is_empty() {
+        write!(output, "{question}: ").map_err(|e| e.to_string())?;
+    } else {
+        write!(output, "{question} [{default}]: ").map_err(|e| e.to_string())?;
+    }
+    output.flush().map_err(|e| e.to_string())?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer).map_err(|e| e.to_string())?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+/// Recursively walks the given directory and returns the paths of all the posts it
+/// contains. A post is any directory holding a `metadata.toml` file. The build output
+/// and image folders of the posts are not walked.
+pub fn find_posts(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut posts = vec![];
+    if is_post_dir(root) {
+        posts.push(root.to_path_buf());
+        return Ok(posts);
+    }
+
+    let mut entries = fs::read_dir(root)
+        .map_err(|e| format!("Failed to read directory {}: {e}", root.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read directory {}: {e}", root.display()))?;
+    entries.sort_by_key(fs::DirEntry::path);
+
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() && !entry.file_name().to_string_lossy().starts_with('.') {
+            posts.extend(find_posts(&path)?);
+        }
+    }
+
+    Ok(posts)
+}
+
+/// Loads the environment variables of the given env file or, when none is given, of
+/// `.env.<name>` when `BLOG_ENV` is set to `<name>`, falling back to `.env`. The variables
+/// already set are not overridden. Returns the path of the loaded file, if any; only the
+/// given file is required to exist.
+pub fn load_env(env_file: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    if let Some(env_file) = env_file {
+        set_env_file_vars(env_file)?;
+        return Ok(Some(env_file.to_path_buf()));
+    }
+
+    let mut candidates = vec![];
+    if let Ok(name) = std::env::var(ENV_VAR) {
+        candidates.push(PathBuf::from(format!(".env.{name}")));
+    }
+    candidates.push(PathBuf::from(".env"));
+    for (index, candidate) in candidates.iter().enumerate() {
+        if !candidate.is_file() {
+            if index + 1 < candidates.len() {
+                warn!("{} does not exist, falling back", candidate.display());
+            }
+            continue;
+        }
+        set_env_file_vars(candidate)?;
+        return Ok(Some(candidate.clone()));
+    }
+    Ok(None)
+}
+
+/// Sets the variables of the given env file that are not already set.
+fn set_env_file_vars(path: &Path) -> Result<(), String> {
+    for (name, value) in read_env_file(path)? {
+        if std::env::var_os(&name).is_none() {
+            std::env::set_var(name, value);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the variables of the given env file, without loading them into the environment.
+// `from_path_iter` is the only way dotenv gives the variables without setting them
+#[allow(deprecated)]
+pub fn read_env_file(path: &Path) -> Result<HashMap<String, String>, String> {
+    dotenv::from_path_iter(path)
+        .and_then(|variables| variables.collect())
+        .map_err(|e| format!("Failed to read env file {}: {e}", path.display()))
+}
+
+/// Parses a date written as RFC 3339 or `YYYY-MM-DD`, the latter at midnight UTC.
+pub fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        })
+        .map_err(|_| format!("Invalid date `{value}`, expected YYYY-MM-DD or an RFC 3339 date"))
+}
+
+/// Decodes the `%XX` escapes of the given URL component. Invalid escapes are kept as is.
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Returns the lowercase hexadecimal representation of the given bytes, e.g. of a hash.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Runs the given future to completion on a new runtime.
+pub fn block_on<F: std::future::Future>(future: F) -> Result<F::Output, String> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(rt.block_on(future))
+}
+
+/// Escapes the characters that have a special meaning in XML and HTML documents.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
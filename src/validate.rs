@@ -0,0 +1,129 @@
+/// This module sanity-checks a whole blog before a deploy: every post found under the root
+/// is loaded, its metadata validated and its content linted. A post that fails to load is
+/// reported like the others rather than stopping the validation.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::PostsConfig;
+use crate::lint::{self, Finding, Severity};
+use crate::post::Post;
+use crate::utils::find_posts;
+
+#[derive(Debug, Serialize)]
+/// The result of the validation of a post
+pub struct PostReport {
+    pub path: PathBuf,
+    pub findings: Vec<Finding>,
+    /// Why the post could not be validated, e.g. its metadata could not be parsed
+    pub error: Option<String>,
+}
+
+impl PostReport {
+    /// Returns whether the post was validated without errors. Warnings do not fail it.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+            && !self
+                .findings
+                .iter()
+                .any(|finding| finding.severity == Severity::Error)
+    }
+}
+
+impl fmt::Display for PostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.passed() { "ok" } else { "FAILED" };
+        writeln!(f, "{}: {status}", self.path.display())?;
+        if let Some(error) = &self.error {
+            writeln!(f, "  {error}")?;
+        }
+        for finding in &self.findings {
+            writeln!(f, "  {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+/// The result of the validation of all the posts of a blog
+pub struct Report {
+    pub posts: Vec<PostReport>,
+}
+
+impl Report {
+    /// Returns whether every post passed the validation.
+    pub fn passed(&self) -> bool {
+        self.posts.iter().all(PostReport::passed)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for post in &self.posts {
+            write!(f, "{post}")?;
+        }
+        let failed = self.posts.iter().filter(|post| !post.passed()).count();
+        writeln!(f, "{} posts checked, {failed} failed", self.posts.len())
+    }
+}
+
+/// Checks that the OpenGraph image of the post exists, when it is a local file.
+fn check_social_image(post: &Post) -> Option<Finding> {
+    let image = &post.metadata.opengraph.opengraphimage;
+    if image.is_empty() || image.contains(':') || image.starts_with('/') {
+        return None;
+    }
+    (!post.path.join(image).is_file()).then(|| {
+        Finding::new(
+            Severity::Error,
+            None,
+            format!("OpenGraph image `{image}` does not exist"),
+        )
+    })
+}
+
+/// Validates the post at the given path: its metadata, the lint checks of its content and
+/// the images it references.
+pub fn validate_post(path: &Path, config: &PostsConfig) -> PostReport {
+    let mut report = PostReport {
+        path: path.to_path_buf(),
+        findings: vec![],
+        error: None,
+    };
+    let post = match Post::load(path) {
+        Ok(post) => post,
+        Err(e) => {
+            report.error = Some(format!("Failed to load post: {e}"));
+            return report;
+        }
+    };
+
+    report.findings.extend(
+        post.metadata
+            .validate(config)
+            .into_iter()
+            .map(|problem| Finding::new(Severity::Error, None, problem)),
+    );
+    report.findings.extend(check_social_image(&post));
+    match lint::lint(&post) {
+        Ok(findings) => report.findings.extend(findings),
+        Err(e) => report.error = Some(format!("Failed to lint post: {e}")),
+    }
+    report
+}
+
+/// Validates every post found under the given root. When failing fast, the validation
+/// stops at the first post that fails it.
+pub fn validate_all(root: &Path, config: &PostsConfig, fail_fast: bool) -> Result<Report, String> {
+    let mut posts = vec![];
+    for path in find_posts(root)? {
+        let report = validate_post(&path, config);
+        let failed = !report.passed();
+        posts.push(report);
+        if fail_fast && failed {
+            break;
+        }
+    }
+    Ok(Report { posts })
+}
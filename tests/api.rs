@@ -0,0 +1,27 @@
+use std::fs;
+
+use blog::post::{BuildOptions, Post};
+
+#[test]
+pub fn test_post_lifecycle() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = Post::new("Library post");
+    post.path = root.path().join(&post.path);
+    post.content = "# Library post\n\nBuilt through the library.\n".to_string();
+    post.metadata.opengraph.description = "A post built through the library".to_string();
+    post.save().unwrap();
+
+    let mut post = Post::load(&post.path).unwrap();
+    assert_eq!(post.metadata.post.title, "Library post");
+    assert!(post.content.contains("Built through the library."));
+
+    post.build(&BuildOptions::default()).unwrap();
+    let index = fs::read_to_string(post.path.join("dist/index.html")).unwrap();
+    assert!(index.contains("<p>Built through the library.</p>"));
+    assert!(Post::load(&post.path)
+        .unwrap()
+        .metadata
+        .post
+        .update
+        .is_some());
+}
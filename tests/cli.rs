@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use blog::post::{Metadata, Post};
+
+/// Runs the binary with the given arguments in the given directory, returning its output.
+fn output(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_blog"))
+        .args(args)
+        .current_dir(dir)
+        .env_remove("PEXEL_API_KEY")
+        .output()
+        .expect("Unable to run the binary")
+}
+
+/// Runs the binary with the given arguments in the given directory, returning whether it
+/// succeeded.
+fn run(dir: &Path, args: &[&str]) -> bool {
+    output(dir, args).status.success()
+}
+
+/// Creates a post under the given root with a single header candidate, by alice.
+fn post_with_candidate(root: &Path) -> Post {
+    let mut post = Post::new("With header");
+    post.path = root.join(&post.path);
+    post.save().unwrap();
+    let candidates = Metadata::header_path(&post.path).join("candidates");
+    fs::create_dir_all(&candidates).unwrap();
+    fs::write(
+        candidates.join("header_1.toml"),
+        r#"width = 1200
+height = 600
+url = "https://www.pexels.com/photo/alice/"
+photographer = "alice"
+photographer_url = "https://www.pexels.com/@alice"
+alt = "A picture by alice"
+
+[src]
+landscape = "https://images.pexels.com/photos/alice.jpg"
+"#,
+    )
+    .unwrap();
+    fs::write(candidates.join("header_1.jpg"), b"image").unwrap();
+    post
+}
+
+#[test]
+pub fn test_header_command() {
+    let root = tempfile::tempdir().unwrap();
+    let post = post_with_candidate(root.path());
+    let path = post.path.to_str().unwrap();
+
+    assert!(run(root.path(), &["header", path, "list"]));
+    assert!(run(root.path(), &["--json", "header", path, "list"]));
+    assert!(!run(root.path(), &["header", path, "choose", "2"]));
+    assert!(run(root.path(), &["header", path, "choose", "1"]));
+    assert!(Metadata::header_exists(&post.path).is_some());
+    // The post has no keyword to search images with
+    assert!(!run(root.path(), &["header", path, "fetch", "1"]));
+}
+
+#[test]
+pub fn test_quiet_flag() {
+    let root = tempfile::tempdir().unwrap();
+    let post = post_with_candidate(root.path());
+    let path = post.path.to_str().unwrap();
+
+    // Only the progress bars are hidden, the output of the command is still printed
+    let quiet = output(root.path(), &["--quiet", "header", path, "list"]);
+    assert!(quiet.status.success());
+    assert_eq!(
+        quiet.stdout,
+        output(root.path(), &["header", path, "list"]).stdout
+    );
+    assert!(String::from_utf8_lossy(&quiet.stdout).contains("alice"));
+    assert!(run(root.path(), &["-q", "header", path, "choose", "1"]));
+    assert!(Metadata::header_exists(&post.path).is_some());
+}
+
+#[test]
+pub fn test_images_add_failure() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = Post::new("With images");
+    post.path = root.path().join(&post.path);
+    post.save().unwrap();
+    let path = post.path.to_str().unwrap();
+    fs::write(root.path().join("diagram.png"), b"image").unwrap();
+
+    assert!(run(root.path(), &["images", path, "add", "diagram.png"]));
+
+    // Adding an image of the same name again fails the command, even along a new image
+    fs::write(root.path().join("chart.png"), b"image").unwrap();
+    let added = output(
+        root.path(),
+        &["images", path, "add", "diagram.png", "chart.png"],
+    );
+    assert!(!added.status.success());
+    assert!(String::from_utf8_lossy(&added.stdout).contains("already exists"));
+    assert!(post.path.join("images/chart.png").is_file());
+
+    assert!(run(
+        root.path(),
+        &["images", path, "add", "diagram.png", "--force"]
+    ));
+}
+
+#[test]
+pub fn test_export_missing_tool() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = Post::new("Exported");
+    post.path = root.path().join(&post.path);
+    post.save().unwrap();
+    let path = post.path.to_str().unwrap();
+
+    // A missing conversion tool fails the command
+    let missing = root.path().join("missing-chromium");
+    let exported = output(
+        root.path(),
+        &[
+            "export",
+            path,
+            "pdf",
+            "--pdf-binary",
+            missing.to_str().unwrap(),
+        ],
+    );
+    assert!(!exported.status.success());
+    assert!(String::from_utf8_lossy(&exported.stdout).contains("was not found"));
+}
+
+#[test]
+pub fn test_export_text_failure() {
+    let root = tempfile::tempdir().unwrap();
+    let mut post = Post::new("Exported");
+    post.path = root.path().join(&post.path);
+    post.save().unwrap();
+    let path = post.path.to_str().unwrap();
+
+    let text = root.path().join("export.txt");
+    assert!(run(
+        root.path(),
+        &["export", path, "text", "--output", text.to_str().unwrap()]
+    ));
+    assert!(text.is_file());
+
+    // A failed write of the export fails the command
+    let unwritable = root.path().join("missing/export.txt");
+    let exported = output(
+        root.path(),
+        &[
+            "export",
+            path,
+            "text",
+            "--output",
+            unwritable.to_str().unwrap(),
+        ],
+    );
+    assert!(!exported.status.success());
+    assert!(String::from_utf8_lossy(&exported.stdout).contains("Unable to write export"));
+}